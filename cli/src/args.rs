@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use clap::{Args, Parser, Subcommand};
 
 const DEFAULT_EDITOR: &str = "vi";
@@ -5,6 +6,15 @@ const DEFAULT_EDITOR: &str = "vi";
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 pub struct CliArgs {
+    /// Path to the config file, defaults to config.toml in the data directory
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Notebook to open instead of the default one
+    #[arg(long, global = true, value_name = "NAME")]
+    pub notebook: Option<String>,
+    /// Database file to open, created if absent; overrides DIARY_DB and the config file
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "notebook")]
+    pub db: Option<PathBuf>,
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -13,6 +23,8 @@ pub struct CliArgs {
 pub enum Commands {
     #[default]
     Interactive,
+    /// Full-screen terminal interface
+    Tui,
     Import(TargetFormat),
     Export(TargetFormat),
 }
@@ -32,8 +44,10 @@ pub struct FormatArg {
     #[arg(long)]
     csv: bool,
 }
+/// 外部编辑器，配置文件中指定的优先于环境变量
 #[cfg(target_os = "linux")]
-pub fn editor() -> String {
+pub fn editor(configured: Option<&str>) -> String {
+    if let Some(editor) = configured { return editor.to_string() }
     std::env::var("VISUAL")
         .or_else(|_| std::env::var("EDITOR"))
         .unwrap_or_else(|_| {
@@ -42,7 +56,8 @@ pub fn editor() -> String {
         })
 }
 #[cfg(target_os = "windows")]
-pub fn editor() -> String {
+pub fn editor(configured: Option<&str>) -> String {
+    if let Some(editor) = configured { return editor.to_string() }
     std::env::var("VISUAL")
         .or_else(|_| std::env::var("EDITOR")).unwrap_or("notepad".to_string())
 }
@@ -52,3 +67,16 @@ impl From<&FormatArg> for diary_core::utils::io::format::Format {
         if value.csv {Self::Csv} else {Self::Json}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_is_global_and_excludes_notebook() {
+        let args = CliArgs::try_parse_from(["diary-cli", "export", "out.json", "--db", "other.db"]).unwrap();
+        assert_eq!(args.db, Some(PathBuf::from("other.db")));
+        assert!(matches!(args.command, Some(Commands::Export(_))));
+        assert!(CliArgs::try_parse_from(["diary-cli", "--db", "other.db", "--notebook", "work"]).is_err());
+    }
+}
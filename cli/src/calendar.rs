@@ -0,0 +1,210 @@
+//! `cal`命令的月历输出
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use diary_core::base::config::WeekStart;
+use diary_core::base::date::{Date, DateRange};
+use diary_core::base::error::Error;
+use diary_core::base::i18n::{month_name, weekday_short};
+use diary_core::model::{Day, Mood};
+use diary_core::t;
+use time::{Duration, Month};
+use unicode_width::UnicodeWidthStr;
+
+/// 连续写作达到该天数时加下划线标出
+const MIN_STREAK: usize = 3;
+/// 年视图中每行展示的月份数
+const MONTHS_PER_ROW: usize = 3;
+/// 单个月份块的宽度（7天 × 3字符）
+const MONTH_WIDTH: usize = 21;
+
+/// 覆盖`range`的所有月份，范围必须有上下界
+pub fn months_of(range: &DateRange) -> Result<Vec<(i32, Month)>, Error> {
+    let (Some(from), Some(to)) = (range.from, range.to) else {
+        return Err(Error::InvalidData("Calendar needs a bounded range".to_string()));
+    };
+    let mut months = Vec::new();
+    let (mut year, mut month) = (from.year(), from.month());
+    while (year, month as u8) <= (to.year(), to.month() as u8) {
+        months.push((year, month));
+        if month == Month::December { year += 1 }
+        month = month.next();
+    }
+    Ok(months)
+}
+
+/// 按月份渲染日历，每行最多`MONTHS_PER_ROW`个月，每周从`week_start`开始
+pub fn render(months: &[(i32, Month)], days: &[Day], today: Date, week_start: WeekStart) -> String {
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let by_date = days.iter().map(|d| (d.date, d)).collect::<HashMap<_, _>>();
+    let streak_days = streak_days(days);
+
+    let mut out = String::new();
+    for row in months.chunks(MONTHS_PER_ROW) {
+        let blocks = row.iter()
+            .map(|&(y, m)| render_month(y, m, &by_date, &streak_days, today, week_start, color))
+            .collect::<Vec<_>>();
+        let height = blocks.iter().map(Vec::len).max().unwrap_or_default();
+        for i in 0..height {
+            let line = blocks.iter()
+                .map(|b| b.get(i).cloned().unwrap_or_else(|| " ".repeat(MONTH_WIDTH)))
+                .collect::<Vec<_>>()
+                .join("  ");
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out.push_str(&legend(color));
+    out
+}
+
+/// 单个月份的各行，每行可见宽度均为`MONTH_WIDTH`
+fn render_month(
+    year: i32,
+    month: Month,
+    by_date: &HashMap<Date, &Day>,
+    streak_days: &[Date],
+    today: Date,
+    week_start: WeekStart,
+    color: bool,
+) -> Vec<String> {
+    let title = t!("cli.calendar.title", month = month_name(month), year = year);
+    // 星期的简称可能是全角字符，按显示宽度对齐
+    let header = (0..7).map(|i| week_start.first_day().nth_next(i))
+        .map(|wd| pad_left(weekday_short(wd), 3))
+        .collect::<String>();
+    let mut lines = vec![center(&title, MONTH_WIDTH), header];
+    let first: Date = time::Date::from_calendar_date(year, month, 1).unwrap().into();
+    let offset = week_start.days_from_start(first.weekday().number_days_from_monday()) as usize;
+    let len = time::util::days_in_month(month, year) as usize;
+
+    let mut line = "   ".repeat(offset);
+    for i in 0..len {
+        let date: Date = first.saturating_add(Duration::days(i as i64)).into();
+        let day = by_date.get(&date);
+        line.push_str(&cell(date, day.copied(), streak_days.contains(&date), date == today, color));
+        if (offset + i + 1).is_multiple_of(7) {
+            lines.push(std::mem::take(&mut line));
+        }
+    }
+    if !line.is_empty() {
+        let pad = MONTH_WIDTH - (offset + len) % 7 * 3;
+        line.push_str(&" ".repeat(pad));
+        lines.push(line);
+    }
+    lines
+}
+
+/// 按显示宽度在左侧补空格
+fn pad_left(s: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width.saturating_sub(s.width())), s)
+}
+
+/// 按显示宽度居中
+fn center(s: &str, width: usize) -> String {
+    let pad = width.saturating_sub(s.width());
+    format!("{}{}{}", " ".repeat(pad / 2), s, " ".repeat(pad - pad / 2))
+}
+
+/// 单个日期格子，可见宽度为3
+fn cell(date: Date, day: Option<&Day>, in_streak: bool, is_today: bool, color: bool) -> String {
+    if !color {
+        let marker = match (day, is_today) {
+            (_, true) => '>',
+            (Some(_), false) => '*',
+            (None, false) => ' ',
+        };
+        return format!("{}{:>2}", marker, date.day());
+    }
+    let mut codes = Vec::new();
+    match day {
+        Some(Day { mood: Some(m), .. }) => codes.push(format!("1;38;5;{}", mood_color(*m))),
+        Some(_) => codes.push("1".to_string()),
+        None => codes.push("2".to_string()),
+    }
+    if in_streak { codes.push("4".to_string()) }
+    if is_today { codes.push("7".to_string()) }
+    format!(" \x1b[{}m{:>2}\x1b[0m", codes.join(";"), date.day())
+}
+
+/// 五档心情所对应的256色编号，由红到绿
+const MOOD_COLORS: [u8; 5] = [196, 208, 226, 154, 46];
+
+fn mood_color(mood: Mood) -> u8 {
+    MOOD_COLORS[mood.level()]
+}
+
+/// 属于长度不小于`MIN_STREAK`的连续写作区间的日期
+fn streak_days(days: &[Day]) -> Vec<Date> {
+    let mut dates = days.iter().map(|d| d.date).collect::<Vec<_>>();
+    dates.sort();
+    dates.dedup();
+
+    let mut res = Vec::new();
+    let mut run: Vec<Date> = Vec::new();
+    for d in dates {
+        if run.last().is_some_and(|last| last.next_day() != Some(*d)) {
+            if run.len() >= MIN_STREAK { res.append(&mut run) }
+            run.clear();
+        }
+        run.push(d);
+    }
+    if run.len() >= MIN_STREAK { res.append(&mut run) }
+    res
+}
+
+fn legend(color: bool) -> String {
+    if !color {
+        return format!("{}\n", t!("cli.calendar.legend_plain"));
+    }
+    let moods = MOOD_COLORS.iter()
+        .map(|c| format!("\x1b[1;38;5;{}m■\x1b[0m", c))
+        .collect::<String>();
+    format!("\x1b[1mdd\x1b[0m {}  {} {} {}  \x1b[4mdd\x1b[0m {}  \x1b[7mdd\x1b[0m {}\n",
+            t!("cli.calendar.written"), t!("cli.calendar.mood"), moods, t!("cli.calendar.low_high"),
+            t!("cli.calendar.streak"), t!("cli.calendar.today"))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+
+    fn month(week_start: WeekStart) -> Vec<String> {
+        // 2025年6月1日是周日
+        let today = date!(2025-06-30).into();
+        render_month(2025, Month::June, &HashMap::new(), &[], today, week_start, false)
+    }
+
+    #[test]
+    fn month_starts_on_monday() {
+        let lines = month(WeekStart::Monday);
+        assert!(lines[1].trim_start().starts_with(weekday_short(time::Weekday::Monday)));
+        assert_eq!(lines[2], format!("{}  1", " ".repeat(18)));
+        assert_eq!(lines.len(), 8);
+    }
+
+    #[test]
+    fn month_starts_on_sunday() {
+        let lines = month(WeekStart::Sunday);
+        assert!(lines[1].trim_start().starts_with(weekday_short(time::Weekday::Sunday)));
+        assert!(lines[2].starts_with("  1  2"));
+        assert_eq!(lines.len(), 7);
+        assert!(lines.iter().skip(1).all(|l| l.width() == MONTH_WIDTH));
+    }
+
+    #[test]
+    fn months_across_a_year() {
+        let range = DateRange::new(Some(date!(2024-11-15).into()), Some(date!(2025-02-01).into()));
+        assert_eq!(months_of(&range).unwrap(), [(2024, Month::November), (2024, Month::December), (2025, Month::January), (2025, Month::February)]);
+        assert!(months_of(&DateRange::new(None, Some(date!(2025-02-01).into()))).is_err());
+    }
+
+    #[test]
+    fn only_long_runs_are_streaks() {
+        let days = [date!(2024-12-30), date!(2024-12-31), date!(2025-01-01), date!(2025-01-01), date!(2025-01-03), date!(2025-01-04)]
+            .map(|d| Day { date: d.into(), ..Default::default() });
+        let streak = streak_days(&days).into_iter().map(|d| *d).collect::<Vec<_>>();
+        assert_eq!(streak, [date!(2024-12-30), date!(2024-12-31), date!(2025-01-01)]);
+    }
+}
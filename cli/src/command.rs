@@ -1,21 +1,149 @@
 use std::str::FromStr;
 use diary_core::base::command::Command;
+use diary_core::base::config::Config;
+use diary_core::base::date::{Date, DateRange};
 use diary_core::base::env::version;
 use diary_core::base::executor::Executor;
 use diary_core::base::error::Error;
-use diary_core::model::Day;
+use diary_core::model::{Day, Frequency, MetricKind};
+use diary_core::t;
+use diary_core::utils::onthisday::Span;
 use crate::error::CliError;
+use crate::calendar::{self, months_of};
+use crate::stats::print_statistics;
 
 #[derive(Debug)]
 pub enum CliCommand {
     Command(Command),
+    Stats(DateRange),
+    Calendar(DateRange),
+    OnThisDay(Date, Span),
+    /// 切换到另一个笔记本
+    Use(String),
+    Notebook(NotebookAction),
+    /// 设置或清除某天（默认今天）的指标
+    SetMetric(Option<Date>, String, Option<String>),
+    Metric(MetricAction),
+    Habit(HabitAction),
     Version,
     Help,
     Quit,
 }
 
+/// 命令第一个参数的种类，用于补全与高亮
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ArgKind {
+    None,
+    Date,
+    Range,
+    /// 日期或`day`/`week`/`month`范围
+    DateOrSpan,
+    Text,
+    Notebook,
+}
+
+/// `notebook`命令的子操作
+#[derive(Debug)]
+pub enum NotebookAction {
+    List,
+    Create(String),
+    Rename(String, String),
+    Delete(String),
+    SetDefault(String),
+}
+impl FromStr for NotebookAction {
+    type Err = CliError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            [] | ["list" | "ls"] => Ok(NotebookAction::List),
+            ["create" | "new", name] => Ok(NotebookAction::Create(name.to_string())),
+            ["rename" | "mv", from, to] => Ok(NotebookAction::Rename(from.to_string(), to.to_string())),
+            ["delete" | "rm", name] => Ok(NotebookAction::Delete(name.to_string())),
+            ["default", name] => Ok(NotebookAction::SetDefault(name.to_string())),
+            _ => Err(CliError::InvalidArgs(format!("notebook {}", s.trim()))),
+        }
+    }
+}
+
+/// `metric`命令的子操作
+#[derive(Debug)]
+pub enum MetricAction {
+    List,
+    /// 名称、类型与单位
+    Add(String, MetricKind, String),
+    Remove(String),
+}
+impl FromStr for MetricAction {
+    type Err = CliError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            [] | ["list" | "ls"] => Ok(MetricAction::List),
+            ["add" | "define", name, kind, unit @ ..] => {
+                Ok(MetricAction::Add(name.to_string(), kind.parse()?, unit.join(" ")))
+            }
+            ["add" | "define", name] => Ok(MetricAction::Add(name.to_string(), MetricKind::Number, String::new())),
+            ["remove" | "rm" | "delete", name] => Ok(MetricAction::Remove(name.to_string())),
+            _ => Err(CliError::InvalidArgs(format!("metric {}", s.trim()))),
+        }
+    }
+}
+
+/// `habit`命令的子操作，日期缺省时为今天
+#[derive(Debug)]
+pub enum HabitAction {
+    List,
+    Add(String, Frequency),
+    Remove(String),
+    /// 标记完成，最后一项为`false`时取消
+    Check(String, Option<Date>, bool),
+}
+impl FromStr for HabitAction {
+    type Err = CliError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        let date = |d: Option<&&str>| d.map(|d| d.parse::<Date>()).transpose();
+        match words.as_slice() {
+            [] | ["list" | "ls"] => Ok(HabitAction::List),
+            ["add" | "new", name] => Ok(HabitAction::Add(name.to_string(), Frequency::Daily)),
+            ["add" | "new", name, frequency] => Ok(HabitAction::Add(name.to_string(), frequency.parse()?)),
+            ["remove" | "rm" | "delete", name] => Ok(HabitAction::Remove(name.to_string())),
+            ["done" | "check", name, rest @ ..] if rest.len() <= 1 => Ok(HabitAction::Check(name.to_string(), date(rest.first())?, true)),
+            ["undo" | "uncheck", name, rest @ ..] if rest.len() <= 1 => Ok(HabitAction::Check(name.to_string(), date(rest.first())?, false)),
+            _ => Err(CliError::InvalidArgs(format!("habit {}", s.trim()))),
+        }
+    }
+}
+
+/// 除核心命令外，CLI额外支持的命令名、参数写法及参数种类
+pub const CLI_COMMANDS: &[(&str, &str, ArgKind)] = &[
+    ("stats", "[range]", ArgKind::Range),
+    ("stat", "[range]", ArgKind::Range),
+    ("cal", "[month]", ArgKind::Range),
+    ("calendar", "[month]", ArgKind::Range),
+    ("onthisday", "[date] [day|week|month]", ArgKind::DateOrSpan),
+    ("otd", "[date] [day|week|month]", ArgKind::DateOrSpan),
+    ("history", "[keyword]", ArgKind::Text),
+    ("use", "<notebook>", ArgKind::Notebook),
+    ("notebook", "[list|create|rename|delete|default] [name]", ArgKind::Text),
+    ("nb", "[list|create|rename|delete|default] [name]", ArgKind::Text),
+    ("set", "[date] <metric> <value>", ArgKind::Text),
+    ("unset", "[date] <metric>", ArgKind::Text),
+    ("metric", "[list|add|remove] [name] [number|bool|text] [unit]", ArgKind::Text),
+    ("habit", "[list|add|remove|done|undo] [name] [daily|<n>/week|date]", ArgKind::Text),
+    ("hist", "[keyword]", ArgKind::Text),
+    ("help", "", ArgKind::None),
+    ("h", "", ArgKind::None),
+    ("version", "", ArgKind::None),
+    ("v", "", ArgKind::None),
+    ("quit", "", ArgKind::None),
+    ("exit", "", ArgKind::None),
+    ("q", "", ArgKind::None),
+];
+
 impl CliCommand {
-    pub async fn exec(&self, exec: &mut Executor) -> Result<Vec<Day>, CliError> {
+    pub async fn exec(&self, exec: &mut Executor, config: &Config) -> Result<Vec<Day>, CliError> {
         match self {
             CliCommand::Command(comm) => exec.exec(comm).await
                 .map_err(|e| e.into()),
@@ -23,6 +151,40 @@ impl CliCommand {
                 self.handle_help();
                 Ok(Vec::new())
             },
+            CliCommand::Stats(range) => {
+                let stats = exec.conn_mut().statistics(*range, config.week_start).await?;
+                print_statistics(&stats);
+                Ok(Vec::new())
+            },
+            CliCommand::Calendar(range) => {
+                let months = months_of(range)?;
+                // 补齐首尾月份，使整月都能显示
+                let (&(fy, fm), &(ty, tm)) = (months.first().unwrap(), months.last().unwrap());
+                let from = DateRange::month(fy, fm)?.start();
+                let to = DateRange::month(ty, tm)?.end();
+                let days = exec.conn_mut().read_from_to(from, to).await?;
+                print!("{}", calendar::render(&months, &days, Date::native_time(), config.week_start));
+                Ok(Vec::new())
+            },
+            CliCommand::OnThisDay(date, span) => {
+                let days = exec.conn_mut().on_this_day(*date, *span).await?;
+                if days.is_empty() {
+                    println!("{}", t!("cli.onthisday.nothing", date = **date));
+                }
+                let mut year = None;
+                for (n, day) in &days {
+                    if year != Some(*n) {
+                        year = Some(*n);
+                        let key = if *n == 1 { "cli.onthisday.year_one" } else { "cli.onthisday.year_other" };
+                        println!("\n── {} ──", t!(key, year = date.year() - n, n = n));
+                    }
+                    println!("{}", day);
+                }
+                Ok(Vec::new())
+            },
+            // 笔记本相关的命令需要替换数据库连接，指标与习惯相关的命令需要配置中的日期格式与一周的第一天，由`CliExecutor`处理
+            CliCommand::Use(_) | CliCommand::Notebook(_)
+            | CliCommand::SetMetric(..) | CliCommand::Metric(_) | CliCommand::Habit(_) => Ok(Vec::new()),
             CliCommand::Version => {
                 self.handle_version();
                 Ok(Vec::new())
@@ -36,15 +198,7 @@ impl CliCommand {
                  version());
     }
     fn handle_help(&self) {
-        println!(r#"
-Available commands:
-  add <date> [context]  - Add or edit an entry with context appened at the last
-        (e.g., add today, add 20251225, add ye1225, add m25)
-  remove <date>         - Delete an entry
-  check <date>          - View a specific entry
-  list                  - List all entries
-  quit/exit             - Close the application
-"#);
+        println!("\n{}", t!("cli.help"));
     }
 }
 impl FromStr for CliCommand {
@@ -54,7 +208,43 @@ impl FromStr for CliCommand {
         match res {
             Ok(cmd) => Ok(CliCommand::Command(cmd)),
             Err(Error::UnknownCommand(cmd)) => {
+                let rest = s.trim().split_once(char::is_whitespace)
+                    .map(|t| t.1)
+                    .unwrap_or_default();
                 match cmd.as_str() {
+                    "stats" | "stat" => Ok(CliCommand::Stats(rest.parse()?)),
+                    "cal" | "calendar" => {
+                        let range = if rest.trim().is_empty() { "month" } else { rest };
+                        Ok(CliCommand::Calendar(range.parse()?))
+                    },
+                    "onthisday" | "otd" => {
+                        let (mut date, mut span) = (Date::native_time(), Span::default());
+                        for word in rest.split_whitespace() {
+                            match word.parse::<Span>() {
+                                Ok(s) => span = s,
+                                Err(_) => date = word.parse()?,
+                            }
+                        }
+                        Ok(CliCommand::OnThisDay(date, span))
+                    },
+                    "use" => match rest.trim() {
+                        "" => Err(CliError::InvalidArgs("use <notebook>".to_string())),
+                        name => Ok(CliCommand::Use(name.to_string())),
+                    },
+                    "notebook" | "nb" => Ok(CliCommand::Notebook(rest.parse()?)),
+                    "set" => {
+                        let (date, rest) = leading_date(rest, 3);
+                        match rest.split_once(char::is_whitespace) {
+                            Some((name, value)) => Ok(CliCommand::SetMetric(date, name.to_string(), Some(value.trim().to_string()))),
+                            None => Err(CliError::InvalidArgs("set [date] <metric> <value>".to_string())),
+                        }
+                    },
+                    "unset" => match leading_date(rest, 2) {
+                        (_, "") => Err(CliError::InvalidArgs("unset [date] <metric>".to_string())),
+                        (date, name) => Ok(CliCommand::SetMetric(date, name.to_string(), None)),
+                    },
+                    "metric" => Ok(CliCommand::Metric(rest.parse()?)),
+                    "habit" => Ok(CliCommand::Habit(rest.parse()?)),
                     "help" | "h" => Ok(CliCommand::Help),
                     "quit" | "exit" | "q" => Ok(CliCommand::Quit),
                     "version" | "v" => Ok(CliCommand::Version),
@@ -64,4 +254,17 @@ impl FromStr for CliCommand {
             Err(e) => Err(e.into()),
         }
     }
-}
\ No newline at end of file
+}
+
+/// 参数不少于`min_words`个且第一个能解析为日期时，把它作为日期取出
+fn leading_date(rest: &str, min_words: usize) -> (Option<Date>, &str) {
+    let rest = rest.trim();
+    // 非ASCII的文字会被解析为今天，不能作为日期
+    if rest.split_whitespace().count() >= min_words
+        && let Some((first, tail)) = rest.split_once(char::is_whitespace)
+        && first.is_ascii()
+        && let Ok(date) = first.parse::<Date>() {
+        return (Some(date), tail.trim());
+    }
+    (None, rest)
+}
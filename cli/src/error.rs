@@ -1,4 +1,5 @@
 use diary_core::base::error::Error;
+use diary_core::t;
 
 pub enum CliError {
     UnknownCommand(String),
@@ -15,4 +16,14 @@ impl From<std::io::Error> for CliError {
     fn from(err: std::io::Error) -> Self {
         CliError::Io(err)
     }
-}
\ No newline at end of file
+}
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownCommand(s) => write!(f, "{}", t!("cli.error.unknown_command", command = s)),
+            CliError::InvalidArgs(s) => write!(f, "{}", t!("cli.error.invalid_args", args = s)),
+            CliError::Io(e) => write!(f, "{}", t!("cli.error.io", error = e)),
+            CliError::Quit => write!(f, "Quit"),
+        }
+    }
+}
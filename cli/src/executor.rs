@@ -1,43 +1,251 @@
-use diary_core::base::command::Command;
+use std::io::Write;
+use unicode_width::UnicodeWidthStr;
+use diary_core::base::command::{Command, SubCommand};
+use diary_core::base::config::Config;
+use diary_core::base::date::Date;
 use diary_core::base::executor::Executor;
-use crate::command::CliCommand;
+use diary_core::base::notebook::Notebooks;
+use diary_core::model::{Frequency, Habit, MetricDef, MetricValue};
+use diary_core::utils::habits::DEFAULT_WINDOW;
+use diary_core::utils::template::Templates;
+use diary_core::t;
+use crate::args;
+use crate::command::{CliCommand, HabitAction, MetricAction, NotebookAction};
 use crate::error::CliError;
 use crate::terminal::edit_with_editor;
 
-pub struct CliExecutor {
-    pub(crate) exec: Executor,
+pub struct CliExecutor<'a> {
+    pub(crate) exec: &'a mut Executor,
+    /// 最后一次查看或编辑的日期，`check`不带参数时使用
+    pub(crate) last_viewed: Option<Date>,
+    pub(crate) config: Config,
+    /// 当前打开的笔记本，直接打开数据库文件时为`None`
+    pub(crate) notebook: Option<String>,
 }
-impl CliExecutor {
+impl<'a> CliExecutor<'a> {
+    pub fn new(exec: &'a mut Executor, config: Config) -> Self {
+        Self { exec, last_viewed: None, config, notebook: None }
+    }
+    /// 编辑日记所用的外部编辑器
+    pub fn editor(&self) -> String {
+        args::editor(self.config.editor.as_deref())
+    }
     pub async fn exec_command(&mut self, comm: &str) -> Result<(), CliError> {
         let mut command = comm.parse::<CliCommand>()?;
+        match command {
+            CliCommand::Use(name) => return self.use_notebook(&name).await,
+            CliCommand::Notebook(action) => return self.exec_notebook(action).await,
+            CliCommand::SetMetric(date, name, value) => return self.set_metric(date, &name, value.as_deref()).await,
+            CliCommand::Metric(action) => return self.exec_metric(action).await,
+            CliCommand::Habit(action) => return self.exec_habit(action).await,
+            _ => (),
+        }
+        // `check`不带日期时，接着查看上次的日期
+        let mut words = comm.split_whitespace();
+        let bare = words.next().and_then(|w| w.parse::<SubCommand>().ok()).is_some() && words.next().is_none();
+        if bare && let CliCommand::Command(Command::Check(date)) = &mut command
+            && let Some(last) = self.last_viewed {
+            *date = last;
+        }
         if let CliCommand::Command(Command::Add(date, ctx)) = &mut command {
             // 使用add命令时，查询当天已经写过的数据
             let the_day = self.exec.conn_mut().read_day(*date).await?;
+            // 还没有写过的日期从模板开始
+            let template = match the_day {
+                Some(_) => None,
+                None => self.exec.conn_mut()
+                    .render_template(&Templates::default(), self.notebook.as_deref(), *date).await?,
+            };
 
-            let mut day_ins = the_day.map(|t| t.event.instruct).unwrap_or_default();
+            let mut day_ins = the_day.map(|t| t.event.instruct)
+                .or_else(|| template.clone())
+                .unwrap_or_default();
             // 如果在命令行中写了其它内容，追加到之前日记的后面
             if let Some(ctx) = ctx.as_deref() && !ctx.is_empty() {
                 day_ins.push('\n');
                 day_ins.push_str(ctx);
             }
             let subfix = date.to_string();
-            let s = edit_with_editor(&day_ins, subfix);
-            *ctx = Some(s?);
+            let s = edit_with_editor(&self.editor(), &day_ins, subfix)?;
+            if template.is_some_and(|t| t.trim() == s.trim()) {
+                println!("{}", t!("cli.template_unchanged"));
+                return Ok(());
+            }
+            *ctx = Some(s);
         }
-        let res = command.exec(&mut self.exec).await?;
+        let res = command.exec(self.exec, &self.config).await?;
         // 
         match command {
-            CliCommand::Command(Command::Check(_)) => res.iter().for_each(|v| println!("{}", v.event)),
-            CliCommand::Command(Command::ListAll) => res.iter().for_each(|x| println!("{}", x)),
+            CliCommand::Command(Command::Check(date)) => {
+                self.last_viewed = Some(date);
+                res.iter().for_each(|v| println!("{}", v.event))
+            },
+            CliCommand::Command(Command::Add(date, _)) => self.last_viewed = Some(date),
+            CliCommand::Command(Command::ListAll) => res.iter().for_each(|x| println!("{}", x.display_with(&self.config))),
             _ => (),
         }
         Ok(())
     }
 
 }
+impl CliExecutor<'_> {
+    /// 关闭当前的数据库并打开另一个笔记本
+    async fn use_notebook(&mut self, name: &str) -> Result<(), CliError> {
+        let db = Notebooks::default().open(name).await?;
+        *self.exec = Executor::from(db);
+        self.notebook = Some(name.to_string());
+        self.last_viewed = None;
+        println!("{}", t!("cli.notebook.using", name = name));
+        Ok(())
+    }
+    async fn exec_notebook(&mut self, action: NotebookAction) -> Result<(), CliError> {
+        let notebooks = Notebooks::default();
+        match action {
+            NotebookAction::List => {
+                let default = notebooks.default_notebook();
+                for name in notebooks.list()? {
+                    let current = if self.notebook.as_ref() == Some(&name) { "*" } else { " " };
+                    if name == default {
+                        println!("{} {} ({})", current, name, t!("cli.notebook.default"));
+                    } else {
+                        println!("{} {}", current, name);
+                    }
+                }
+            }
+            NotebookAction::Create(name) => {
+                notebooks.create(&name).await?;
+                println!("{}", t!("cli.notebook.created", name = name));
+            }
+            NotebookAction::Rename(from, to) => {
+                notebooks.rename(&from, &to)?;
+                if self.notebook.as_ref() == Some(&from) {
+                    self.notebook = Some(to.clone());
+                }
+                println!("{}", t!("cli.notebook.renamed", from = from, to = to));
+            }
+            NotebookAction::Delete(name) => {
+                if self.notebook.as_ref() == Some(&name) {
+                    return Err(CliError::InvalidArgs(t!("cli.notebook.delete_in_use").to_string()));
+                }
+                if !notebooks.exists(&name) {
+                    return Err(CliError::InvalidArgs(t!("cli.notebook.no_such", name = name)));
+                }
+                print!("{} [y/N] ", t!("cli.notebook.confirm_delete", name = name));
+                std::io::stdout().flush()?;
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if answer.trim().eq_ignore_ascii_case("y") {
+                    notebooks.delete(&name)?;
+                    println!("{}", t!("cli.notebook.deleted", name = name));
+                }
+            }
+            NotebookAction::SetDefault(name) => {
+                notebooks.set_default(&name)?;
+                println!("{}", t!("cli.notebook.now_default", name = name));
+            }
+        }
+        Ok(())
+    }
+    /// 设置或清除某天（没有指定时为今天）的指标，未定义的指标按取值推断类型后定义
+    async fn set_metric(&mut self, date: Option<Date>, name: &str, value: Option<&str>) -> Result<(), CliError> {
+        let date = date.unwrap_or_else(Date::native_time);
+        let conn = self.exec.conn_mut();
+        let def = match (conn.metric_defs().await?.into_iter().find(|d| d.name == name), value) {
+            (Some(def), _) => def,
+            (None, Some(value)) => {
+                let def = MetricDef::new(name, MetricValue::infer(value).kind(), "")?;
+                conn.define_metric(&def).await?;
+                println!("{}", t!("cli.metric.defined", name = name, kind = def.kind.label()));
+                def
+            }
+            (None, None) => return Err(CliError::InvalidArgs(t!("cli.metric.no_such", name = name))),
+        };
+        let value = value.map(|v| def.kind.parse(v)).transpose()?;
+        let day = conn.set_metric(date, name, value).await?;
+        let date = self.config.format_date(date);
+        match day.metrics.get(name) {
+            Some(v) => println!("{}", t!("cli.metric.set", name = name, value = def.format(v), date = date)),
+            None => println!("{}", t!("cli.metric.unset", name = name, date = date)),
+        }
+        self.last_viewed = Some(day.date);
+        Ok(())
+    }
+    async fn exec_metric(&mut self, action: MetricAction) -> Result<(), CliError> {
+        let conn = self.exec.conn_mut();
+        match action {
+            MetricAction::List => {
+                let defs = conn.metric_defs().await?;
+                if defs.is_empty() {
+                    println!("{}", t!("cli.metric.none"));
+                }
+                let width = defs.iter().map(|d| d.name.width()).max().unwrap_or_default();
+                for def in defs {
+                    let pad = " ".repeat(width - def.name.width());
+                    println!("  {}{}  {}  {}", def.name, pad, def.kind.label(), def.unit);
+                }
+            }
+            MetricAction::Add(name, kind, unit) => {
+                conn.define_metric(&MetricDef::new(&name, kind, &unit)?).await?;
+                println!("{}", t!("cli.metric.defined", name = name, kind = kind.label()));
+            }
+            MetricAction::Remove(name) => {
+                let n = conn.remove_metric(&name).await?;
+                println!("{}", t!("cli.metric.removed", name = name, n = n));
+            }
+        }
+        Ok(())
+    }
+    async fn exec_habit(&mut self, action: HabitAction) -> Result<(), CliError> {
+        let today = Date::native_time();
+        let conn = self.exec.conn_mut();
+        match action {
+            HabitAction::List => {
+                let progress = conn.habit_progress(today, DEFAULT_WINDOW, self.config.week_start).await?;
+                if progress.is_empty() {
+                    println!("{}", t!("cli.habit.none"));
+                    return Ok(());
+                }
+                // 标出今天完成的习惯
+                let done = conn.read_day(today).await?.map(|d| d.habits).unwrap_or_default();
+                println!("{}", t!("cli.habit.header", date = self.config.format_date(today), days = DEFAULT_WINDOW));
+                let labels = progress.iter().map(|p| p.habit.frequency.label()).collect::<Vec<_>>();
+                let name_width = progress.iter().map(|p| p.habit.name.width()).max().unwrap_or_default();
+                let label_width = labels.iter().map(|l| l.width()).max().unwrap_or_default();
+                for (p, label) in progress.iter().zip(labels) {
+                    let mark = if done.contains(&p.habit.name) { "✓" } else { " " };
+                    let unit = match p.habit.frequency {
+                        Frequency::Daily => t!("cli.habit.days"),
+                        Frequency::Weekly(_) => t!("cli.habit.weeks"),
+                    };
+                    println!("  [{}] {}{}  {}{}  {}", mark,
+                             p.habit.name, " ".repeat(name_width - p.habit.name.width()),
+                             label, " ".repeat(label_width - label.width()),
+                             t!("cli.habit.progress", current = p.current_streak, longest = p.longest_streak,
+                                unit = unit, rate = format!("{:.0}", p.rate() * 100.)));
+                }
+            }
+            HabitAction::Add(name, frequency) => {
+                conn.define_habit(&Habit::new(&name, frequency)?).await?;
+                println!("{}", t!("cli.habit.added", name = name, frequency = frequency.label()));
+            }
+            HabitAction::Remove(name) => {
+                let n = conn.remove_habit(&name).await?;
+                println!("{}", t!("cli.habit.removed", name = name, n = n));
+            }
+            HabitAction::Check(name, date, done) => {
+                let date = date.unwrap_or(today);
+                conn.check_habit(date, &name, done).await?;
+                let key = if done { "cli.habit.done" } else { "cli.habit.undone" };
+                println!("{}", t!(key, name = name, date = self.config.format_date(date)));
+                self.last_viewed = Some(date);
+            }
+        }
+        Ok(())
+    }
+}
 
-impl From<Executor> for CliExecutor {
-    fn from(exec: Executor) -> Self {
-        Self { exec }
+impl<'a> From<&'a mut Executor> for CliExecutor<'a> {
+    fn from(exec: &'a mut Executor) -> Self {
+        Self::new(exec, Config::default())
     }
 }
\ No newline at end of file
@@ -0,0 +1,238 @@
+//! 交互模式下的补全、提示与高亮
+use std::borrow::Cow;
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::{Hint, Hinter};
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use diary_core::base::command::SubCommand;
+use diary_core::base::date::{Date, DateRange, DATE_FORMAT1};
+use diary_core::utils::onthisday::Span;
+use crate::command::{ArgKind, CLI_COMMANDS};
+
+const RED: &str = "\x1b[31m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// 行首命令的解析结果：(参数写法, 参数种类)
+fn lookup(name: &str) -> Option<(&'static str, ArgKind)> {
+    if let Ok(sub) = name.parse::<SubCommand>() {
+        let kind = if sub.takes_date() { ArgKind::Date } else { ArgKind::None };
+        return Some((sub.syntax(), kind));
+    }
+    CLI_COMMANDS.iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, syntax, kind)| (*syntax, *kind))
+}
+
+fn command_names() -> impl Iterator<Item = &'static str> {
+    SubCommand::ALIASES.iter().map(|t| t.0)
+        .chain(CLI_COMMANDS.iter().map(|t| t.0))
+}
+
+/// 行内提示，命令语法说明不可被接受为输入
+pub struct CommandHint {
+    display: String,
+    completion: Option<String>,
+}
+impl Hint for CommandHint {
+    fn display(&self) -> &str {
+        &self.display
+    }
+    fn completion(&self) -> Option<&str> {
+        self.completion.as_deref()
+    }
+}
+
+/// `rustyline`的辅助器，负责补全命令、日期以及输入时的提示与高亮
+#[derive(Default)]
+pub struct DiaryHelper {
+    /// 已有记录的日期，用于补全
+    dates: Vec<String>,
+    /// 已有的笔记本名，用于补全`use`
+    notebooks: Vec<String>,
+}
+impl DiaryHelper {
+    pub fn set_dates(&mut self, dates: Vec<Date>) {
+        self.dates = dates.iter()
+            .rev()
+            .filter_map(|d| d.format(DATE_FORMAT1).ok())
+            .collect();
+    }
+    pub fn set_notebooks(&mut self, notebooks: Vec<String>) {
+        self.notebooks = notebooks;
+    }
+}
+
+impl Completer for DiaryHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+        let prefix = &line[start..];
+        let words_before = line[..start].split_whitespace().collect::<Vec<_>>();
+
+        let candidates: Vec<String> = match words_before.as_slice() {
+            [] => command_names()
+                .filter(|n| n.starts_with(prefix))
+                .map(str::to_string)
+                .collect(),
+            [cmd] => {
+                let keywords = match lookup(cmd) {
+                    Some((_, ArgKind::Date)) => Date::KEYWORDS.to_vec(),
+                    Some((_, ArgKind::Range)) => DateRange::KEYWORDS.to_vec(),
+                    Some((_, ArgKind::DateOrSpan)) => [Span::KEYWORDS, Date::KEYWORDS].concat(),
+                    Some((_, ArgKind::Notebook)) => {
+                        let names = self.notebooks.iter()
+                            .filter(|n| n.starts_with(prefix))
+                            .map(|n| Pair { display: n.clone(), replacement: n.clone() })
+                            .collect();
+                        return Ok((start, names));
+                    }
+                    _ => return Ok((start, Vec::new())),
+                };
+                keywords.iter()
+                    .map(|k| k.to_string())
+                    .chain(self.dates.iter().cloned())
+                    .filter(|c| c.starts_with(prefix))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        let pairs = candidates.into_iter()
+            .map(|c| Pair { display: c.clone(), replacement: format!("{} ", c) })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for DiaryHelper {
+    type Hint = CommandHint;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<CommandHint> {
+        if pos < line.len() || line.is_empty() { return None }
+        let mut words = line.split_whitespace();
+        let first = words.next()?;
+        let ends_with_space = line.ends_with(char::is_whitespace);
+
+        if !ends_with_space && words.next().is_none() {
+            // 正在输入命令名时，补全唯一匹配的剩余部分
+            let mut matches = command_names().filter(|n| n.starts_with(first) && *n != first);
+            let only = matches.next()?;
+            if matches.next().is_some() { return None }
+            let rest = only[first.len()..].to_string();
+            return Some(CommandHint { display: rest.clone(), completion: Some(rest) });
+        }
+        if ends_with_space && line.split_whitespace().count() == 1 {
+            let (syntax, _) = lookup(first)?;
+            if syntax.is_empty() { return None }
+            return Some(CommandHint { display: syntax.to_string(), completion: None });
+        }
+        None
+    }
+}
+
+impl Highlighter for DiaryHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        let trimmed = line.trim_start();
+        let Some(first) = trimmed.split_whitespace().next() else { return Cow::Borrowed(line) };
+        let lead = &line[..line.len() - trimmed.len()];
+        let rest = &trimmed[first.len()..];
+
+        let Some((_, kind)) = lookup(first) else {
+            // 仍可能补全为合法命令时不标红
+            let is_prefix = rest.is_empty() && command_names().any(|n| n.starts_with(first));
+            if is_prefix { return Cow::Borrowed(line) }
+            return Cow::Owned(format!("{}{}{}{}{}", lead, RED, first, RESET, rest));
+        };
+        let mut out = format!("{}{}{}{}", lead, BOLD, first, RESET);
+
+        // 检查第一个参数能否被解析
+        let arg_trimmed = rest.trim_start();
+        let arg = arg_trimmed.split_whitespace().next().unwrap_or_default();
+        let valid = match kind {
+            _ if arg.is_empty() => true,
+            ArgKind::Date => arg.parse::<Date>().is_ok(),
+            ArgKind::Range => arg.parse::<DateRange>().is_ok(),
+            ArgKind::DateOrSpan => arg.parse::<Span>().is_ok() || arg.parse::<Date>().is_ok(),
+            ArgKind::Notebook => self.notebooks.iter().any(|n| n == arg),
+            ArgKind::None | ArgKind::Text => true,
+        };
+        if valid {
+            out.push_str(rest);
+        } else {
+            let gap = &rest[..rest.len() - arg_trimmed.len()];
+            let tail = &arg_trimmed[arg.len()..];
+            out.push_str(&format!("{}{}{}{}{}", gap, RED, arg, RESET, tail));
+        }
+        Cow::Owned(out)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(format!("{}{}{}", DIM, hint, RESET))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, kind: CmdKind) -> bool {
+        kind != CmdKind::MoveCursor
+    }
+}
+
+impl Validator for DiaryHelper {}
+
+impl Helper for DiaryHelper {}
+
+#[cfg(test)]
+mod tests {
+    use rustyline::history::DefaultHistory;
+    use time::macros::date;
+    use super::*;
+
+    fn helper() -> DiaryHelper {
+        let mut helper = DiaryHelper::default();
+        helper.set_dates(vec![date!(2025-01-02).into(), date!(2025-01-03).into()]);
+        helper.set_notebooks(vec!["work".to_string()]);
+        helper
+    }
+    fn complete(line: &str) -> Vec<String> {
+        let history = DefaultHistory::new();
+        let (_, pairs) = helper().complete(line, line.len(), &Context::new(&history)).unwrap();
+        pairs.into_iter().map(|p| p.replacement).collect()
+    }
+    fn hint(line: &str) -> Option<(String, Option<String>)> {
+        let history = DefaultHistory::new();
+        helper().hint(line, line.len(), &Context::new(&history))
+            .map(|h| (h.display, h.completion))
+    }
+
+    #[test]
+    fn complete_commands_dates_and_notebooks() {
+        assert_eq!(complete("ad"), ["ad ", "add "]);
+        // 日期从新到旧
+        assert_eq!(complete("check 2025"), ["20250103 ", "20250102 "]);
+        assert_eq!(complete("cal mo"), ["month "]);
+        assert_eq!(complete("use w"), ["work"]);
+        assert!(complete("add 20250102 te").is_empty());
+    }
+
+    #[test]
+    fn hint_the_rest_of_a_command_and_its_syntax() {
+        assert_eq!(hint("onth"), Some(("isday".to_string(), Some("isday".to_string()))));
+        // 有多个匹配时不提示
+        assert_eq!(hint("h"), None);
+        assert_eq!(hint("add "), Some(("<date> [context]".to_string(), None)));
+        assert_eq!(hint("list "), None);
+    }
+
+    #[test]
+    fn highlight_unknown_commands_and_bad_arguments() {
+        let helper = helper();
+        assert!(helper.highlight("xyz", 3).contains(RED));
+        assert!(!helper.highlight("ad", 2).contains(RED));
+        assert!(helper.highlight("add 2025x", 9).contains(RED));
+        assert!(!helper.highlight("add today text", 14).contains(RED));
+        assert!(helper.highlight("use home", 8).contains(RED));
+        assert!(!helper.highlight("otd week", 8).contains(RED));
+    }
+}
@@ -1,4 +1,10 @@
-use diary_core::base::env::open_with_default_database;
+use clap::Parser;
+use diary_core::base::config::Config;
+use diary_core::base::blocking::BlockingDiary;
+use diary_core::base::env::open_with_config;
+use diary_core::base::i18n::set_locale;
+use diary_core::base::notebook::Notebooks;
+use crate::args::CliArgs;
 use crate::terminal::CliSession;
 
 mod terminal;
@@ -6,11 +12,42 @@ mod executor;
 mod error;
 mod command;
 mod args;
+mod stats;
+mod calendar;
+mod tui;
+mod helper;
+mod session;
 
 fn main() {
-    let db = open_with_default_database()
-        .expect("Could not open database");
+    let args = CliArgs::parse();
+    let mut config = Config::load(args.config.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Could not load config: {}", e);
+        std::process::exit(1);
+    });
+    set_locale(config.locale());
+    if let Some(db) = &args.db {
+        config.database = Some(db.clone());
+    }
+    let notebooks = Notebooks::default();
+    // 未指定笔记本且配置了数据库路径时，不属于任何笔记本
+    let notebook = match &args.notebook {
+        Some(name) => Some(name.clone()),
+        None => config.database.is_none().then(|| notebooks.default_notebook()),
+    };
+    let open = async {
+        match &notebook {
+            Some(name) => notebooks.open(name).await,
+            None => open_with_config(&config).await,
+        }
+    };
+    let mut diary = BlockingDiary::open_with(open).unwrap_or_else(|e| {
+        eprintln!("Could not open database: {}", e);
+        std::process::exit(1);
+    });
 
-    let mut cli = CliSession::new(db);
-    cli.run()
+    diary.run(async |exec| {
+        let mut cli = CliSession::new(args, config, exec);
+        cli.executor.notebook = notebook;
+        cli.run().await
+    })
 }
\ No newline at end of file
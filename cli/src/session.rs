@@ -0,0 +1,88 @@
+//! 交互模式跨次运行保留的状态
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use diary_core::base::date::{Date, DATE_FORMAT1};
+
+/// 每个笔记本最后查看日期的键前缀
+const LAST_VIEWED_PREFIX: &str = "last_viewed.";
+
+/// 保存在项目目录中的会话状态，格式为每行一个`key=value`
+#[derive(Debug, Default)]
+pub struct Session {
+    path: PathBuf,
+    /// 各笔记本最后一次查看或编辑的日期
+    last_viewed: BTreeMap<String, Date>,
+}
+impl Session {
+    /// 读取会话文件，不存在或无法解析的内容会被忽略
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut session = Self { path, last_viewed: BTreeMap::new() };
+        for (key, value) in content.lines().filter_map(|l| l.split_once('=')) {
+            if let Some(notebook) = key.trim().strip_prefix(LAST_VIEWED_PREFIX)
+                && let Ok(date) = time::Date::parse(value.trim(), DATE_FORMAT1) {
+                session.last_viewed.insert(notebook.to_string(), date.into());
+            }
+        }
+        session
+    }
+    pub fn save(&self) -> std::io::Result<()> {
+        let mut content = String::new();
+        for (notebook, date) in &self.last_viewed {
+            content.push_str(&format!("{}{}={}\n", LAST_VIEWED_PREFIX, notebook, date.format(DATE_FORMAT1).unwrap()));
+        }
+        std::fs::write(&self.path, content)
+    }
+    /// 某个笔记本最后查看的日期，直接打开数据库文件（`notebook`为`None`）时不保留
+    pub fn last_viewed(&self, notebook: Option<&str>) -> Option<Date> {
+        self.last_viewed.get(notebook?).copied()
+    }
+    /// 更新某个笔记本最后查看的日期，有变化时返回`true`
+    pub fn set_last_viewed(&mut self, notebook: Option<&str>, date: Option<Date>) -> bool {
+        let Some(notebook) = notebook else { return false };
+        if self.last_viewed.get(notebook).copied() == date { return false }
+        match date {
+            Some(date) => self.last_viewed.insert(notebook.to_string(), date),
+            None => self.last_viewed.remove(notebook),
+        };
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+
+    #[test]
+    fn last_viewed_survives_a_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session");
+        let mut session = Session::load(&path);
+        assert_eq!(session.last_viewed(Some("default")), None);
+        assert!(session.set_last_viewed(Some("default"), Some(date!(2025-01-02).into())));
+        assert!(session.set_last_viewed(Some("work"), Some(date!(2024-12-31).into())));
+        assert!(!session.set_last_viewed(Some("work"), Some(date!(2024-12-31).into())));
+        // 直接打开的数据库文件不保留
+        assert!(!session.set_last_viewed(None, Some(date!(2025-01-02).into())));
+        session.save().unwrap();
+
+        let mut session = Session::load(&path);
+        assert_eq!(session.last_viewed(Some("default")), Some(date!(2025-01-02).into()));
+        assert_eq!(session.last_viewed(None), None);
+        assert!(session.set_last_viewed(Some("work"), None));
+        session.save().unwrap();
+        assert_eq!(Session::load(&path).last_viewed(Some("work")), None);
+    }
+
+    #[test]
+    fn unreadable_lines_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session");
+        std::fs::write(&path, "garbage\nlast_viewed.a=2025-13-01\nother=1\n last_viewed.b = 20250102 \n").unwrap();
+        let session = Session::load(&path);
+        assert_eq!(session.last_viewed(Some("a")), None);
+        assert_eq!(session.last_viewed(Some("b")), Some(date!(2025-01-02).into()));
+    }
+}
@@ -0,0 +1,95 @@
+//! `stats`命令的终端表格输出
+use unicode_width::UnicodeWidthStr;
+use diary_core::base::i18n::weekday_name;
+use diary_core::t;
+use diary_core::utils::stats::{MetricSummary, MoodSummary, PeriodSummary, Statistics};
+
+/// 按周汇总时最多展示的行数
+const MAX_WEEKS: usize = 12;
+/// 天气分布最多展示的行数
+const MAX_WEATHER: usize = 10;
+
+pub fn print_statistics(stats: &Statistics) {
+    println!("{}", t!("cli.stats.range", range = stats.range));
+    println!("{}", t!("cli.stats.totals", entries = stats.entries, words = stats.words, chars = stats.chars));
+    println!("{}", t!("cli.stats.streaks", current = stats.current_streak, longest = stats.longest_streak));
+    println!("{}", t!("cli.stats.mood", mood = fmt_mood(stats.mood.as_ref())));
+    if stats.entries == 0 { return }
+
+    println!("\n{}", t!("cli.stats.monthly"));
+    print_periods(&stats.monthly, |p| format!("{}-{:02}", p.start.year(), p.start.month() as u8));
+
+    println!("\n{}", t!("cli.stats.weekly", n = MAX_WEEKS));
+    let skip = stats.weekly.len().saturating_sub(MAX_WEEKS);
+    print_periods(&stats.weekly[skip..], |p| format!("{}", *p.start));
+
+    println!("\n{}", t!("cli.stats.mood_by_weekday"));
+    let width = stats.mood_by_weekday.iter().map(|(wd, _)| weekday_name(*wd).width()).max().unwrap_or_default();
+    for (wd, mood) in &stats.mood_by_weekday {
+        println!("  {} {}", pad_right(weekday_name(*wd), width), fmt_mood(mood.as_ref()));
+    }
+
+    if !stats.weather.is_empty() {
+        println!("\n{}", t!("cli.stats.weather"));
+        let labels = stats.weather.iter().take(MAX_WEATHER)
+            .map(|(c, n)| (format!("{} {}", c.icon(), c.label()), n))
+            .collect::<Vec<_>>();
+        let width = labels.iter().map(|(l, _)| l.width()).max().unwrap_or_default();
+        for (label, n) in labels {
+            let pad = " ".repeat(width - label.width());
+            println!("  {}{} {:>5}  {}", label, pad, n, "#".repeat((*n).min(40)));
+        }
+    }
+
+    if !stats.metrics.is_empty() {
+        println!("\n{}", t!("cli.stats.metrics"));
+        let width = stats.metrics.iter().map(|(name, _)| name.width()).max().unwrap_or_default();
+        for (name, summary) in &stats.metrics {
+            let pad = " ".repeat(width - name.width());
+            println!("  {}{} {}", name, pad, fmt_metric(summary));
+        }
+    }
+}
+
+fn print_periods(periods: &[PeriodSummary], label: impl Fn(&PeriodSummary) -> String) {
+    println!("  {} {} {} {}  {}", pad_right(t!("cli.stats.period"), 10), pad_left(t!("cli.stats.entries"), 7),
+             pad_left(t!("cli.stats.words"), 7), pad_left(t!("cli.stats.chars"), 7), t!("cli.stats.mood_columns"));
+    for p in periods {
+        println!("  {:<10} {:>7} {:>7} {:>7}  {}",
+                 label(p), p.entries, p.words, p.chars, fmt_mood_short(p.mood.as_ref()));
+    }
+}
+
+fn fmt_mood(mood: Option<&MoodSummary>) -> String {
+    match mood {
+        Some(m) => t!("cli.stats.mood_summary", average = format!("{:.1}", m.average),
+                      min = format!("{:.1}", m.min), max = format!("{:.1}", m.max), n = m.count),
+        None => "-".to_string(),
+    }
+}
+
+fn fmt_metric(summary: &MetricSummary) -> String {
+    match summary {
+        MetricSummary::Number(m) => t!("cli.stats.number_summary", average = format!("{:.1}", m.average),
+                                       min = format!("{:.1}", m.min), max = format!("{:.1}", m.max), n = m.count),
+        MetricSummary::Bool { yes, count } => t!("cli.stats.yes_rate", yes = yes, n = count,
+                                                 rate = format!("{:.0}", *yes as f64 / *count as f64 * 100.)),
+        MetricSummary::Text { count } => t!("cli.stats.recorded", n = count),
+    }
+}
+
+fn fmt_mood_short(mood: Option<&MoodSummary>) -> String {
+    match mood {
+        Some(m) => format!("{:.1}/{:.1}/{:.1}", m.average, m.min, m.max),
+        None => "-".to_string(),
+    }
+}
+
+/// 按显示宽度左对齐，中文等宽字符占两列
+fn pad_right(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(s.width())))
+}
+/// 按显示宽度右对齐
+fn pad_left(s: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width.saturating_sub(s.width())), s)
+}
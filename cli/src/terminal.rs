@@ -2,48 +2,60 @@ use std::ffi::OsStr;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::process::ExitStatus;
-use clap::Parser;
-use rustyline::{Config, DefaultEditor};
+use rustyline::{CompletionType, Config, Editor};
+use rustyline::history::DefaultHistory;
 use rustyline::error::ReadlineError;
+use diary_core::base::env::default_project_path;
 use diary_core::base::executor::Executor;
-use diary_core::storage::DatabaseManager;
+use diary_core::base::notebook::{Notebooks, DEFAULT_NOTEBOOK};
 use diary_core::utils::io::export::Exporter;
-use diary_core::utils::io::import::DuplicateStrategy::Replace;
 use diary_core::utils::io::import::Importer;
-use crate::args;
+use diary_core::t;
 use crate::args::{CliArgs, Commands};
 use crate::error::CliError;
 use crate::executor::CliExecutor;
+use crate::helper::DiaryHelper;
+use crate::session::Session;
+use crate::tui;
+
+/// 历史命令文件名，位于项目数据目录
+const HISTORY_FILE: &str = "history.txt";
+/// 最多保留的历史命令条数
+const HISTORY_SIZE: usize = 1000;
+/// 会话状态文件名，位于项目数据目录
+const SESSION_FILE: &str = "session";
 
 /// Cli实体表示
-pub struct CliSession {
+pub struct CliSession<'a> {
     /// 用户启动程序时所采用的参数
     pub args: CliArgs,
-    pub(crate) executor: CliExecutor,
+    pub(crate) executor: CliExecutor<'a>,
 }
-impl CliSession {
-    pub fn new(db_mgr: DatabaseManager) -> Self {
-        let args = CliArgs::parse();
-        let exec = Executor::from(db_mgr);
-        let exec = CliExecutor::from(exec);
+impl<'a> CliSession<'a> {
+    pub fn new(args: CliArgs, config: diary_core::base::config::Config, exec: &'a mut Executor) -> Self {
+        let exec = CliExecutor::new(exec, config);
         Self {
             args,
             executor: exec,
         }
     }
-    pub fn run(&mut self) {
-        async_std::task::block_on(async {
-            if self.args.command.is_some() {
-                self.once().await;
-            }else {
-                self.interactive().await;
-            }
-        });
+    pub async fn run(&mut self) {
+        if self.args.command.is_some() {
+            self.once().await;
+        }else {
+            self.interactive().await;
+        }
     }
     /// 如果用户通过命令行解析
     async fn once(&mut self) {
         match self.args.command.as_ref().unwrap() {
             Commands::Interactive => self.interactive().await,
+            Commands::Tui => {
+                let editor = self.executor.editor();
+                if let Err(e) = tui::run(self.executor.exec, &editor, self.executor.notebook.as_deref()).await {
+                    eprintln!("TUI error: {}", e);
+                }
+            }
             Commands::Import(val) => {
                 let mut imp = Importer::new(self.executor.exec.conn_mut());
                 let data = Importer::read_from_file(&val.path, (&val.format).into())
@@ -53,8 +65,15 @@ impl CliSession {
                         eprintln!("Import Fail at {}", i);
                     }
                 }
-                imp.import_to_db(data.0, Replace).await
-                    .expect("Error when import to database");
+                // 中途失败时也报告已写入的记录
+                let (summary, error) = match imp.import_to_db(data.0, self.executor.config.import_strategy).await {
+                    Ok(summary) => (summary, None),
+                    Err((summary, e)) => (summary, Some(e)),
+                };
+                println!("{}", t!("cli.imported", summary = summary));
+                if let Some(e) = error {
+                    eprintln!("{}", t!("cli.import_stopped", error = e));
+                }
             }
             Commands::Export(val) => {
                 let mut exp = Exporter::new(self.executor.exec.conn_mut(),
@@ -67,23 +86,63 @@ impl CliSession {
     /// 如果用户通过交互式运行程序
     async fn interactive(&mut self) {
         // History Enable
-        let s = Config::builder().auto_add_history(true).build();
-        let mut rl = DefaultEditor::with_config(s).unwrap();
+        let s = Config::builder()
+            .auto_add_history(true)
+            .max_history_size(HISTORY_SIZE).unwrap()
+            .history_ignore_dups(true).unwrap()
+            .history_ignore_space(true)
+            .completion_type(CompletionType::List)
+            .build();
+        let mut rl = Editor::<DiaryHelper, DefaultHistory>::with_config(s).unwrap();
+        rl.set_helper(Some(DiaryHelper::default()));
+
+        let project_dir = default_project_path();
+        let history_path = project_dir.join(HISTORY_FILE);
+        // 首次运行时历史文件不存在
+        let _ = rl.load_history(&history_path);
+        let mut session = Session::load(project_dir.join(SESSION_FILE));
+        self.executor.last_viewed = session.last_viewed(self.executor.notebook.as_deref());
         loop {
-            match rl.readline(">: ") {
+            // 刷新补全所用的日期
+            if let Ok(dates) = self.executor.exec.conn_mut().read_dates().await {
+                rl.helper_mut().unwrap().set_dates(dates);
+            }
+            if let Ok(names) = Notebooks::default().list() {
+                rl.helper_mut().unwrap().set_notebooks(names);
+            }
+            // 使用非默认笔记本时在提示符中标出
+            let prompt = match self.executor.notebook.as_deref() {
+                Some(name) if name != DEFAULT_NOTEBOOK => format!("{}>: ", name),
+                _ => ">: ".to_string(),
+            };
+            match rl.readline(&prompt) {
                 Ok(line) => {
                     if line.is_empty() { continue }
-                    match self.executor.exec_command(&line).await {
+                    if let Err(e) = rl.append_history(&history_path) {
+                        eprintln!("Could not save history: {}", e);
+                    }
+                    if let Some(keyword) = history_query(&line) {
+                        print_history(rl.history(), keyword);
+                        continue;
+                    }
+                    let notebook = self.executor.notebook.clone();
+                    let res = self.executor.exec_command(&line).await;
+                    if self.executor.notebook != notebook {
+                        // 切换笔记本后接着查看该笔记本上次的日期
+                        self.executor.last_viewed = session.last_viewed(self.executor.notebook.as_deref());
+                    } else if session.set_last_viewed(notebook.as_deref(), self.executor.last_viewed)
+                        && let Err(e) = session.save() {
+                        eprintln!("Could not save session: {}", e);
+                    }
+                    match res {
                         Ok(_) => (),
                         Err(CliError::Quit) => break,
-                        Err(CliError::InvalidArgs(s)) => println!("Invalid args: {}", s),
-                        Err(CliError::Io(s)) => println!("IO error: {}", s),
-                        Err(CliError::UnknownCommand(s)) => println!("Unknown command: {}", s),
+                        Err(e) => println!("{}", e),
                         // Err(CliError::CoreError(e)) => println!("Core Error: {}", e),
                     }
                 },
                 Err(ReadlineError::Eof) | Err(ReadlineError::Interrupted) => {
-                    println!("Have a nice day!");
+                    println!("{}", t!("cli.farewell"));
                     break;
                 },
                 Err(err) => panic!("{}", err),
@@ -91,10 +150,24 @@ impl CliSession {
         }
     }
 }
+/// 如果输入的是`history`命令，返回其关键字（可能为空）
+fn history_query(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let (cmd, keyword) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    matches!(cmd, "history" | "hist").then(|| keyword.trim())
+}
+/// 打印包含关键字的历史命令，序号与上下键翻阅的顺序一致
+fn print_history(history: &DefaultHistory, keyword: &str) {
+    history.iter()
+        .enumerate()
+        .filter(|(_, l)| l.contains(keyword) && history_query(l).is_none())
+        .for_each(|(i, l)| println!("{:>5}  {}", i + 1, l));
+}
 /// 通过调用外部编辑器编辑文本
+/// *editor* 编辑器命令
 /// *s* 预设文本
 /// *date* 预设日期（将在临时文件名中出现）
-pub fn edit_with_editor(s: &str, date: impl AsRef<OsStr>) -> Result<String, CliError> {
+pub fn edit_with_editor(editor_cmd: &str, s: &str, date: impl AsRef<OsStr>) -> Result<String, CliError> {
     let mut suffix = date.as_ref().to_os_string();
     // 设置临时文件为markdown格式
     suffix.push(".md");
@@ -107,16 +180,16 @@ pub fn edit_with_editor(s: &str, date: impl AsRef<OsStr>) -> Result<String, CliE
     editor.flush()?;
     editor.seek(SeekFrom::Start(0))?;
 
-    edit_file(editor.path())?;
+    edit_file(editor_cmd, editor.path())?;
 
 
     let mut res = String::new();
     editor.read_to_string(&mut res)?;
     Ok(res)
 }
-/// 通过默认编辑器打开文件
-pub fn edit_file(file: impl AsRef<Path>) -> std::io::Result<ExitStatus> {
-    std::process::Command::new(args::editor())
+/// 通过指定的编辑器打开文件
+pub fn edit_file(editor: &str, file: impl AsRef<Path>) -> std::io::Result<ExitStatus> {
+    std::process::Command::new(editor)
         .arg(file.as_ref())
         .status()
 }
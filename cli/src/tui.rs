@@ -0,0 +1,83 @@
+//! 全屏终端界面，通过`diary-cli tui`启动
+mod state;
+mod view;
+
+use std::io::stdout;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::DefaultTerminal;
+use diary_core::base::command::Command;
+use diary_core::base::date::Date;
+use diary_core::base::executor::Executor;
+use diary_core::t;
+use diary_core::utils::template::Templates;
+use crate::error::CliError;
+use crate::terminal::edit_with_editor;
+use crate::tui::state::{Action, TuiState};
+
+/// 运行TUI直到用户退出，`notebook`用于选择新日期的模板
+pub async fn run(exec: &mut Executor, editor: &str, notebook: Option<&str>) -> Result<(), CliError> {
+    let entries = exec.exec(&Command::ListAll).await?;
+    let mut state = TuiState::new(entries);
+    let mut terminal = ratatui::init();
+    let res = event_loop(&mut terminal, &mut state, exec, editor, notebook).await;
+    ratatui::restore();
+    res
+}
+
+async fn event_loop(terminal: &mut DefaultTerminal, state: &mut TuiState, exec: &mut Executor, editor: &str, notebook: Option<&str>) -> Result<(), CliError> {
+    loop {
+        terminal.draw(|f| view::draw(f, state))?;
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press { continue }
+
+        match state.handle_key(key) {
+            None => {}
+            Some(Action::Quit) => return Ok(()),
+            Some(Action::Save(day)) => {
+                exec.conn_mut().add_day(&day).await?;
+                state.status = t!("cli.tui.saved", date = *day.date);
+                state.set_entries(exec.exec(&Command::ListAll).await?);
+            }
+            Some(Action::Edit(date)) => {
+                let template = new_day_template(exec, notebook, date).await?;
+                state.start_edit(template);
+            }
+            Some(Action::ExternalEdit(date)) => {
+                let mut day = match state.current().cloned() {
+                    Some(day) => day,
+                    None => diary_core::model::Day {
+                        date,
+                        event: new_day_template(exec, notebook, date).await?.unwrap_or_default().into(),
+                        ..Default::default()
+                    },
+                };
+                // 暂时离开全屏模式，把终端交给外部编辑器
+                disable_raw_mode()?;
+                execute!(stdout(), LeaveAlternateScreen)?;
+                let edited = edit_with_editor(editor, &day.event.instruct, date.to_string());
+                enable_raw_mode()?;
+                execute!(stdout(), EnterAlternateScreen)?;
+                terminal.clear()?;
+
+                match edited {
+                    Ok(text) if text != day.event.instruct => {
+                        day.event = text.into();
+                        exec.conn_mut().add_day(&day).await?;
+                        state.status = t!("cli.tui.saved", date = *date);
+                        state.set_entries(exec.exec(&Command::ListAll).await?);
+                    }
+                    Ok(_) => state.status = t!("cli.tui.nothing_changed").to_string(),
+                    Err(CliError::Io(e)) => state.status = t!("cli.tui.editor_failed", error = e),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+}
+
+/// 还没有记录的日期的初始内容，与`add`命令使用相同的模板
+async fn new_day_template(exec: &mut Executor, notebook: Option<&str>, date: Date) -> Result<Option<String>, CliError> {
+    Ok(exec.conn_mut().render_template(&Templates::default(), notebook, date).await?)
+}
@@ -0,0 +1,394 @@
+//! TUI的界面状态与按键处理
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+use time::Duration;
+use unicode_width::UnicodeWidthChar;
+use diary_core::base::date::Date;
+use diary_core::model::{Day, Event};
+use diary_core::t;
+
+/// 当前的交互模式
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Mode {
+    /// 浏览，方向键切换日期
+    Browse,
+    /// 输入搜索关键字，列表随输入过滤
+    Search,
+    /// 在预览区直接编辑
+    Edit,
+}
+
+/// 需要访问数据库或终端的操作，由主循环执行
+pub enum Action {
+    Quit,
+    Save(Day),
+    /// 在预览区编辑，还没有记录的日期需要先生成模板
+    Edit(Date),
+    ExternalEdit(Date),
+}
+
+pub struct TuiState {
+    /// 全部记录，按日期降序
+    pub entries: Vec<Day>,
+    pub selected: Date,
+    pub query: String,
+    pub mode: Mode,
+    pub editor: TextBuffer,
+    /// 正在编辑的新日期所用的模板，保存时内容未改动则不写入
+    pub template: Option<String>,
+    pub list: ListState,
+    pub status: String,
+}
+impl TuiState {
+    pub fn new(entries: Vec<Day>) -> Self {
+        let mut state = Self {
+            entries: Vec::new(),
+            selected: Date::native_time(),
+            query: String::new(),
+            mode: Mode::Browse,
+            editor: TextBuffer::default(),
+            template: None,
+            list: ListState::default(),
+            status: t!("cli.tui.help_browse").to_string(),
+        };
+        state.set_entries(entries);
+        state
+    }
+    /// 替换全部记录并保持当前选中的日期
+    pub fn set_entries(&mut self, mut entries: Vec<Day>) {
+        entries.sort_by_key(|d| std::cmp::Reverse(d.date));
+        self.entries = entries;
+        self.sync_list();
+    }
+    /// 满足搜索条件的记录
+    pub fn filtered(&self) -> Vec<&Day> {
+        let query = self.query.to_lowercase();
+        self.entries.iter()
+            .filter(|d| query.is_empty()
+                || d.event.instruct.to_lowercase().contains(&query)
+                || d.weather.as_ref().is_some_and(|w| w.describe().to_lowercase().contains(&query)
+                    || w.to_string().contains(&query)))
+            .collect()
+    }
+    /// 当前选中日期的记录
+    pub fn current(&self) -> Option<&Day> {
+        self.entries.iter().find(|d| d.date == self.selected)
+    }
+    pub fn select(&mut self, date: Date) {
+        self.selected = date;
+        self.sync_list();
+    }
+    /// 让列表的高亮行与选中的日期保持一致
+    fn sync_list(&mut self) {
+        let idx = self.filtered().iter().position(|d| d.date == self.selected);
+        self.list.select(idx);
+    }
+    /// 开始在预览区编辑，`template`为新日期的初始内容
+    pub fn start_edit(&mut self, template: Option<String>) {
+        let text = match self.current() {
+            Some(d) => d.event.instruct.clone(),
+            None => template.clone().unwrap_or_default(),
+        };
+        self.editor = TextBuffer::from(text.as_str());
+        self.template = template;
+        self.mode = Mode::Edit;
+        self.status = t!("cli.tui.help_edit").to_string();
+    }
+    fn move_days(&mut self, n: i64) {
+        self.select(self.selected.saturating_add(Duration::days(n)).into());
+    }
+    fn move_months(&mut self, n: i32) {
+        let (mut year, mut month) = (self.selected.year(), self.selected.month() as i32 - 1 + n);
+        year += month.div_euclid(12);
+        month = month.rem_euclid(12);
+        let month = time::Month::try_from(month as u8 + 1).unwrap();
+        let day = self.selected.day().min(time::util::days_in_month(month, year));
+        if let Ok(d) = time::Date::from_calendar_date(year, month, day) {
+            self.select(d.into());
+        }
+    }
+    /// 在过滤后的列表中上下移动
+    fn move_in_list(&mut self, delta: isize) {
+        let list = self.filtered();
+        if list.is_empty() { return }
+        let next = match self.list.selected() {
+            Some(i) => (i as isize + delta).clamp(0, list.len() as isize - 1) as usize,
+            // 当前日期不在列表中时，跳到离它最近的记录
+            None => list.iter().position(|d| d.date < self.selected).unwrap_or(list.len() - 1),
+        };
+        let date = list[next].date;
+        self.select(date);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match self.mode {
+            Mode::Browse => self.handle_browse(key),
+            Mode::Search => {
+                self.handle_search(key);
+                None
+            }
+            Mode::Edit => self.handle_edit(key),
+        }
+    }
+    fn handle_browse(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Some(Action::Quit),
+            KeyCode::Left | KeyCode::Char('h') => self.move_days(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.move_days(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_in_list(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_in_list(1),
+            KeyCode::PageUp | KeyCode::Char('[') => self.move_months(-1),
+            KeyCode::PageDown | KeyCode::Char(']') => self.move_months(1),
+            KeyCode::Char('t') => self.select(Date::native_time()),
+            KeyCode::Char('/') => {
+                self.mode = Mode::Search;
+                self.status = t!("cli.tui.help_search").to_string();
+            }
+            KeyCode::Char('i') | KeyCode::Enter => match self.current() {
+                Some(_) => self.start_edit(None),
+                None => return Some(Action::Edit(self.selected)),
+            },
+            KeyCode::Char('e') => return Some(Action::ExternalEdit(self.selected)),
+            KeyCode::Char('?') => self.status = t!("cli.tui.help_browse").to_string(),
+            _ => {}
+        }
+        None
+    }
+    fn handle_search(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.query.clear();
+                self.mode = Mode::Browse;
+                self.status = t!("cli.tui.help_browse").to_string();
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::Browse;
+                self.status = t!("cli.tui.help_browse").to_string();
+            }
+            KeyCode::Backspace => { self.query.pop(); }
+            KeyCode::Up => self.move_in_list(-1),
+            KeyCode::Down => self.move_in_list(1),
+            KeyCode::Char(c) => self.query.push(c),
+            _ => {}
+        }
+        self.sync_list();
+        // 过滤后当前日期不在结果中时，自动选中第一条
+        if self.list.selected().is_none() && let Some(first) = self.filtered().first().map(|d| d.date) {
+            self.select(first);
+        }
+    }
+    fn handle_edit(&mut self, key: KeyEvent) -> Option<Action> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Browse;
+                self.status = format!("{} {}", t!("cli.tui.edit_discarded"), t!("cli.tui.help_browse"));
+            }
+            KeyCode::Char('s') if ctrl => {
+                self.mode = Mode::Browse;
+                if self.current().is_none() && self.template.take().is_some_and(|t| t.trim() == self.editor.text().trim()) {
+                    self.status = format!("{} {}", t!("cli.template_unchanged"), t!("cli.tui.help_browse"));
+                    return None;
+                }
+                let day = match self.current() {
+                    Some(d) => Day { event: Event::from(self.editor.text()), ..d.clone() },
+                    None => Day { date: self.selected, event: Event::from(self.editor.text()), ..Default::default() },
+                };
+                return Some(Action::Save(day));
+            }
+            KeyCode::Char(c) if !ctrl => self.editor.insert(c),
+            KeyCode::Enter => self.editor.insert('\n'),
+            KeyCode::Backspace => self.editor.backspace(),
+            KeyCode::Delete => self.editor.delete(),
+            KeyCode::Left => self.editor.left(),
+            KeyCode::Right => self.editor.right(),
+            KeyCode::Up => self.editor.up(),
+            KeyCode::Down => self.editor.down(),
+            KeyCode::Home => self.editor.col = 0,
+            KeyCode::End => self.editor.col = self.editor.line_len(self.editor.row),
+            _ => {}
+        }
+        None
+    }
+}
+
+
+/// 预览区内联编辑使用的多行文本，光标以字符为单位
+pub struct TextBuffer {
+    pub lines: Vec<String>,
+    pub row: usize,
+    pub col: usize,
+}
+impl TextBuffer {
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+    /// 光标之前的文本在终端中占据的列数
+    pub fn cursor_width(&self) -> usize {
+        self.lines[self.row].chars().take(self.col).map(|c| c.width().unwrap_or(0)).sum()
+    }
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+    /// 光标所在位置对应的字节下标
+    fn byte_idx(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices().nth(self.col).map(|t| t.0).unwrap_or(line.len())
+    }
+    fn insert(&mut self, c: char) {
+        let idx = self.byte_idx();
+        if c == '\n' {
+            let rest = self.lines[self.row].split_off(idx);
+            self.lines.insert(self.row + 1, rest);
+            self.row += 1;
+            self.col = 0;
+        } else {
+            self.lines[self.row].insert(idx, c);
+            self.col += 1;
+        }
+    }
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let idx = self.byte_idx();
+            self.lines[self.row].remove(idx);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+    fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let idx = self.byte_idx();
+            self.lines[self.row].remove(idx);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+    fn left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+    fn right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+    fn up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+    fn down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+}
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self { lines: vec![String::new()], row: 0, col: 0 }
+    }
+}
+impl From<&str> for TextBuffer {
+    fn from(text: &str) -> Self {
+        let lines = text.split('\n').map(str::to_string).collect();
+        Self { lines, row: 0, col: 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+    fn save() -> KeyEvent {
+        KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn new_day_starts_from_template() {
+        let mut state = TuiState::new(Vec::new());
+        let date = state.selected;
+        assert!(matches!(state.handle_key(key(KeyCode::Enter)), Some(Action::Edit(d)) if d == date));
+        state.start_edit(Some("## Today".to_string()));
+        assert_eq!(state.editor.text(), "## Today");
+        // 模板未改动时不保存
+        assert!(state.handle_key(save()).is_none());
+        assert_eq!(state.mode, Mode::Browse);
+
+        state.start_edit(Some("## Today".to_string()));
+        state.handle_key(key(KeyCode::End));
+        state.handle_key(key(KeyCode::Enter));
+        state.handle_key(key(KeyCode::Char('x')));
+        match state.handle_key(save()) {
+            Some(Action::Save(day)) => assert_eq!((day.date, day.event.instruct.as_str()), (date, "## Today\nx")),
+            _ => panic!("expected a save"),
+        }
+    }
+
+    #[test]
+    fn existing_day_keeps_its_text() {
+        let day = Day { event: Event::from("written".to_string()), ..Default::default() };
+        let mut state = TuiState::new(vec![day]);
+        assert!(state.handle_key(key(KeyCode::Char('i'))).is_none());
+        assert_eq!((state.mode, state.editor.text()), (Mode::Edit, "written".to_string()));
+    }
+
+    #[test]
+    fn search_selects_the_first_match() {
+        let days = [(date!(2025-01-01), "apple"), (date!(2025-01-02), "banana")]
+            .map(|(d, text)| Day { date: d.into(), event: Event::from(text.to_string()), ..Default::default() });
+        let mut state = TuiState::new(days.to_vec());
+        state.select(date!(2025-01-01).into());
+        state.handle_key(key(KeyCode::Char('/')));
+        state.handle_key(key(KeyCode::Char('N')));
+        assert_eq!(state.filtered().len(), 1);
+        assert_eq!(*state.selected, date!(2025-01-02));
+        // 退出搜索时清空关键字
+        state.handle_key(key(KeyCode::Esc));
+        assert_eq!((state.mode, state.filtered().len()), (Mode::Browse, 2));
+    }
+
+    #[test]
+    fn months_keep_the_day_within_the_month() {
+        let mut state = TuiState::new(Vec::new());
+        state.select(date!(2025-01-31).into());
+        state.handle_key(key(KeyCode::PageDown));
+        assert_eq!(*state.selected, date!(2025-02-28));
+        state.handle_key(key(KeyCode::Char('[')));
+        state.handle_key(key(KeyCode::Char('[')));
+        assert_eq!(*state.selected, date!(2024-12-28));
+    }
+
+    #[test]
+    fn text_buffer_edits_by_character() {
+        let mut buffer = TextBuffer::from("日记\nab");
+        buffer.right();
+        assert_eq!(buffer.cursor_width(), 2);
+        buffer.backspace();
+        buffer.down();
+        // 行首退格与上一行合并
+        buffer.backspace();
+        assert_eq!((buffer.text(), buffer.row, buffer.col), ("记ab".to_string(), 0, 1));
+        buffer.insert('\n');
+        buffer.delete();
+        assert_eq!(buffer.text(), "记\nb");
+    }
+}
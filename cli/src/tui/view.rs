@@ -0,0 +1,152 @@
+//! TUI的绘制
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Wrap};
+use time::{Duration, Weekday};
+use diary_core::base::i18n::{weekday_name, weekday_short};
+use diary_core::t;
+use unicode_width::UnicodeWidthStr;
+use diary_core::base::date::Date;
+use diary_core::model::Mood;
+use crate::tui::state::{Mode, TuiState};
+
+pub fn draw(frame: &mut Frame, state: &mut TuiState) {
+    let [main, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)])
+        .areas(frame.area());
+    let [left, right] = Layout::horizontal([Constraint::Length(30), Constraint::Min(0)])
+        .areas(main);
+    let [cal, search, list] = Layout::vertical([
+        Constraint::Length(10),
+        Constraint::Length(3),
+        Constraint::Min(0),
+    ]).areas(left);
+
+    draw_calendar(frame, cal, state);
+    draw_search(frame, search, state);
+    draw_list(frame, list, state);
+    match state.mode {
+        Mode::Edit => draw_editor(frame, right, state),
+        _ => draw_preview(frame, right, state),
+    }
+    frame.render_widget(Paragraph::new(state.status.as_str()).reversed(), status);
+}
+
+/// 选中日期所在月份的月历，周一为每周第一天
+fn draw_calendar(frame: &mut Frame, area: Rect, state: &TuiState) {
+    let selected = state.selected;
+    let today = Date::native_time();
+    let first: Date = selected.replace_day(1).unwrap().into();
+    let offset = first.weekday().number_days_from_monday() as usize;
+    let len = time::util::days_in_month(selected.month(), selected.year()) as usize;
+
+    let header = (0..7).map(|i| Weekday::Monday.nth_next(i))
+        .map(|wd| {
+            let name = weekday_short(wd);
+            format!("{}{}", " ".repeat(3usize.saturating_sub(name.width())), name)
+        })
+        .collect::<String>();
+    let mut lines = vec![Line::from(header).dim()];
+    let mut spans = vec![Span::raw("   ".repeat(offset))];
+    for i in 0..len {
+        let date: Date = first.saturating_add(Duration::days(i as i64)).into();
+        let mut style = Style::new();
+        match state.entries.iter().find(|d| d.date == date) {
+            Some(d) => {
+                style = style.add_modifier(Modifier::BOLD);
+                if let Some(m) = d.mood { style = style.fg(mood_color(m)) }
+            }
+            None => style = style.add_modifier(Modifier::DIM),
+        }
+        if date == today { style = style.add_modifier(Modifier::UNDERLINED) }
+        if date == selected { style = style.add_modifier(Modifier::REVERSED) }
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(format!("{:>2}", date.day()), style));
+        if (offset + i + 1).is_multiple_of(7) {
+            lines.push(Line::from(std::mem::take(&mut spans)));
+        }
+    }
+    if !spans.is_empty() {
+        lines.push(Line::from(spans));
+    }
+    let title = format!(" {} {} ", selected.month(), selected.year());
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+}
+
+fn draw_search(frame: &mut Frame, area: Rect, state: &TuiState) {
+    let block = Block::bordered().title(format!(" {} ", t!("cli.tui.search")));
+    let block = if state.mode == Mode::Search { block.border_style(Style::new().yellow()) } else { block };
+    frame.render_widget(Paragraph::new(state.query.as_str()).block(block), area);
+    if state.mode == Mode::Search {
+        let x = area.x + 1 + unicode_width::UnicodeWidthStr::width(state.query.as_str()) as u16;
+        frame.set_cursor_position(Position::new(x.min(area.right() - 2), area.y + 1));
+    }
+}
+
+fn draw_list(frame: &mut Frame, area: Rect, state: &mut TuiState) {
+    let items = state.filtered().into_iter()
+        .map(|d| {
+            let first_line = d.event.instruct.lines().find(|l| !l.trim().is_empty()).unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(d.date.to_string(), Style::new().fg(d.mood.map(mood_color).unwrap_or(Color::Reset))),
+                Span::raw(" "),
+                Span::raw(first_line.trim().to_string()).dim(),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    let title = format!(" {} ", t!("cli.tui.entries", n = items.len()));
+    let list = List::new(items)
+        .block(Block::bordered().title(title))
+        .highlight_style(Style::new().reversed());
+    frame.render_stateful_widget(list, area, &mut state.list);
+}
+
+fn draw_preview(frame: &mut Frame, area: Rect, state: &TuiState) {
+    let title = format!(" {} {} ", *state.selected, weekday_name(state.selected.weekday()));
+    let text = match state.current() {
+        Some(day) => {
+            let mut meta = Vec::new();
+            if let Some(w) = &day.weather { meta.push(Span::raw(format!("{}: {}  ", t!("day.weather"), w.describe()))) }
+            if let Some(m) = day.mood {
+                meta.push(Span::styled(format!("{}: {:.1} {}", t!("day.mood"), m.value(), m.label()), Style::new().fg(mood_color(m))));
+            }
+            for (name, value) in &day.metrics {
+                meta.push(Span::raw(format!("  {}: {}", name, value.describe())));
+            }
+            if !day.habits.is_empty() {
+                let habits = day.habits.iter().map(String::as_str).collect::<Vec<_>>().join(", ");
+                meta.push(Span::raw(format!("  {}: {}", t!("day.habits"), habits)));
+            }
+            let mut text = Text::from(Line::from(meta));
+            text.push_line(Line::default());
+            text.extend(Text::raw(day.event.instruct.as_str()));
+            text
+        }
+        None => Text::from(t!("cli.tui.no_entry")).dim(),
+    };
+    let preview = Paragraph::new(text)
+        .block(Block::bordered().title(title))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, area);
+}
+
+fn draw_editor(frame: &mut Frame, area: Rect, state: &TuiState) {
+    let editor = &state.editor;
+    let inner_height = area.height.saturating_sub(2) as usize;
+    // 保证光标所在行始终可见
+    let scroll = editor.row.saturating_sub(inner_height.saturating_sub(1));
+    let title = format!(" {} ", t!("cli.tui.editing", date = *state.selected));
+    let paragraph = Paragraph::new(editor.text())
+        .block(Block::bordered().title(title).border_style(Style::new().yellow()))
+        .scroll((scroll as u16, 0));
+    frame.render_widget(paragraph, area);
+    let x = area.x + 1 + editor.cursor_width() as u16;
+    let y = area.y + 1 + (editor.row - scroll) as u16;
+    frame.set_cursor_position(Position::new(x.min(area.right() - 2), y));
+}
+
+/// 心情所对应的颜色，由红到绿
+fn mood_color(mood: Mood) -> Color {
+    [Color::Red, Color::LightRed, Color::Yellow, Color::LightGreen, Color::Green][mood.level()]
+}
@@ -1,5 +1,9 @@
 pub mod command;
 pub mod error;
 pub mod executor;
+pub mod blocking;
 pub mod date;
-pub mod env;
\ No newline at end of file
+pub mod env;
+pub mod config;
+pub mod notebook;
+pub mod i18n;
@@ -0,0 +1,167 @@
+//! 阻塞式接口：为不使用异步的程序持有唯一的运行时，并在其中执行所有数据库操作
+//!
+//! 核心库的其余部分都是异步的，不依赖特定的运行时；需要同步调用时使用[`BlockingDiary`]，
+//! 不要再自行创建运行时或混用其他运行时的`block_on`
+use std::future::Future;
+use std::path::Path;
+use tokio::runtime::{Builder, Runtime};
+use crate::base::config::WeekStart;
+use crate::base::date::{Date, DateRange};
+use crate::base::error::Error;
+use crate::base::executor::Executor;
+use crate::model::{Day, Habit, MetricDef, MetricValue};
+use crate::storage::{DatabaseManager, MoveMode};
+use crate::utils::habits::HabitProgress;
+use crate::utils::onthisday::Span;
+use crate::utils::search::SearchCondition;
+use crate::utils::stats::Statistics;
+use crate::utils::template::Templates;
+
+pub struct BlockingDiary {
+    runtime: Runtime,
+    executor: Executor,
+}
+impl BlockingDiary {
+    /// 打开（不存在时创建）数据库文件
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        Self::open_with(crate::base::env::open_with_db_file(path))
+    }
+    /// 用`open`打开数据库，例如`Notebooks::open`或`open_with_config`
+    pub fn open_with(open: impl Future<Output = Result<DatabaseManager, Error>>) -> Result<Self, Error> {
+        let runtime = Builder::new_current_thread().build()?;
+        let conn = runtime.block_on(open)?;
+        Ok(Self { runtime, executor: Executor::from(conn) })
+    }
+    /// 在同一个运行时中换用另一个数据库，失败时保留原来的连接
+    pub fn reopen_with(&mut self, open: impl Future<Output = Result<DatabaseManager, Error>>) -> Result<(), Error> {
+        let conn = self.runtime.block_on(open)?;
+        self.executor = Executor::from(conn);
+        Ok(())
+    }
+    /// 在持有的运行时中执行任意异步操作
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+    /// 在持有的运行时中执行需要访问`Executor`的异步操作
+    pub fn run<T>(&mut self, f: impl AsyncFnOnce(&mut Executor) -> T) -> T {
+        let Self { runtime, executor } = self;
+        runtime.block_on(f(executor))
+    }
+    pub fn executor(&self) -> &Executor {
+        &self.executor
+    }
+    pub fn executor_mut(&mut self) -> &mut Executor {
+        &mut self.executor
+    }
+
+    pub fn read_day(&mut self, date: Date) -> Result<Option<Day>, Error> {
+        self.run(async |e| e.conn_mut().read_day(date).await)
+    }
+    /// 添加某一天，已有记录时覆盖
+    pub fn add_day(&mut self, day: &Day) -> Result<u64, Error> {
+        self.run(async |e| e.conn_mut().add_day(day).await)
+    }
+    pub fn remove_day(&mut self, date: Date) -> Result<u64, Error> {
+        self.run(async |e| e.conn_mut().remove_day(date).await)
+    }
+    /// 把某一天的记录移动到另一天
+    pub fn move_day(&mut self, from: Date, to: Date, mode: MoveMode) -> Result<Day, Error> {
+        self.run(async |e| e.conn_mut().move_day(from, to, mode).await)
+    }
+    /// 把某一天的正文复制到另一天
+    pub fn copy_day(&mut self, from: Date, to: Date, mode: MoveMode) -> Result<Day, Error> {
+        self.run(async |e| e.conn_mut().copy_day(from, to, mode).await)
+    }
+    pub fn metric_defs(&mut self) -> Result<Vec<MetricDef>, Error> {
+        self.run(async |e| e.conn_mut().metric_defs().await)
+    }
+    /// 添加或修改指标定义
+    pub fn define_metric(&mut self, def: &MetricDef) -> Result<(), Error> {
+        self.run(async |e| e.conn_mut().define_metric(def).await)
+    }
+    /// 删除指标定义及其全部取值
+    pub fn remove_metric(&mut self, name: &str) -> Result<u64, Error> {
+        self.run(async |e| e.conn_mut().remove_metric(name).await)
+    }
+    /// 设置或清除某一天某个指标的取值
+    pub fn set_metric(&mut self, date: Date, name: &str, value: Option<MetricValue>) -> Result<Day, Error> {
+        self.run(async |e| e.conn_mut().set_metric(date, name, value).await)
+    }
+    pub fn habits(&mut self) -> Result<Vec<Habit>, Error> {
+        self.run(async |e| e.conn_mut().habits().await)
+    }
+    /// 添加习惯，已有同名习惯时只修改目标频率
+    pub fn define_habit(&mut self, habit: &Habit) -> Result<(), Error> {
+        self.run(async |e| e.conn_mut().define_habit(habit).await)
+    }
+    /// 删除习惯及其全部打卡记录
+    pub fn remove_habit(&mut self, name: &str) -> Result<u64, Error> {
+        self.run(async |e| e.conn_mut().remove_habit(name).await)
+    }
+    /// 标记或取消某一天完成某个习惯
+    pub fn check_habit(&mut self, date: Date, name: &str, done: bool) -> Result<Day, Error> {
+        self.run(async |e| e.conn_mut().check_habit(date, name, done).await)
+    }
+    /// 全部习惯截止到`today`的完成情况
+    pub fn habit_progress(&mut self, today: Date, window: usize, week_start: WeekStart) -> Result<Vec<HabitProgress>, Error> {
+        self.run(async |e| e.conn_mut().habit_progress(today, window, week_start).await)
+    }
+    /// 有记录的日期，按时间升序
+    pub fn read_dates(&mut self) -> Result<Vec<Date>, Error> {
+        self.run(async |e| e.conn_mut().read_dates().await)
+    }
+    pub fn read_all(&mut self) -> Result<Vec<Day>, Error> {
+        self.run(async |e| e.conn_mut().read_all().await)
+    }
+    /// 查询某段时间内（含首尾）的全部内容
+    pub fn read_from_to(&mut self, from: Date, to: Date) -> Result<Vec<Day>, Error> {
+        self.run(async |e| e.conn_mut().read_from_to(from, to).await)
+    }
+    pub fn search_in_condition(&mut self, condition: SearchCondition) -> Result<Vec<Day>, Error> {
+        self.run(async |e| e.conn_mut().search_in_condition(condition).await)
+    }
+    pub fn statistics(&mut self, range: DateRange, week_start: WeekStart) -> Result<Statistics, Error> {
+        self.run(async |e| e.conn_mut().statistics(range, week_start).await)
+    }
+    pub fn on_this_day(&mut self, date: Date, span: Span) -> Result<Vec<(i32, Day)>, Error> {
+        self.run(async |e| e.conn_mut().on_this_day(date, span).await)
+    }
+    pub fn render_template(&mut self, templates: &Templates, notebook: Option<&str>, date: Date) -> Result<Option<String>, Error> {
+        self.run(async |e| e.conn_mut().render_template(templates, notebook, date).await)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+
+    #[test]
+    fn reopen_switches_database_and_keeps_it_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut diary = BlockingDiary::open(dir.path().join("a.db")).unwrap();
+        diary.add_day(&Day { date: date!(2025-01-02).into(), ..Default::default() }).unwrap();
+
+        diary.reopen_with(crate::base::env::open_with_db_file(dir.path().join("b.db"))).unwrap();
+        assert!(diary.read_dates().unwrap().is_empty());
+
+        // 打开失败时仍使用原来的数据库
+        let failed = diary.reopen_with(async { Err(Error::InvalidData("fail".to_string())) });
+        assert!(failed.is_err());
+        diary.add_day(&Day { date: date!(2025-01-03).into(), ..Default::default() }).unwrap();
+        diary.reopen_with(crate::base::env::open_with_db_file(dir.path().join("a.db"))).unwrap();
+        assert_eq!(diary.read_dates().unwrap(), [date!(2025-01-02).into()]);
+    }
+
+    #[test]
+    fn operations_share_one_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut diary = BlockingDiary::open(dir.path().join("diary.db")).unwrap();
+        let handle = diary.block_on(async { tokio::runtime::Handle::current().id() });
+        assert_eq!(diary.run(async |_| tokio::runtime::Handle::current().id()), handle);
+        let day = Day { date: date!(2025-01-02).into(), ..Default::default() };
+        let written = diary.run(async |e| e.conn_mut().add_day(&day).await).unwrap();
+        assert_eq!(written, 1);
+        assert!(diary.read_day(day.date).unwrap().is_some());
+    }
+}
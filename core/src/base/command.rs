@@ -23,18 +23,43 @@ pub enum SubCommand {
     // Quit,
     // Help,
 }
+impl SubCommand {
+    /// 命令名及其别名，供解析与补全共用
+    pub const ALIASES: &'static [(&'static str, SubCommand)] = &[
+        ("ad", SubCommand::Add),
+        ("add", SubCommand::Add),
+        ("rm", SubCommand::Remove),
+        ("remove", SubCommand::Remove),
+        ("delete", SubCommand::Remove),
+        ("del", SubCommand::Remove),
+        ("chk", SubCommand::Check),
+        ("check", SubCommand::Check),
+        ("read", SubCommand::Check),
+        ("show", SubCommand::Check),
+        ("ls", SubCommand::ListAll),
+        ("list", SubCommand::ListAll),
+    ];
+
+    /// 命令参数的写法说明
+    pub fn syntax(&self) -> &'static str {
+        match self {
+            SubCommand::Add => "<date> [context]",
+            SubCommand::Remove | SubCommand::Check => "<date>",
+            SubCommand::ListAll => "",
+        }
+    }
+    /// 第一个参数是否为日期
+    pub fn takes_date(&self) -> bool {
+        !matches!(self, SubCommand::ListAll)
+    }
+}
 impl FromStr for SubCommand {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "ad" | "add" => Ok(SubCommand::Add),
-            "rm" | "remove" | "delete" | "del" => Ok(SubCommand::Remove),
-            "chk" | "check" | "read" | "show" => Ok(SubCommand::Check),
-            "ls" | "list" => Ok(SubCommand::ListAll),
-            // "h" | "help" => Ok(SubCommand::Help),
-            // "quit" | "exit" | "q" => Ok(SubCommand::Quit),
-            _ => Err(Error::UnknownCommand(s.to_string())),
-        }
+        Self::ALIASES.iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, sub)| *sub)
+            .ok_or_else(|| Error::UnknownCommand(s.to_string()))
     }
 }
 impl FromStr for Command {
@@ -0,0 +1,352 @@
+//! 配置文件
+//!
+//! 默认读取项目数据目录下的`config.toml`，可通过命令行参数或
+//! `DIARY_CONFIG`环境变量指定其它路径。单项配置还可以被
+//! `DIARY_DB`、`DIARY_EDITOR`、`DIARY_DATE_FORMAT`、`DIARY_IMPORT_STRATEGY`、`DIARY_THEME`、
+//! `DIARY_LANG`等环境变量覆盖。
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use time::format_description::OwnedFormatItem;
+use crate::base::date::Date;
+use crate::base::env::default_project_path;
+use crate::base::error::Error;
+use crate::base::i18n::Locale;
+use crate::model::MoodScale;
+use crate::utils::io::import::DuplicateStrategy;
+
+pub const DEFAULT_CONFIG_NAME: &str = "config.toml";
+/// 指定配置文件路径的环境变量
+pub const CONFIG_ENV: &str = "DIARY_CONFIG";
+/// 默认的日期展示格式
+pub const DEFAULT_DATE_FORMAT: &str = "[year]-[month]-[day]";
+/// 默认的自动保存间隔（秒）
+pub const DEFAULT_AUTOSAVE: u64 = 2;
+/// 自动保存间隔的上限（秒）
+pub const MAX_AUTOSAVE: u64 = 600;
+
+/// 界面主题
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+impl FromStr for Theme {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            "system" => Ok(Theme::System),
+            _ => Err(Error::Config(format!("Unknown theme: '{}'", s))),
+        }
+    }
+}
+
+/// 图形界面使用的字体
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum FontFamily {
+    /// 霞鹜文楷
+    #[default]
+    #[serde(rename = "wenkai")]
+    WenKai,
+    /// 得意黑
+    #[serde(rename = "smiley-sans")]
+    SmileySans,
+}
+impl FromStr for FontFamily {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wenkai" => Ok(FontFamily::WenKai),
+            "smiley-sans" | "smileysans" => Ok(FontFamily::SmileySans),
+            _ => Err(Error::Config(format!("Unknown font: '{}'", s))),
+        }
+    }
+}
+
+/// 图形界面的字体与字号
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FontConfig {
+    pub family: FontFamily,
+    /// 正文字号，其余文字按比例缩放
+    pub size: f32,
+}
+impl FontConfig {
+    pub const MIN_SIZE: f32 = 8.;
+    pub const MAX_SIZE: f32 = 32.;
+}
+impl Default for FontConfig {
+    fn default() -> Self {
+        Self { family: FontFamily::default(), size: 14. }
+    }
+}
+
+/// 一周从哪一天开始
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+impl WeekStart {
+    /// 把从周一数起的序号（0~6）换算为从一周第一天数起的序号
+    pub fn days_from_start(&self, days_from_monday: u8) -> u8 {
+        match self {
+            WeekStart::Monday => days_from_monday % 7,
+            WeekStart::Sunday => (days_from_monday + 1) % 7,
+        }
+    }
+    /// 一周的第一天是星期几
+    pub fn first_day(&self) -> time::Weekday {
+        match self {
+            WeekStart::Monday => time::Weekday::Monday,
+            WeekStart::Sunday => time::Weekday::Sunday,
+        }
+    }
+    /// `date`所在周的第一天
+    pub fn start_of(&self, date: Date) -> Date {
+        let days = self.days_from_start(date.weekday().number_days_from_monday());
+        date.saturating_sub(time::Duration::days(days as i64)).into()
+    }
+}
+
+/// 软件的全部配置项，缺省的项使用默认值
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 数据库文件路径，为空时使用项目数据目录下的`diary.db`
+    pub database: Option<PathBuf>,
+    /// 编辑日记所用的外部编辑器，为空时使用`$VISUAL`或`$EDITOR`
+    pub editor: Option<String>,
+    /// 日期的展示格式，语法同`time`库的格式描述
+    pub date_format: String,
+    /// 导入时遇到已存在日期的默认处理方式
+    pub import_strategy: DuplicateStrategy,
+    pub theme: Theme,
+    pub mood: MoodScale,
+    pub font: FontConfig,
+    /// 停止输入多少秒后自动保存，为0时不自动保存
+    pub autosave: u64,
+    pub week_start: WeekStart,
+    /// 界面语言，为空时按系统环境检测
+    pub language: Option<Locale>,
+    /// 图形界面的快捷键，键为操作名称，值形如`ctrl+shift+s`，空字符串表示取消
+    pub shortcuts: BTreeMap<String, String>,
+}
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database: None,
+            editor: None,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            import_strategy: DuplicateStrategy::Replace,
+            theme: Theme::default(),
+            mood: MoodScale::default(),
+            font: FontConfig::default(),
+            autosave: DEFAULT_AUTOSAVE,
+            week_start: WeekStart::default(),
+            language: None,
+            shortcuts: BTreeMap::new(),
+        }
+    }
+}
+impl Config {
+    /// 默认的配置文件路径
+    pub fn default_path() -> PathBuf {
+        default_project_path().join(DEFAULT_CONFIG_NAME)
+    }
+    /// 实际使用的配置文件路径：依次为`path`、`DIARY_CONFIG`与默认路径
+    pub fn resolve_path(path: Option<&Path>) -> PathBuf {
+        path.map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_ENV).map(PathBuf::from))
+            .unwrap_or_else(Self::default_path)
+    }
+    /// 读取配置
+    ///
+    /// `path`为`None`时依次尝试`DIARY_CONFIG`与默认路径，
+    /// 默认路径下没有配置文件时使用默认配置；显式指定的文件必须存在。
+    pub fn load(path: Option<&Path>) -> Result<Self, Error> {
+        let explicit = path.map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_ENV).map(PathBuf::from));
+        let mut config = match explicit {
+            Some(path) => Self::from_file(&path)?,
+            None => {
+                let path = Self::default_path();
+                if path.exists() { Self::from_file(&path)? } else { Self::default() }
+            }
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+    /// 只读取文件内容，不做环境变量覆盖
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+    }
+    /// 写入配置文件，目录不存在时自动创建
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| Error::Config(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+    /// 用`DIARY_*`环境变量覆盖对应的配置项
+    fn apply_env(&mut self) -> Result<(), Error> {
+        self.apply_vars(|key| std::env::var(key).ok())
+    }
+    /// 用`var`按名称取得的值覆盖对应的配置项，空值不覆盖
+    fn apply_vars(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), Error> {
+        let var = |key: &str| var(key).filter(|v| !v.trim().is_empty());
+        if let Some(db) = var("DIARY_DB") {
+            self.database = Some(PathBuf::from(db));
+        }
+        if let Some(editor) = var("DIARY_EDITOR") {
+            self.editor = Some(editor);
+        }
+        if let Some(format) = var("DIARY_DATE_FORMAT") {
+            self.date_format = format;
+        }
+        if let Some(strategy) = var("DIARY_IMPORT_STRATEGY") {
+            self.import_strategy = strategy.parse()?;
+        }
+        if let Some(theme) = var("DIARY_THEME") {
+            self.theme = theme.parse()?;
+        }
+        if let Some(language) = var("DIARY_LANG") {
+            self.language = Some(language.parse()?);
+        }
+        Ok(())
+    }
+    /// 检查各配置项是否合法
+    pub fn validate(&self) -> Result<(), Error> {
+        self.date_format_items()?;
+        if !(FontConfig::MIN_SIZE..=FontConfig::MAX_SIZE).contains(&self.font.size) {
+            return Err(Error::Config(format!(
+                "Font size must be between {} and {}, got {}", FontConfig::MIN_SIZE, FontConfig::MAX_SIZE, self.font.size)));
+        }
+        if self.autosave > MAX_AUTOSAVE {
+            return Err(Error::Config(format!(
+                "Autosave interval must be at most {} seconds, got {}", MAX_AUTOSAVE, self.autosave)));
+        }
+        if self.editor.as_deref().is_some_and(|e| e.trim().is_empty()) {
+            return Err(Error::Config("Editor must not be empty".to_string()));
+        }
+        if self.database.as_deref().is_some_and(|p| p.as_os_str().is_empty() || p.is_dir()) {
+            return Err(Error::Config("Database must be a file path".to_string()));
+        }
+        Ok(())
+    }
+    /// 解析后的日期展示格式
+    pub fn date_format_items(&self) -> Result<OwnedFormatItem, Error> {
+        time::format_description::parse_owned::<2>(&self.date_format)
+            .map_err(|e| Error::Config(format!("Invalid date format '{}': {}", self.date_format, e)))
+    }
+    /// 实际使用的界面语言：配置的语言、系统语言或英文
+    pub fn locale(&self) -> Locale {
+        self.language.or_else(Locale::detect).unwrap_or_default()
+    }
+    /// 按配置的格式展示日期，格式无效时退回默认格式
+    pub fn format_date(&self, date: Date) -> String {
+        self.date_format_items().ok()
+            .and_then(|f| date.format(&f).ok())
+            .unwrap_or_else(|| date.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_vars(vars: &[(&str, &str)]) -> Result<Config, Error> {
+        let vars = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<BTreeMap<_, _>>();
+        let mut config = Config::default();
+        config.apply_vars(|key| vars.get(key).cloned())?;
+        Ok(config)
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_bad_values() {
+        let invalid = |change: fn(&mut Config)| {
+            let mut config = Config::default();
+            change(&mut config);
+            config.validate().is_err()
+        };
+        assert!(invalid(|c| c.date_format = "[year".to_string()));
+        assert!(invalid(|c| c.font.size = FontConfig::MAX_SIZE + 1.));
+        assert!(invalid(|c| c.autosave = MAX_AUTOSAVE + 1));
+        assert!(invalid(|c| c.editor = Some(" ".to_string())));
+        assert!(invalid(|c| c.database = Some(PathBuf::new())));
+        assert!(invalid(|c| c.database = Some(std::env::temp_dir())));
+    }
+
+    #[test]
+    fn env_overrides_the_file() {
+        let config = with_vars(&[("DIARY_DB", "/tmp/other.db"), ("DIARY_THEME", "Dark"), ("DIARY_LANG", "zh_CN.UTF-8"),
+                                 ("DIARY_IMPORT_STRATEGY", "append"), ("DIARY_EDITOR", " ")]).unwrap();
+        assert_eq!(config.database, Some(PathBuf::from("/tmp/other.db")));
+        assert_eq!(config.theme, Theme::Dark);
+        assert_eq!(config.language, Some(Locale::ZhCn));
+        assert_eq!(config.import_strategy, DuplicateStrategy::Append);
+        // 空值不覆盖
+        assert_eq!(config.editor, None);
+        assert!(with_vars(&[("DIARY_THEME", "blue")]).is_err());
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(toml::from_str::<Config>("autosave = 5\nweek_start = \"sunday\"").is_ok());
+        assert!(toml::from_str::<Config>("autosaves = 5").is_err());
+        assert!(toml::from_str::<Config>("[font]\nsize = 14\ncolor = \"red\"").is_err());
+    }
+
+    #[test]
+    fn saved_settings_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join(DEFAULT_CONFIG_NAME);
+        let config = Config {
+            theme: Theme::Dark,
+            font: FontConfig { family: "Smiley-Sans".parse().unwrap(), size: 18. },
+            autosave: 0,
+            week_start: WeekStart::Sunday,
+            mood: MoodScale::Emoji,
+            language: Some(Locale::ZhCn),
+            ..Config::default()
+        };
+        config.save(&path).unwrap();
+        let loaded = Config::from_file(&path).unwrap();
+        assert_eq!(loaded.theme, Theme::Dark);
+        assert_eq!(loaded.font, config.font);
+        assert_eq!(loaded.autosave, 0);
+        assert_eq!(loaded.week_start, WeekStart::Sunday);
+        assert_eq!(loaded.mood, MoodScale::Emoji);
+        assert_eq!(loaded.language, Some(Locale::ZhCn));
+        assert!(Config::from_file(&dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn weeks_start_on_the_configured_day() {
+        // 周一为0，周日为6
+        assert_eq!(WeekStart::Monday.days_from_start(0), 0);
+        assert_eq!(WeekStart::Monday.days_from_start(6), 6);
+        assert_eq!(WeekStart::Sunday.days_from_start(6), 0);
+        assert_eq!(WeekStart::Sunday.days_from_start(0), 1);
+    }
+}
@@ -1,5 +1,6 @@
 //! 项目封装的日期类
 
+use std::fmt::Display;
 use std::ops::Deref;
 use std::str::FromStr;
 use time::format_description::BorrowedFormatItem;
@@ -12,11 +13,19 @@ pub const DATE_FORMAT1: &[BorrowedFormatItem<'static>] = time::macros::format_de
 pub const DATE_FORMAT2: &[BorrowedFormatItem<'static>] = time::macros::format_description!("[year]-[month]-[day]");
 
 /// 项目中与时间相关的操作均用此结构体表示
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Date {
     date: time::Date,
 }
 impl Date {
+    /// 可表示的最早日期
+    pub const MIN: Date = Date { date: time::Date::MIN };
+    /// 可表示的最晚日期
+    pub const MAX: Date = Date { date: time::Date::MAX };
+
+    /// `from_str`支持的日期关键字
+    pub const KEYWORDS: &'static [&'static str] = &["today", "t", "yesterday", "y", "yes", "tomorrow", "tom"];
+
     /// 通过年月日手动创建`Date`
     pub fn new(year: i32, month: u8, day: u8) -> Result<Self, time::error::Error> {
         let d = time::Date::from_calendar_date(year, Month::try_from(month)?, day)?;
@@ -48,27 +57,27 @@ impl FromStr for Date {
             "tomorrow" | "tom" => today.next_day()
                 .ok_or_else(|| Error::InvalidData("It's too large".to_string())),
             "today" | "t" => Ok(today),
+            // 八位数字是`20250101`形式的日期，不作为相隔的天数
+            _ if source.len() == 8 && source.bytes().all(|b| b.is_ascii_digit()) =>
+                time::Date::parse(source, &DATE_FORMAT1)
+                    .map_err(|_| Error::InvalidData(source.to_string())),
             _ => {
                 if let Ok(dx) = source.parse() {
                     return Ok(today.saturating_add(Duration::days(dx)).into())
                 }
                 // Regard value input as day when prefix is "m"
-                if let Some(dxs) = source.strip_prefix("m") {
-                    if let Ok(dx) = dxs.parse() {
-                        if let Ok(d) = today.replace_day(dx) {
-                            return Ok(Self { date: d });
-                        }
-                    }
+                if let Some(dxs) = source.strip_prefix("m")
+                    && let Ok(dx) = dxs.parse()
+                    && let Ok(d) = today.replace_day(dx) {
+                    return Ok(Self { date: d });
                 }
                 // Regard value input as day when prefix is "ye"
-                if let Some(dxs) = source.strip_prefix("ye") {
-                    if dxs.len() == 4 {
-                        let month = Month::try_from((dxs.as_bytes()[0]-b'0')*10+dxs.as_bytes()[1]-b'0');
-                        let day = (dxs.as_bytes()[2]-b'0')*10+dxs.as_bytes()[3]-b'0';
-
-                        if let Ok(m) = month && let Ok(d) = time::Date::from_calendar_date(today.year(), m, day) {
-                            return Ok(Self { date: d });
-                        }
+                if let Some(dxs) = source.strip_prefix("ye") && dxs.len() == 4 {
+                    let month = Month::try_from((dxs.as_bytes()[0]-b'0')*10+dxs.as_bytes()[1]-b'0');
+                    let day = (dxs.as_bytes()[2]-b'0')*10+dxs.as_bytes()[3]-b'0';
+
+                    if let Ok(m) = month && let Ok(d) = time::Date::from_calendar_date(today.year(), m, day) {
+                        return Ok(Self { date: d });
                     }
                 }
                 time::Date::parse(source, &DATE_FORMAT1)
@@ -96,4 +105,158 @@ impl Deref for Date {
     fn deref(&self) -> &Self::Target {
         &self.date
     }
-}
\ No newline at end of file
+}
+
+/// 闭区间形式的日期范围，边界为`None`时表示不设限
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct DateRange {
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+}
+impl DateRange {
+    /// `from_str`支持的范围关键字
+    pub const KEYWORDS: &'static [&'static str] = &["all", "week", "w", "month", "year"];
+
+    pub fn new(from: Option<Date>, to: Option<Date>) -> Self {
+        Self { from, to }
+    }
+    /// 某一天
+    pub fn day(date: Date) -> Self {
+        Self::new(Some(date), Some(date))
+    }
+    /// 某年某月的第一天至最后一天
+    pub fn month(year: i32, month: Month) -> Result<Self, Error> {
+        let first = time::Date::from_calendar_date(year, month, 1)
+            .map_err(|e| Error::InvalidData(e.to_string()))?;
+        let last = first.replace_day(time::util::days_in_month(month, year))
+            .map_err(|e| Error::InvalidData(e.to_string()))?;
+        Ok(Self::new(Some(first.into()), Some(last.into())))
+    }
+    /// 某年的一月一日至十二月三十一日
+    pub fn year(year: i32) -> Result<Self, Error> {
+        let first = time::Date::from_calendar_date(year, Month::January, 1)
+            .map_err(|e| Error::InvalidData(e.to_string()))?;
+        let last = time::Date::from_calendar_date(year, Month::December, 31)
+            .map_err(|e| Error::InvalidData(e.to_string()))?;
+        Ok(Self::new(Some(first.into()), Some(last.into())))
+    }
+    /// `date`所在的周一至周日
+    pub fn week_of(date: Date) -> Self {
+        let monday = date.date.saturating_sub(Duration::days(date.weekday().number_days_from_monday() as i64));
+        let sunday = monday.saturating_add(Duration::days(6));
+        Self::new(Some(monday.into()), Some(sunday.into()))
+    }
+    /// 日期是否落在范围内
+    pub fn contains(&self, date: Date) -> bool {
+        self.from.is_none_or(|f| f <= date) && self.to.is_none_or(|t| date <= t)
+    }
+    /// 下界，不设限时为`Date::MIN`
+    pub fn start(&self) -> Date {
+        self.from.unwrap_or(Date::MIN)
+    }
+    /// 上界，不设限时为`Date::MAX`
+    pub fn end(&self) -> Date {
+        self.to.unwrap_or(Date::MAX)
+    }
+}
+impl Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.from, self.to) {
+            (None, None) => write!(f, "all"),
+            (Some(from), Some(to)) if from == to => write!(f, "{}", *from),
+            (from, to) => {
+                if let Some(from) = from { write!(f, "{}", *from)?; }
+                write!(f, "..")?;
+                if let Some(to) = to { write!(f, "{}", *to)?; }
+                Ok(())
+            }
+        }
+    }
+}
+impl FromStr for DateRange {
+    type Err = Error;
+
+    /// 支持的写法：
+    /// - `all` 或空串：全部日期
+    /// - `week` / `month` / `year`：本周、本月、今年
+    /// - `2025`：某一年
+    /// - `202501` / `2025-01`：某一月
+    /// - `<date>..<date>`：两端均可省略，日期写法同`Date`
+    /// - `<date>`：某一天
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let source = source.trim();
+        let today = Date::native_time();
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        match source {
+            "" | "all" => return Ok(Self::default()),
+            "week" | "w" => return Ok(Self::week_of(today)),
+            "month" => return Self::month(today.year(), today.month()),
+            "year" => return Self::year(today.year()),
+            _ => {}
+        }
+        if let Some((from, to)) = source.split_once("..") {
+            let parse_side = |s: &str| {
+                let s = s.trim();
+                if s.is_empty() { Ok(None) } else { s.parse::<Date>().map(Some) }
+            };
+            let (from, to) = (parse_side(from)?, parse_side(to)?);
+            if let (Some(f), Some(t)) = (from, to) && f > t {
+                return Err(Error::InvalidData(format!("Range starts after it ends: '{}'", source)));
+            }
+            return Ok(Self::new(from, to));
+        }
+        if source.len() == 4 && is_digits(source) {
+            return Self::year(source.parse().map_err(|_| Error::InvalidData(source.to_string()))?);
+        }
+        let ym = source.replace('-', "");
+        if ym.len() == 6 && is_digits(&ym) && source.len() <= 7 {
+            let year = ym[..4].parse().map_err(|_| Error::InvalidData(source.to_string()))?;
+            let month = ym[4..].parse::<u8>().ok()
+                .and_then(|m| Month::try_from(m).ok())
+                .ok_or_else(|| Error::InvalidData(source.to_string()))?;
+            return Self::month(year, month);
+        }
+        Ok(Self::day(source.parse()?))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+
+    fn range(from: time::Date, to: time::Date) -> DateRange {
+        DateRange::new(Some(from.into()), Some(to.into()))
+    }
+
+    #[test]
+    fn parse_compact_and_dashed_dates() {
+        assert_eq!("20250101".parse::<Date>().unwrap(), date!(2025-01-01).into());
+        assert_eq!("2025-01-01".parse::<Date>().unwrap(), date!(2025-01-01).into());
+        assert!("20251301".parse::<Date>().is_err());
+        let today = Date::native_time();
+        assert_eq!("-1".parse::<Date>().unwrap(), today.saturating_sub(Duration::days(1)).into());
+    }
+
+    #[test]
+    fn parse_range_between_dates() {
+        let today = Date::native_time();
+        assert_eq!("20250101..t".parse::<DateRange>().unwrap(), DateRange::new(Some(date!(2025-01-01).into()), Some(today)));
+        assert_eq!("2025-01-01..20250131".parse::<DateRange>().unwrap(), range(date!(2025-01-01), date!(2025-01-31)));
+        assert_eq!("..20250131".parse::<DateRange>().unwrap(), DateRange::new(None, Some(date!(2025-01-31).into())));
+        assert_eq!("20250101".parse::<DateRange>().unwrap(), DateRange::day(date!(2025-01-01).into()));
+    }
+
+    #[test]
+    fn parse_range_of_month_and_year() {
+        assert_eq!("2024-02".parse::<DateRange>().unwrap(), range(date!(2024-02-01), date!(2024-02-29)));
+        assert_eq!("202412".parse::<DateRange>().unwrap(), range(date!(2024-12-01), date!(2024-12-31)));
+        assert_eq!("2025".parse::<DateRange>().unwrap(), range(date!(2025-01-01), date!(2025-12-31)));
+        assert!("2025-13".parse::<DateRange>().is_err());
+    }
+
+    #[test]
+    fn reject_inverted_range() {
+        assert!("20250131..20250101".parse::<DateRange>().is_err());
+        assert!("t..y".parse::<DateRange>().is_err());
+    }
+}
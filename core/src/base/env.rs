@@ -1,5 +1,7 @@
 use std::path::PathBuf;
+use crate::base::config::Config;
 use crate::base::error::Error;
+use crate::base::notebook::Notebooks;
 use crate::storage::DatabaseManager;
 
 pub const DEFAULT_DB_NAME: &str = "diary.db";
@@ -7,24 +9,57 @@ pub fn default_project_path() -> PathBuf {
     let prjdir = directories::ProjectDirs::from("x0710", "x0710", "diary")
         .expect("Could not find a valid home directory");
     let data_dir = prjdir.data_dir();
-    std::fs::create_dir_all(&data_dir)
+    std::fs::create_dir_all(data_dir)
         .expect("Could not create data directory");
     data_dir.to_path_buf()
 }
-pub fn open_with_default_database() -> Result<DatabaseManager, Error> {
+pub async fn open_with_default_database() -> Result<DatabaseManager, Error> {
     let base_dir = default_project_path();
     let db_path = base_dir.join(DEFAULT_DB_NAME);
 
-    open_with_db_file(db_path)
+    open_with_db_file(db_path).await
 }
-pub fn open_with_db_file(
+/// 按配置打开数据库，未配置路径时打开默认笔记本
+pub async fn open_with_config(config: &Config) -> Result<DatabaseManager, Error> {
+    match &config.database {
+        Some(path) => open_with_db_file(path.clone()).await,
+        None => {
+            let notebooks = Notebooks::default();
+            notebooks.open(&notebooks.default_notebook()).await
+        }
+    }
+}
+pub async fn open_with_db_file(
     db_path: PathBuf,
 ) -> Result<DatabaseManager, Error> {
+    if let Some(dir) = db_path.parent() && !dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(dir)?;
+    }
     if !db_path.exists() {
         std::fs::File::create(&db_path)?;
     }
-    Ok(DatabaseManager::from_path(&db_path)?)
+    DatabaseManager::open(&db_path).await
 }
 pub fn version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+    use crate::base::blocking::BlockingDiary;
+    use crate::model::Day;
+
+    #[test]
+    fn configured_database_is_created_with_its_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("other.db");
+        let config = Config { database: Some(path.clone()), ..Config::default() };
+        let mut diary = BlockingDiary::open_with(open_with_config(&config)).unwrap();
+        diary.add_day(&Day { date: date!(2025-01-02).into(), ..Default::default() }).unwrap();
+        drop(diary);
+        assert!(path.is_file());
+        let mut diary = BlockingDiary::open(&path).unwrap();
+        assert!(diary.read_day(date!(2025-01-02).into()).unwrap().is_some());
+    }
+}
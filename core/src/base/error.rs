@@ -8,6 +8,10 @@ pub enum Error {
     Csv(csv::Error),
     InvalidData(String),
     UnknownCommand(String),
+    /// 配置文件缺失字段或取值不合法
+    Config(String),
+    /// 导入、导出等耗时操作被用户取消
+    Cancelled,
 }
 impl From<csv::Error> for Error {
     fn from(err: csv::Error) -> Self {
@@ -11,10 +11,14 @@ impl Executor {
     pub async fn exec(&mut self, command: &Command) -> Result<Vec<Day>, Error> {
         match command {
             Command::Add(date, ctx) => {
-                let d = Day {
-                    date: *date,
-                    event: Event::new(ctx.as_deref().unwrap_or_default()),
-                    ..Default::default()
+                // 只改写正文，保留当天已有的天气、心情与指标
+                let d = match self.conn.read_day(*date).await? {
+                    Some(old) => Day { event: Event::new(ctx.as_deref().unwrap_or_default()), ..old },
+                    None => Day {
+                        date: *date,
+                        event: Event::new(ctx.as_deref().unwrap_or_default()),
+                        ..Default::default()
+                    },
                 };
                 self.conn.add_day(&d).await?;
                 Ok(vec![d])
@@ -0,0 +1,189 @@
+//! 界面文字的本地化
+//!
+//! 每种语言的消息目录是`i18n/`下的一个 TOML 文件，嵌套的表展开为以`.`连接的键。
+//! 查找顺序为当前语言、英文，都没有时返回键本身。
+//! 当前语言是进程级的设置，启动时按配置或系统环境调用[`set_locale`]。
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+use time::{Month, Weekday};
+use crate::base::error::Error;
+
+/// 支持的语言
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    /// 简体中文
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::ZhCn];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+    /// 以该语言书写的语言名称
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::ZhCn => "简体中文",
+        }
+    }
+    /// 按`LANGUAGE`、`LC_ALL`、`LC_MESSAGES`、`LANG`的顺序检测系统语言，都无法识别时为`None`
+    pub fn detect() -> Option<Locale> {
+        ["LANGUAGE", "LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|key| std::env::var(key).ok())
+            // `LANGUAGE`可以是以`:`分隔的多个语言
+            .flat_map(|value| value.split(':').map(str::to_string).collect::<Vec<_>>())
+            .find_map(|value| value.parse().ok())
+    }
+    fn catalog_source(&self) -> &'static str {
+        match self {
+            Locale::En => include_str!("i18n/en.toml"),
+            Locale::ZhCn => include_str!("i18n/zh-CN.toml"),
+        }
+    }
+    fn catalog(&self) -> &'static HashMap<String, String> {
+        static CATALOGS: [OnceLock<HashMap<String, String>>; 2] = [OnceLock::new(), OnceLock::new()];
+        CATALOGS[*self as usize].get_or_init(|| {
+            let table = toml::from_str::<toml::Table>(self.catalog_source())
+                .unwrap_or_else(|e| panic!("Invalid message catalog for {}: {}", self.code(), e));
+            let mut catalog = HashMap::new();
+            flatten("", table, &mut catalog);
+            catalog
+        })
+    }
+}
+impl FromStr for Locale {
+    type Err = Error;
+    /// 接受`zh-CN`、`zh_CN.UTF-8`、`en_US`等写法，其它中文地区也使用简体中文
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = s.split(['.', '@']).next().unwrap_or_default()
+            .to_ascii_lowercase()
+            .replace('_', "-");
+        match tag.split('-').next().unwrap_or_default() {
+            "en" | "c" | "posix" => Ok(Locale::En),
+            "zh" => Ok(Locale::ZhCn),
+            _ => Err(Error::Config(format!("Unsupported language: '{}'", s))),
+        }
+    }
+}
+
+fn flatten(prefix: &str, table: toml::Table, catalog: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::String(s) => { catalog.insert(key, s); }
+            toml::Value::Table(t) => flatten(&key, t, catalog),
+            _ => {}
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 设置当前语言
+pub fn set_locale(locale: Locale) {
+    CURRENT.store(locale as u8, Ordering::Relaxed);
+}
+pub fn locale() -> Locale {
+    Locale::ALL[CURRENT.load(Ordering::Relaxed) as usize]
+}
+
+pub(crate) fn lookup(key: &str) -> Option<&'static str> {
+    find(&[locale().catalog(), Locale::En.catalog()], key)
+}
+/// 依次在各消息目录中查找`key`
+fn find<'a>(catalogs: &[&'a HashMap<String, String>], key: &str) -> Option<&'a str> {
+    catalogs.iter().find_map(|c| c.get(key)).map(String::as_str)
+}
+/// 当前语言下`key`对应的文字
+pub fn tr(key: &str) -> &str {
+    lookup(key).unwrap_or(key)
+}
+/// 替换文字中形如`{name}`的参数
+pub fn tr_args(key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    let mut text = tr(key).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// 星期的全称
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    lookup(&format!("weekday.{}", weekday.to_string().to_ascii_lowercase())).unwrap_or_default()
+}
+/// 星期的简称，用于日历表头
+pub fn weekday_short(weekday: Weekday) -> &'static str {
+    lookup(&format!("weekday_short.{}", weekday.to_string().to_ascii_lowercase())).unwrap_or_default()
+}
+/// 月份名称
+pub fn month_name(month: Month) -> &'static str {
+    lookup(&format!("month.{}", month.to_string().to_ascii_lowercase())).unwrap_or_default()
+}
+
+/// 按当前语言查找文字，可以带`name = value`形式的参数
+///
+/// 不带参数时返回`&str`，带参数时返回`String`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::base::i18n::tr($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::base::i18n::tr_args($key, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use super::*;
+
+    /// 文字中`{name}`形式的参数名
+    fn params(text: &str) -> BTreeSet<&str> {
+        text.split('{').skip(1).filter_map(|s| s.split_once('}')).map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn locales_parse_from_system_values() {
+        assert_eq!("zh_CN.UTF-8".parse::<Locale>().unwrap(), Locale::ZhCn);
+        assert_eq!("zh-TW".parse::<Locale>().unwrap(), Locale::ZhCn);
+        assert_eq!("en_US@euro".parse::<Locale>().unwrap(), Locale::En);
+        assert_eq!("C".parse::<Locale>().unwrap(), Locale::En);
+        assert!("fr_FR".parse::<Locale>().is_err());
+        for locale in Locale::ALL {
+            assert_eq!(locale.code().parse::<Locale>().unwrap(), locale);
+        }
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_english() {
+        let local = HashMap::from([("a".to_string(), "甲".to_string())]);
+        let english = HashMap::from([("a".to_string(), "A".to_string()), ("b".to_string(), "B".to_string())]);
+        assert_eq!(find(&[&local, &english], "a"), Some("甲"));
+        assert_eq!(find(&[&local, &english], "b"), Some("B"));
+        assert_eq!(find(&[&local, &english], "c"), None);
+        assert_eq!(tr("no.such.key"), "no.such.key");
+    }
+
+    #[test]
+    fn catalogs_match_english() {
+        let english = Locale::En.catalog();
+        for locale in Locale::ALL {
+            for (key, text) in locale.catalog() {
+                let en = english.get(key).unwrap_or_else(|| panic!("{} has unknown key '{}'", locale.code(), key));
+                assert_eq!(params(text), params(en), "{} '{}'", locale.code(), key);
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! 多本日记（笔记本）的管理
+//!
+//! 默认笔记本即数据目录下原有的`diary.db`，其余笔记本以`<name>.db`
+//! 保存在数据目录的`notebooks`子目录中。
+use std::path::PathBuf;
+use crate::base::env::{default_project_path, open_with_db_file, DEFAULT_DB_NAME};
+use crate::base::error::Error;
+use crate::storage::DatabaseManager;
+
+/// 原有`diary.db`对应的笔记本名，不可重命名或删除
+pub const DEFAULT_NOTEBOOK: &str = "diary";
+const NOTEBOOK_DIR: &str = "notebooks";
+/// 记录默认笔记本名的文件
+const DEFAULT_FILE: &str = "default_notebook";
+const EXTENSION: &str = "db";
+
+/// 数据目录中的全部笔记本
+pub struct Notebooks {
+    root: PathBuf,
+}
+impl Default for Notebooks {
+    fn default() -> Self {
+        Self::new(default_project_path())
+    }
+}
+impl Notebooks {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+    /// 笔记本名只能由字母、数字、`-`和`_`组成
+    fn check_name(name: &str) -> Result<(), Error> {
+        let valid = !name.is_empty() && name.chars().count() <= 64
+            && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if valid { Ok(()) } else {
+            Err(Error::InvalidData(format!("Invalid notebook name: '{}'", name)))
+        }
+    }
+    fn ensure_exists(&self, name: &str) -> Result<(), Error> {
+        if self.exists(name) { Ok(()) } else {
+            Err(Error::InvalidData(format!("No such notebook: '{}'", name)))
+        }
+    }
+    fn ensure_not_default(name: &str) -> Result<(), Error> {
+        if name == DEFAULT_NOTEBOOK {
+            return Err(Error::InvalidData(format!("The '{}' notebook cannot be renamed or deleted", name)));
+        }
+        Ok(())
+    }
+    /// 笔记本对应的数据库文件
+    pub fn path(&self, name: &str) -> Result<PathBuf, Error> {
+        Self::check_name(name)?;
+        Ok(match name {
+            DEFAULT_NOTEBOOK => self.root.join(DEFAULT_DB_NAME),
+            _ => self.root.join(NOTEBOOK_DIR).join(name).with_extension(EXTENSION),
+        })
+    }
+    pub fn exists(&self, name: &str) -> bool {
+        // 默认笔记本总是可用，首次打开时创建
+        name == DEFAULT_NOTEBOOK || self.path(name).is_ok_and(|p| p.is_file())
+    }
+    /// 按名称排序的全部笔记本，默认笔记本总在其中
+    pub fn list(&self) -> Result<Vec<String>, Error> {
+        let mut names = vec![DEFAULT_NOTEBOOK.to_string()];
+        let dir = self.root.join(NOTEBOOK_DIR);
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|e| e == EXTENSION)
+                    && let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                    && Self::check_name(name).is_ok() && name != DEFAULT_NOTEBOOK {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names[1..].sort();
+        Ok(names)
+    }
+    /// 新建笔记本并打开
+    pub async fn create(&self, name: &str) -> Result<DatabaseManager, Error> {
+        let path = self.path(name)?;
+        if self.exists(name) {
+            return Err(Error::InvalidData(format!("Notebook '{}' already exists", name)));
+        }
+        open_with_db_file(path).await
+    }
+    /// 打开已有的笔记本
+    pub async fn open(&self, name: &str) -> Result<DatabaseManager, Error> {
+        self.ensure_exists(name)?;
+        open_with_db_file(self.path(name)?).await
+    }
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), Error> {
+        Self::ensure_not_default(from)?;
+        self.ensure_exists(from)?;
+        let target = self.path(to)?;
+        if self.exists(to) {
+            return Err(Error::InvalidData(format!("Notebook '{}' already exists", to)));
+        }
+        let was_default = self.default_notebook() == from;
+        std::fs::rename(self.path(from)?, target)?;
+        if was_default {
+            self.set_default(to)?;
+        }
+        Ok(())
+    }
+    /// 删除笔记本及其全部记录
+    pub fn delete(&self, name: &str) -> Result<(), Error> {
+        Self::ensure_not_default(name)?;
+        self.ensure_exists(name)?;
+        let was_default = self.default_notebook() == name;
+        std::fs::remove_file(self.path(name)?)?;
+        if was_default {
+            self.set_default(DEFAULT_NOTEBOOK)?;
+        }
+        Ok(())
+    }
+    /// 未指定笔记本时打开的笔记本，记录的笔记本已不存在时退回`DEFAULT_NOTEBOOK`
+    pub fn default_notebook(&self) -> String {
+        std::fs::read_to_string(self.root.join(DEFAULT_FILE))
+            .map(|s| s.trim().to_string())
+            .ok()
+            .filter(|name| self.exists(name))
+            .unwrap_or_else(|| DEFAULT_NOTEBOOK.to_string())
+    }
+    pub fn set_default(&self, name: &str) -> Result<(), Error> {
+        self.ensure_exists(name)?;
+        std::fs::write(self.root.join(DEFAULT_FILE), name)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录中建立名为`names`的笔记本文件
+    fn notebooks(names: &[&str]) -> (tempfile::TempDir, Notebooks) {
+        let dir = tempfile::tempdir().unwrap();
+        let notebooks = Notebooks::new(dir.path().to_path_buf());
+        std::fs::create_dir_all(dir.path().join(NOTEBOOK_DIR)).unwrap();
+        for name in names {
+            std::fs::write(notebooks.path(name).unwrap(), "").unwrap();
+        }
+        (dir, notebooks)
+    }
+
+    #[test]
+    fn list_puts_the_default_notebook_first() {
+        let (dir, notebooks) = notebooks(&["work", "travel"]);
+        std::fs::write(dir.path().join(NOTEBOOK_DIR).join("notes.txt"), "").unwrap();
+        assert_eq!(notebooks.list().unwrap(), [DEFAULT_NOTEBOOK, "travel", "work"]);
+        assert!(notebooks.path("../escape").is_err());
+        assert!(notebooks.path("").is_err());
+    }
+
+    #[test]
+    fn rename_keeps_the_default() {
+        let (_dir, notebooks) = notebooks(&["work", "travel"]);
+        notebooks.set_default("work").unwrap();
+        notebooks.rename("work", "job").unwrap();
+        assert_eq!(notebooks.default_notebook(), "job");
+        assert!(!notebooks.exists("work"));
+        assert!(notebooks.rename("job", "travel").is_err());
+        assert!(notebooks.rename(DEFAULT_NOTEBOOK, "other").is_err());
+        assert!(notebooks.rename("missing", "other").is_err());
+    }
+
+    #[test]
+    fn delete_falls_back_to_the_default_notebook() {
+        let (_dir, notebooks) = notebooks(&["work"]);
+        assert!(notebooks.set_default("missing").is_err());
+        notebooks.set_default("work").unwrap();
+        notebooks.delete("work").unwrap();
+        assert_eq!(notebooks.default_notebook(), DEFAULT_NOTEBOOK);
+        assert_eq!(notebooks.list().unwrap(), [DEFAULT_NOTEBOOK]);
+        assert!(notebooks.delete(DEFAULT_NOTEBOOK).is_err());
+    }
+}
@@ -1,22 +1,70 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 use std::ops::Deref;
-use crate::model::Event;
+use crate::model::{Event, MetricValue, Mood, MoodScale, Weather};
+use crate::base::config::Config;
 use crate::base::date::Date;
+use crate::base::i18n::weekday_name;
+use crate::t;
 
 /// 某一天的完整记录(DTO)
 #[derive(Debug, Clone)]
 pub struct Day {
     pub date: Date,
     pub event: Event,
-    pub weather: Option<String>,
-    pub mood: Option<f64>,
+    pub weather: Option<Weather>,
+    pub mood: Option<Mood>,
+    /// 自定义指标的取值，按名称排序
+    pub metrics: BTreeMap<String, MetricValue>,
+    /// 当天完成的习惯
+    pub habits: BTreeSet<String>,
+}
+impl Day {
+    /// 把内容接在已有记录`old`之后，天气、心情与指标以已有的为准，完成的习惯取两者之和
+    pub fn merge_into(mut self, old: Day) -> Day {
+        if !old.event.instruct.is_empty() && !self.event.instruct.is_empty() {
+            self.event.instruct = format!("{}\n{}", old.event.instruct, self.event.instruct);
+        } else if self.event.instruct.is_empty() {
+            self.event = old.event;
+        }
+        self.weather = old.weather.or(self.weather);
+        self.mood = old.mood.or(self.mood);
+        self.metrics.extend(old.metrics);
+        self.habits.extend(old.habits);
+        self
+    }
+    /// 正文、天气、心情、指标与习惯都为空
+    pub fn is_empty(&self) -> bool {
+        self.event.instruct.trim().is_empty() && self.weather.is_none() && self.mood.is_none()
+            && self.metrics.is_empty() && self.habits.is_empty()
+    }
+    /// 按配置中的日期格式展示
+    pub fn display_with(&self, config: &Config) -> DayDisplay<'_> {
+        DayDisplay { day: self, date: config.format_date(self.date), scale: config.mood }
+    }
 }
 impl Display for Day {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[Date: {}, {}]", self.date.deref(), self.date.weekday())?;
-        if let Some(w) = &self.weather { write!(f, " [Weather: {}]", w)?; }
-        if let Some(m) = &self.mood { write!(f, " [Mood: {}]", m)?; }
-        write!(f, "\nEvent: {}", self.event)
+        DayDisplay { day: self, date: self.date.deref().to_string(), scale: MoodScale::default() }.fmt(f)
+    }
+}
+/// 日期与心情已按指定格式展示的`Day`
+pub struct DayDisplay<'a> {
+    day: &'a Day,
+    date: String,
+    scale: MoodScale,
+}
+impl Display for DayDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let day = self.day;
+        write!(f, "[{}: {}, {}]", t!("day.date"), self.date, weekday_name(day.date.weekday()))?;
+        if let Some(w) = &day.weather { write!(f, " [{}: {}]", t!("day.weather"), w.describe())?; }
+        if let Some(m) = day.mood { write!(f, " [{}: {} {}]", t!("day.mood"), self.scale.format(m), m.label())?; }
+        for (name, value) in &day.metrics { write!(f, " [{}: {}]", name, value.describe())?; }
+        if !day.habits.is_empty() {
+            write!(f, " [{}: {}]", t!("day.habits"), day.habits.iter().map(String::as_str).collect::<Vec<_>>().join(", "))?;
+        }
+        write!(f, "\n{}: {}", t!("day.event"), day.event)
     }
 }
 impl Default for Day {
@@ -27,6 +75,45 @@ impl Default for Day {
             event: Event::default(),
             weather: None,
             mood: None,
+            metrics: BTreeMap::new(),
+            habits: BTreeSet::new(),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Condition;
+
+    fn day(event: &str) -> Day {
+        Day { event: Event::new(event), ..Default::default() }
+    }
+
+    #[test]
+    fn merge_appends_event_and_keeps_existing_details() {
+        let mut old = day("morning");
+        old.mood = Some(Mood::new(3.).unwrap());
+        old.metrics.insert("steps".to_string(), MetricValue::Number(100.));
+        old.habits.insert("read".to_string());
+        let mut new = day("evening");
+        new.weather = Some(Condition::Rainy.into());
+        new.mood = Some(Mood::new(8.).unwrap());
+        new.metrics.insert("steps".to_string(), MetricValue::Number(200.));
+        new.metrics.insert("sleep".to_string(), MetricValue::Number(7.));
+        new.habits.insert("run".to_string());
+
+        let merged = new.merge_into(old);
+        assert_eq!(merged.event.instruct, "morning\nevening");
+        assert_eq!(merged.weather, Some(Condition::Rainy.into()));
+        assert_eq!(merged.mood, Some(Mood::new(3.).unwrap()));
+        assert_eq!(merged.metrics["steps"], MetricValue::Number(100.));
+        assert_eq!(merged.metrics["sleep"], MetricValue::Number(7.));
+        assert_eq!(merged.habits.iter().map(String::as_str).collect::<Vec<_>>(), ["read", "run"]);
+    }
+
+    #[test]
+    fn merge_with_empty_event() {
+        assert_eq!(day("").merge_into(day("old")).event.instruct, "old");
+        assert_eq!(day("new").merge_into(day("")).event.instruct, "new");
+    }
+}
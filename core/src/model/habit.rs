@@ -0,0 +1,83 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::base::date::Date;
+use crate::base::error::Error;
+use crate::t;
+
+/// 习惯的目标频率
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Frequency {
+    /// 每天
+    #[default]
+    Daily,
+    /// 每周若干次，1~7，一周从配置的`week_start`开始
+    Weekly(u8),
+}
+impl Frequency {
+    /// 每周的目标次数
+    pub fn per_week(&self) -> u8 {
+        match self {
+            Frequency::Daily => 7,
+            Frequency::Weekly(n) => *n,
+        }
+    }
+    /// 当前语言下的说明
+    pub fn label(&self) -> String {
+        match self {
+            Frequency::Daily => t!("habit.daily").to_string(),
+            Frequency::Weekly(n) => t!("habit.weekly", n = n),
+        }
+    }
+}
+impl Display for Frequency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Frequency::Daily => write!(f, "daily"),
+            Frequency::Weekly(n) => write!(f, "{}/week", n),
+        }
+    }
+}
+impl FromStr for Frequency {
+    type Err = Error;
+    /// 接受`daily`、`weekly`（每周一次）与`3/week`、`3x/week`、`3/w`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let invalid = || Error::InvalidData(format!("Invalid frequency: '{}', expected daily or 1/week ~ 7/week", s));
+        match s.as_str() {
+            "daily" | "day" | "d" => return Ok(Frequency::Daily),
+            "weekly" | "week" | "w" => return Ok(Frequency::Weekly(1)),
+            _ => {}
+        }
+        let times = s.strip_suffix("/week").or_else(|| s.strip_suffix("/w")).ok_or_else(invalid)?;
+        let times = times.trim_end_matches('x').trim().parse::<u8>().map_err(|_| invalid())?;
+        match times {
+            1..=7 => Ok(Frequency::Weekly(times)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// 习惯的定义，保存在数据库中
+#[derive(Debug, Clone, PartialEq)]
+pub struct Habit {
+    pub name: String,
+    pub frequency: Frequency,
+    /// 开始养成的日期，完成率从这一天算起
+    pub since: Date,
+}
+impl Habit {
+    /// 检查名称后创建从今天开始的习惯
+    pub fn new(name: &str, frequency: Frequency) -> Result<Self, Error> {
+        check_name(name)?;
+        Ok(Self { name: name.to_string(), frequency, since: Date::native_time() })
+    }
+}
+
+/// 习惯名称只能包含字母、数字、`-`与`_`
+pub fn check_name(name: &str) -> Result<(), Error> {
+    let valid = !name.is_empty() && name.chars().count() <= 32
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid { Ok(()) } else {
+        Err(Error::InvalidData(format!("Invalid habit name: '{}'", name)))
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::base::error::Error;
+use crate::base::i18n::lookup;
+
+/// 自定义指标的类型
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum MetricKind {
+    /// 数值，如睡眠时长、精力
+    #[default]
+    Number,
+    /// 是/否，如是否运动
+    Bool,
+    /// 一小段文字
+    Text,
+}
+impl MetricKind {
+    pub const ALL: [MetricKind; 3] = [MetricKind::Number, MetricKind::Bool, MetricKind::Text];
+
+    /// 保存到数据库时使用的名称
+    pub fn code(&self) -> &'static str {
+        match self {
+            MetricKind::Number => "number",
+            MetricKind::Bool => "bool",
+            MetricKind::Text => "text",
+        }
+    }
+    /// 当前语言下的名称
+    pub fn label(&self) -> &'static str {
+        lookup(&format!("metric.{}", self.code())).unwrap_or(self.code())
+    }
+    /// 按该类型解析输入的文字
+    pub fn parse(&self, s: &str) -> Result<MetricValue, Error> {
+        let s = s.trim();
+        match self {
+            MetricKind::Number => s.parse::<f64>().ok()
+                .filter(|v| v.is_finite())
+                .map(MetricValue::Number)
+                .ok_or_else(|| Error::InvalidData(format!("Not a number: '{}'", s))),
+            MetricKind::Bool => parse_bool(s)
+                .map(MetricValue::Bool)
+                .ok_or_else(|| Error::InvalidData(format!("Expected yes or no, got '{}'", s))),
+            MetricKind::Text => Ok(MetricValue::Text(s.to_string())),
+        }
+    }
+    /// 把`value`转换为该类型，无法转换时报错
+    pub fn coerce(&self, value: MetricValue) -> Result<MetricValue, Error> {
+        if value.kind() == *self { Ok(value) } else { self.parse(&value.to_string()) }
+    }
+}
+impl Display for MetricKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+impl FromStr for MetricKind {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "number" | "num" | "n" => Ok(MetricKind::Number),
+            "bool" | "boolean" | "yes/no" | "b" => Ok(MetricKind::Bool),
+            "text" | "t" => Ok(MetricKind::Text),
+            _ => Err(Error::InvalidData(format!("Unknown metric type: '{}', expected number, bool or text", s))),
+        }
+    }
+}
+
+/// 指标的定义，保存在数据库中
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDef {
+    pub name: String,
+    pub kind: MetricKind,
+    /// 数值的单位，如`h`，可以为空
+    pub unit: String,
+}
+impl MetricDef {
+    /// 检查名称后创建定义
+    pub fn new(name: &str, kind: MetricKind, unit: &str) -> Result<Self, Error> {
+        check_name(name)?;
+        Ok(Self { name: name.to_string(), kind, unit: unit.trim().to_string() })
+    }
+    /// 按定义展示取值，数值带单位
+    pub fn format(&self, value: &MetricValue) -> String {
+        match value {
+            MetricValue::Number(_) if !self.unit.is_empty() => format!("{} {}", value, self.unit),
+            _ => value.describe(),
+        }
+    }
+}
+
+/// 某一天某个指标的取值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MetricValue {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+}
+impl MetricValue {
+    pub fn kind(&self) -> MetricKind {
+        match self {
+            MetricValue::Number(_) => MetricKind::Number,
+            MetricValue::Bool(_) => MetricKind::Bool,
+            MetricValue::Text(_) => MetricKind::Text,
+        }
+    }
+    /// 按内容推断类型：数字视为数值，`yes`/`no`等视为是/否，其余视为文字
+    pub fn infer(s: &str) -> MetricValue {
+        let s = s.trim();
+        if let Ok(v) = MetricKind::Number.parse(s) {
+            v
+        } else if let Some(b) = parse_bool(s) {
+            MetricValue::Bool(b)
+        } else {
+            MetricValue::Text(s.to_string())
+        }
+    }
+    /// 空文字视为没有取值
+    pub fn is_empty(&self) -> bool {
+        matches!(self, MetricValue::Text(s) if s.trim().is_empty())
+    }
+    /// 按当前语言展示，是/否翻译为对应的文字
+    pub fn describe(&self) -> String {
+        match self {
+            MetricValue::Bool(b) => {
+                let key = if *b { "yes" } else { "no" };
+                lookup(&format!("metric.{}", key)).unwrap_or(key).to_string()
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+impl Display for MetricValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetricValue::Number(v) => write!(f, "{}", v),
+            MetricValue::Bool(b) => write!(f, "{}", if *b { "yes" } else { "no" }),
+            MetricValue::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// 指标名称只能包含字母、数字、`-`与`_`，且不能是纯数字
+pub fn check_name(name: &str) -> Result<(), Error> {
+    let valid = !name.is_empty() && name.chars().count() <= 32
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        && name.parse::<f64>().is_err();
+    if valid { Ok(()) } else {
+        Err(Error::InvalidData(format!("Invalid metric name: '{}'", name)))
+    }
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.to_lowercase().as_str() {
+        "yes" | "y" | "true" | "on" | "是" | "✓" | "✔" => Some(true),
+        "no" | "n" | "false" | "off" | "否" | "✗" | "✘" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infer_kind_from_text() {
+        assert_eq!(MetricValue::infer(" 7.5 "), MetricValue::Number(7.5));
+        assert_eq!(MetricValue::infer("-2"), MetricValue::Number(-2.));
+        assert_eq!(MetricValue::infer("Yes"), MetricValue::Bool(true));
+        assert_eq!(MetricValue::infer("否"), MetricValue::Bool(false));
+        assert_eq!(MetricValue::infer("inf"), MetricValue::Text("inf".to_string()));
+        assert_eq!(MetricValue::infer("went swimming"), MetricValue::Text("went swimming".to_string()));
+        assert!(MetricValue::infer("  ").is_empty());
+    }
+
+    #[test]
+    fn coerce_to_defined_kind() {
+        assert_eq!(MetricKind::Text.coerce(MetricValue::Number(3.)).unwrap(), MetricValue::Text("3".to_string()));
+        assert_eq!(MetricKind::Bool.coerce(MetricValue::Text("y".to_string())).unwrap(), MetricValue::Bool(true));
+        assert!(MetricKind::Number.coerce(MetricValue::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn metric_names() {
+        assert!(check_name("sleep_h").is_ok() && check_name("睡眠").is_ok());
+        assert!(check_name("").is_err() && check_name("42").is_err() && check_name("a b").is_err());
+    }
+}
@@ -1,5 +1,13 @@
 pub mod day;
 pub mod event;
+pub mod habit;
+pub mod metric;
+pub mod mood;
+pub mod weather;
 
 pub use day::Day;
-pub use event::Event;
\ No newline at end of file
+pub use event::Event;
+pub use habit::{Frequency, Habit};
+pub use metric::{MetricDef, MetricKind, MetricValue};
+pub use mood::{Mood, MoodScale};
+pub use weather::{Condition, Weather};
\ No newline at end of file
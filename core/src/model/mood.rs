@@ -0,0 +1,210 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::base::error::Error;
+use crate::base::i18n::lookup;
+
+/// 心情，统一以0~10保存，界面中按[`MoodScale`]换算
+///
+/// 没有记录心情时用`Option<Mood>`的`None`表示，0是合法的心情值
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Mood(f64);
+impl Mood {
+    pub const MIN: f64 = 0.;
+    pub const MAX: f64 = 10.;
+
+    /// 检查取值是否在0~10之间
+    pub fn new(value: f64) -> Result<Mood, Error> {
+        if value.is_finite() && (Self::MIN..=Self::MAX).contains(&value) {
+            Ok(Mood(value))
+        } else {
+            Err(Error::InvalidData(format!("Mood must be between {} and {}, got {}", Self::MIN, Self::MAX, value)))
+        }
+    }
+    /// 超出范围的值按边界处理，用于读取旧数据
+    pub fn clamped(value: f64) -> Mood {
+        Mood(if value.is_nan() { Self::MIN } else { value.clamp(Self::MIN, Self::MAX) })
+    }
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+    /// 五档中的第几档（0~4），用于名称与表情
+    pub fn level(&self) -> usize {
+        ((self.0 / Self::MAX * 5.) as usize).min(4)
+    }
+    /// 当前语言下的名称
+    pub fn label(&self) -> &'static str {
+        let key = ["awful", "bad", "okay", "good", "great"][self.level()];
+        lookup(&format!("mood.{}", key)).unwrap_or(key)
+    }
+}
+impl Display for Mood {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl FromStr for Mood {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim().parse::<f64>()
+            .map_err(|_| Error::InvalidData(format!("Invalid mood: '{}'", s)))?;
+        Mood::new(value)
+    }
+}
+
+/// 输入与展示心情时使用的刻度
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum MoodScale {
+    /// 1~5分
+    OneToFive,
+    /// 0~10分，与保存的值相同
+    #[default]
+    ZeroToTen,
+    /// 五个表情
+    Emoji,
+}
+impl MoodScale {
+    pub const ALL: [MoodScale; 3] = [MoodScale::OneToFive, MoodScale::ZeroToTen, MoodScale::Emoji];
+    /// 从低到高的五个表情
+    pub const EMOJI: [&'static str; 5] = ["😞", "🙁", "😐", "🙂", "😄"];
+
+    /// 配置文件中的写法
+    pub fn code(&self) -> &'static str {
+        match self {
+            MoodScale::OneToFive => "1-5",
+            MoodScale::ZeroToTen => "0-10",
+            MoodScale::Emoji => "emoji",
+        }
+    }
+    /// 刻度上的最小值与最大值，表情按1~5计
+    pub fn range(&self) -> (f64, f64) {
+        match self {
+            MoodScale::ZeroToTen => (0., 10.),
+            MoodScale::OneToFive | MoodScale::Emoji => (1., 5.),
+        }
+    }
+    /// 刻度上相邻两档的间隔
+    pub fn step(&self) -> f64 {
+        match self {
+            MoodScale::ZeroToTen => 0.5,
+            MoodScale::OneToFive | MoodScale::Emoji => 1.,
+        }
+    }
+    /// 把心情换算到该刻度
+    pub fn to_scale(&self, mood: Mood) -> f64 {
+        let (min, max) = self.range();
+        min + mood.value() / Mood::MAX * (max - min)
+    }
+    /// 把该刻度上的值换算为心情，超出范围时报错
+    pub fn from_scale(&self, value: f64) -> Result<Mood, Error> {
+        let (min, max) = self.range();
+        if !(value.is_finite() && (min..=max).contains(&value)) {
+            return Err(Error::InvalidData(format!("Mood must be between {} and {}, got {}", min, max, value)));
+        }
+        Mood::new((value - min) / (max - min) * Mood::MAX)
+    }
+    /// 按该刻度展示，如`4`、`7.5`或`🙂`
+    pub fn format(&self, mood: Mood) -> String {
+        match self {
+            MoodScale::Emoji => Self::EMOJI[self.to_scale(mood).round() as usize - 1].to_string(),
+            _ => format!("{:.1}", self.to_scale(mood)).trim_end_matches(".0").to_string(),
+        }
+    }
+    /// 解析该刻度上的数值，表情刻度也接受表情本身
+    pub fn parse(&self, s: &str) -> Result<Mood, Error> {
+        let s = s.trim();
+        if let Some(i) = Self::EMOJI.iter().position(|e| *e == s) {
+            return MoodScale::Emoji.from_scale(i as f64 + 1.);
+        }
+        let value = s.parse::<f64>().map_err(|_| Error::InvalidData(format!("Invalid mood: '{}'", s)))?;
+        self.from_scale(value)
+    }
+}
+impl Display for MoodScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+impl FromStr for MoodScale {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MoodScale::ALL.into_iter()
+            .find(|scale| scale.code().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::Config(format!("Unknown mood scale: '{}', expected 1-5, 0-10 or emoji", s)))
+    }
+}
+impl Serialize for MoodScale {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+impl<'de> Deserialize<'de> for MoodScale {
+    /// 也接受旧版本的`{ min = 1, max = 5 }`写法，最大值不超过5时视为1~5分，其余视为0~10分
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Name(String),
+            Range { max: Option<f64> },
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Name(name) => name.parse().map_err(serde::de::Error::custom),
+            Raw::Range { max: Some(max) } if max <= 5. => Ok(MoodScale::OneToFive),
+            Raw::Range { .. } => Ok(MoodScale::ZeroToTen),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mood(value: f64) -> Mood {
+        Mood::new(value).unwrap()
+    }
+
+    #[test]
+    fn mood_range() {
+        assert!(Mood::new(0.).is_ok() && Mood::new(10.).is_ok());
+        assert!(Mood::new(-0.1).is_err() && Mood::new(10.5).is_err() && Mood::new(f64::NAN).is_err());
+        assert_eq!(Mood::clamped(12.), mood(10.));
+        assert_eq!(Mood::clamped(f64::NAN), mood(0.));
+    }
+
+    #[test]
+    fn convert_between_scales() {
+        assert_eq!(MoodScale::OneToFive.to_scale(mood(0.)), 1.);
+        assert_eq!(MoodScale::OneToFive.to_scale(mood(10.)), 5.);
+        assert_eq!(MoodScale::OneToFive.from_scale(3.).unwrap(), mood(5.));
+        assert_eq!(MoodScale::ZeroToTen.from_scale(7.5).unwrap(), mood(7.5));
+        assert!(MoodScale::OneToFive.from_scale(0.).is_err());
+        assert!(MoodScale::Emoji.from_scale(6.).is_err());
+        for scale in MoodScale::ALL {
+            let (min, max) = scale.range();
+            assert_eq!(scale.to_scale(scale.from_scale(min).unwrap()), min);
+            assert_eq!(scale.to_scale(scale.from_scale(max).unwrap()), max);
+        }
+    }
+
+    #[test]
+    fn format_and_parse() {
+        assert_eq!(MoodScale::ZeroToTen.format(mood(7.5)), "7.5");
+        assert_eq!(MoodScale::ZeroToTen.format(mood(7.)), "7");
+        assert_eq!(MoodScale::OneToFive.format(mood(7.5)), "4");
+        assert_eq!(MoodScale::Emoji.format(mood(7.5)), "🙂");
+        assert_eq!(MoodScale::Emoji.format(mood(0.)), "😞");
+        assert_eq!(MoodScale::Emoji.parse("🙂").unwrap(), mood(7.5));
+        assert_eq!(MoodScale::ZeroToTen.parse("😄").unwrap(), mood(10.));
+        assert_eq!(MoodScale::OneToFive.parse(" 2 ").unwrap(), mood(2.5));
+        assert!(MoodScale::OneToFive.parse("great").is_err());
+    }
+
+    #[test]
+    fn scale_codes() {
+        for scale in MoodScale::ALL {
+            assert_eq!(scale.code().parse::<MoodScale>().unwrap(), scale);
+        }
+        assert_eq!("EMOJI".parse::<MoodScale>().unwrap(), MoodScale::Emoji);
+        assert!("1-10".parse::<MoodScale>().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::base::error::Error;
+use crate::base::i18n::lookup;
+
+/// 规范的天气状况
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Condition {
+    Sunny,
+    Cloudy,
+    Overcast,
+    Rainy,
+    Stormy,
+    Snowy,
+    Foggy,
+    Windy,
+}
+impl Condition {
+    pub const ALL: [Condition; 8] = [
+        Condition::Sunny, Condition::Cloudy, Condition::Overcast, Condition::Rainy,
+        Condition::Stormy, Condition::Snowy, Condition::Foggy, Condition::Windy,
+    ];
+
+    /// 保存到数据库与导出时使用的名称
+    pub fn code(&self) -> &'static str {
+        match self {
+            Condition::Sunny => "sunny",
+            Condition::Cloudy => "cloudy",
+            Condition::Overcast => "overcast",
+            Condition::Rainy => "rainy",
+            Condition::Stormy => "stormy",
+            Condition::Snowy => "snowy",
+            Condition::Foggy => "foggy",
+            Condition::Windy => "windy",
+        }
+    }
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Condition::Sunny => "☀",
+            Condition::Cloudy => "⛅",
+            Condition::Overcast => "☁",
+            Condition::Rainy => "🌧",
+            Condition::Stormy => "⛈",
+            Condition::Snowy => "❄",
+            Condition::Foggy => "🌫",
+            Condition::Windy => "💨",
+        }
+    }
+    /// 当前语言下的名称
+    pub fn label(&self) -> &'static str {
+        lookup(&format!("weather.{}", self.code())).unwrap_or(self.code())
+    }
+    /// 旧数据中的写法，不区分大小写
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Condition::Sunny => &["sun", "clear", "fine", "fair", "晴", "晴天", "晴朗"],
+            Condition::Cloudy => &["partly cloudy", "mostly cloudy", "clouds", "多云", "少云", "晴转多云"],
+            Condition::Overcast => &["grey", "gray", "阴", "阴天"],
+            Condition::Rainy => &["rain", "drizzle", "shower", "showers", "雨", "小雨", "中雨", "大雨", "阵雨", "下雨"],
+            Condition::Stormy => &["storm", "thunder", "thunderstorm", "雷雨", "雷阵雨", "暴雨"],
+            Condition::Snowy => &["snow", "sleet", "雪", "小雪", "大雪", "下雪", "雨夹雪"],
+            Condition::Foggy => &["fog", "mist", "misty", "haze", "hazy", "smog", "雾", "霾", "雾霾"],
+            Condition::Windy => &["wind", "breezy", "风", "大风", "有风"],
+        }
+    }
+}
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+impl FromStr for Condition {
+    type Err = Error;
+    /// 接受规范名称、图标与常见的中英文写法
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        Condition::ALL.into_iter()
+            .find(|c| c.code() == name || c.icon() == name || c.aliases().contains(&name.as_str()))
+            .ok_or_else(|| Error::InvalidData(format!("Unknown weather: '{}'", s)))
+    }
+}
+
+/// 某一天的天气：状况、气温（摄氏度）与备注，都可以为空
+///
+/// 以`sunny, 23°C, 傍晚起风`的形式保存，读取旧数据时按[`Weather::parse`]规范化
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Weather {
+    pub condition: Option<Condition>,
+    pub temperature: Option<f64>,
+    pub note: String,
+}
+impl Weather {
+    pub fn is_empty(&self) -> bool {
+        self.condition.is_none() && self.temperature.is_none() && self.note.trim().is_empty()
+    }
+    /// 解析保存的或旧的自由文本，内容为空时返回`None`
+    ///
+    /// 按`,`、`，`、`;`分段：第一个可识别的状况与气温分别作为状况和气温，其余部分作为备注。
+    /// 无法识别的文本都保留在备注中，不会丢失
+    pub fn parse(s: &str) -> Option<Weather> {
+        let mut weather = Weather::default();
+        let mut notes = Vec::new();
+        for part in s.split([',', '，', ';', '；']).map(str::trim).filter(|p| !p.is_empty()) {
+            if !weather.take(part) {
+                // `Sunny 23°C`这样以空格分隔的写法
+                let words = part.split_whitespace().collect::<Vec<_>>();
+                let mut trial = weather.clone();
+                if words.len() < 2 || !words.iter().all(|w| trial.take(w)) {
+                    notes.push(part);
+                    continue;
+                }
+                weather = trial;
+            }
+        }
+        weather.note = notes.join(", ");
+        (!weather.is_empty()).then_some(weather)
+    }
+    /// 把`part`作为还没有值的状况或气温，成功时返回`true`
+    fn take(&mut self, part: &str) -> bool {
+        if self.condition.is_none() && let Ok(condition) = part.parse() {
+            self.condition = Some(condition);
+            true
+        } else if self.temperature.is_none() && let Some(t) = parse_temperature(part) {
+            self.temperature = Some(t);
+            true
+        } else {
+            false
+        }
+    }
+    /// 按当前语言展示，状况带图标
+    pub fn describe(&self) -> String {
+        self.join(|c| format!("{} {}", c.icon(), c.label()))
+    }
+    fn join(&self, condition: impl Fn(Condition) -> String) -> String {
+        let mut parts = Vec::new();
+        if let Some(c) = self.condition { parts.push(condition(c)) }
+        if let Some(t) = self.temperature { parts.push(format!("{}°C", t)) }
+        if !self.note.trim().is_empty() { parts.push(self.note.trim().to_string()) }
+        parts.join(", ")
+    }
+}
+impl Display for Weather {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.join(|c| c.code().to_string()))
+    }
+}
+impl From<Condition> for Weather {
+    fn from(condition: Condition) -> Self {
+        Self { condition: Some(condition), ..Default::default() }
+    }
+}
+
+/// 解析`23°C`、`23℃`、`23度`、`73°F`等写法，华氏度换算为摄氏度并保留一位小数
+///
+/// 必须带单位，`rain, 3`中不带单位的数字不视为气温
+fn parse_temperature(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let (number, fahrenheit) = if let Some(n) = ["°F", "°f", "℉", "F", "f"].iter().find_map(|suffix| s.strip_suffix(suffix)) {
+        (n, true)
+    } else {
+        let n = ["°C", "°c", "℃", "°", "度", "C", "c"].iter()
+            .find_map(|suffix| s.strip_suffix(suffix))?;
+        (n, false)
+    };
+    let value = number.trim().parse::<f64>().ok().filter(|v| v.is_finite())?;
+    let celsius = if fahrenheit { (value - 32.) * 5. / 9. } else { value };
+    Some((celsius * 10.).round() / 10.).filter(|t| (-100.0..=100.0).contains(t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weather(condition: Option<Condition>, temperature: Option<f64>, note: &str) -> Weather {
+        Weather { condition, temperature, note: note.to_string() }
+    }
+
+    #[test]
+    fn temperature_needs_a_unit() {
+        assert_eq!(parse_temperature("23°C"), Some(23.));
+        assert_eq!(parse_temperature("-3℃"), Some(-3.));
+        assert_eq!(parse_temperature("18度"), Some(18.));
+        assert_eq!(parse_temperature("73°F"), Some(22.8));
+        assert_eq!(parse_temperature("3"), None);
+        assert_eq!(parse_temperature("500°C"), None);
+    }
+
+    #[test]
+    fn parse_condition_temperature_and_note() {
+        assert_eq!(Weather::parse("sunny, 23°C, 傍晚起风"), Some(weather(Some(Condition::Sunny), Some(23.), "傍晚起风")));
+        assert_eq!(Weather::parse("Rain 12C"), Some(weather(Some(Condition::Rainy), Some(12.), "")));
+        assert_eq!(Weather::parse("晴，18度"), Some(weather(Some(Condition::Sunny), Some(18.), "")));
+        assert_eq!(Weather::parse("rain, 3"), Some(weather(Some(Condition::Rainy), None, "3")));
+        assert_eq!(Weather::parse("too hot to think"), Some(weather(None, None, "too hot to think")));
+        assert_eq!(Weather::parse(" , "), None);
+    }
+
+    #[test]
+    fn saved_form_parses_back() {
+        let w = weather(Some(Condition::Foggy), Some(-1.5), "morning only");
+        assert_eq!(w.to_string(), "foggy, -1.5°C, morning only");
+        assert_eq!(Weather::parse(&w.to_string()), Some(w));
+    }
+}
@@ -1,65 +1,466 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::{Deref, DerefMut};
 use sqlx::{Connection, Executor, Row, SqliteConnection};
-use sqlx::sqlite::SqliteRow;
-use crate::model::Day;
-use crate::base::date::Date;
+use sqlx::sqlite::{Sqlite, SqliteRow};
+use crate::model::{Day, Habit, MetricDef, MetricKind, MetricValue, Mood, Weather};
+use crate::base::date::{Date, DateRange};
 use crate::base::date::DATE_FORMAT1;
 use crate::base::error::Error;
 
+/// 移动或复制到的日期已有记录时的处理方式
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum MoveMode {
+    /// 目标日期已有记录时失败
+    #[default]
+    Fail,
+    /// 接在目标日期的内容之后，天气、心情与指标以目标日期的为准，完成的习惯合并
+    Merge,
+    /// 覆盖目标日期的记录
+    Overwrite,
+}
+
 pub struct DatabaseManager {
     pub(crate) conn: SqliteConnection,
 }
 impl DatabaseManager {
-    pub fn from_path(path: &std::path::Path) -> Result<Self, Error> {
+    /// 连接数据库文件并建表，可在任意异步运行时中使用
+    pub async fn open(path: &std::path::Path) -> Result<Self, Error> {
         let init_query = sqlx::query(r"
         CREATE TABLE IF NOT EXISTS day (
             date TEXT NOT NULL PRIMARY KEY,
             event TEXT,
             weather TEXT,
-            mood DOUBLE)
+            mood DOUBLE);
+        CREATE TABLE IF NOT EXISTS metric_def (
+            name TEXT NOT NULL PRIMARY KEY,
+            kind TEXT NOT NULL,
+            unit TEXT NOT NULL DEFAULT '');
+        CREATE TABLE IF NOT EXISTS metric (
+            date TEXT NOT NULL,
+            name TEXT NOT NULL,
+            value,
+            PRIMARY KEY (date, name));
+        CREATE TABLE IF NOT EXISTS habit (
+            name TEXT NOT NULL PRIMARY KEY,
+            frequency TEXT NOT NULL,
+            since TEXT NOT NULL);
+        CREATE TABLE IF NOT EXISTS habit_check (
+            date TEXT NOT NULL,
+            name TEXT NOT NULL,
+            PRIMARY KEY (date, name))
         ");
-        let rt = tokio::runtime::Builder::new_current_thread().build()?;
-        let conn = rt.block_on(async {
-            let mut conn = SqliteConnection::connect(path.to_str().unwrap()).await?;
-            conn.execute(init_query).await?;
-            Ok::<SqliteConnection, Error>(conn)
-        })?;
+        let mut conn = SqliteConnection::connect(path.to_str().unwrap()).await?;
+        conn.execute(init_query).await?;
+        migrate(&mut conn).await?;
         Ok(Self {
             conn
         })
     }
     pub async fn remove_day(&mut self, date: Date) -> Result<u64, Error> {
-        let query = sqlx::query("DELETE FROM day WHERE date = ?")
-            .bind(date.format(DATE_FORMAT1).unwrap());
-        Ok(self.conn.execute(query).await?.rows_affected())
+        let mut tx = self.conn.begin().await?;
+        let res = delete_day(&mut tx, date).await?;
+        tx.commit().await?;
+        Ok(res)
     }
     pub async fn read_all(&mut self) -> Result<Vec<Day>, Error> {
         let query = sqlx::query("SELECT date, event, weather, mood FROM day ORDER BY date ASC");
+        let mut days = self.conn.fetch_all(query).await?.iter()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+        self.attach_details(&mut days).await?;
+        Ok(days)
+    }
+    /// 只读取有记录的日期，按时间升序
+    pub async fn read_dates(&mut self) -> Result<Vec<Date>, Error> {
+        let query = sqlx::query("SELECT date FROM day ORDER BY date ASC");
         Ok(self.conn.fetch_all(query).await?.iter()
+            .filter_map(|row| time::Date::parse(row.get("date"), DATE_FORMAT1).ok())
             .map(Into::into)
             .collect())
     }
     pub async fn read_from_to(&mut self, from: Date, to: Date) -> Result<Vec<Day>, Error> {
-        let query = sqlx::query("SELECT date,event,weather,mood FROM day WHERE date BETWEEN ?1 AND ?2")
+        let query = sqlx::query("SELECT date,event,weather,mood FROM day WHERE date BETWEEN ?1 AND ?2 ORDER BY date ASC")
             .bind(from.format(DATE_FORMAT1).unwrap())
             .bind(to.format(DATE_FORMAT1).unwrap());
-        Ok(self.conn.fetch_all(query).await?.iter()
+        let mut days = self.conn.fetch_all(query).await?.iter()
             .map(Into::into)
-            .collect())
+            .collect::<Vec<_>>();
+        self.attach_details(&mut days).await?;
+        Ok(days)
+    }
+    /// 读取范围内的记录，范围不设限时等同于`read_all`
+    pub async fn read_range(&mut self, range: &DateRange) -> Result<Vec<Day>, Error> {
+        match range {
+            DateRange { from: None, to: None } => self.read_all().await,
+            _ => self.read_from_to(range.start(), range.end()).await,
+        }
     }
     pub async fn read_day(&mut self, date: Date) -> Result<Option<Day>, Error> {
-        let query = sqlx::query("SELECT date,event,weather,mood FROM day WHERE date = ?")
-            .bind(date.format(DATE_FORMAT1).unwrap());
-        Ok(self.conn.fetch_optional(query).await?.map(|t| (&t).into()))
+        read_day_in(&mut self.conn, date).await
     }
+    /// 保存某一天，已有记录时覆盖，指标与完成的习惯也整体替换
     pub async fn add_day(&mut self, day: &Day) -> Result<u64, Error> {
-        let query = sqlx::query("INSERT OR REPLACE INTO day (date, event, weather, mood) VALUES (?1, ?2, ?3, ?4)")
-            .bind(day.date.format(DATE_FORMAT1).unwrap())
-            .bind(&day.event.instruct)
-            .bind(day.weather.as_deref())
-            .bind(day.mood);
-        Ok(self.conn.execute(query).await?.rows_affected())
+        let mut tx = self.conn.begin().await?;
+        let res = write_day(&mut tx, day).await?;
+        tx.commit().await?;
+        Ok(res)
+    }
+    /// 读取指标定义，按定义的先后排列
+    pub async fn metric_defs(&mut self) -> Result<Vec<MetricDef>, Error> {
+        read_defs(&mut self.conn).await
+    }
+    /// 添加或修改指标定义；已有取值时不能修改类型
+    pub async fn define_metric(&mut self, def: &MetricDef) -> Result<(), Error> {
+        crate::model::metric::check_name(&def.name)?;
+        let mut tx = self.conn.begin().await?;
+        if let Some(old) = read_defs(&mut tx).await?.into_iter().find(|d| d.name == def.name)
+            && old.kind != def.kind {
+            let used = tx.fetch_optional(sqlx::query("SELECT 1 FROM metric WHERE name = ? LIMIT 1").bind(&def.name)).await?;
+            if used.is_some() {
+                return Err(Error::InvalidData(format!("Metric '{}' already has {} values", def.name, old.kind)));
+            }
+        }
+        let query = sqlx::query("INSERT INTO metric_def (name, kind, unit) VALUES (?1, ?2, ?3) \
+            ON CONFLICT(name) DO UPDATE SET kind = excluded.kind, unit = excluded.unit")
+            .bind(&def.name)
+            .bind(def.kind.code())
+            .bind(&def.unit);
+        tx.execute(query).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    /// 删除指标定义及其全部取值，返回删除的取值数
+    pub async fn remove_metric(&mut self, name: &str) -> Result<u64, Error> {
+        let mut tx = self.conn.begin().await?;
+        let res = tx.execute(sqlx::query("DELETE FROM metric WHERE name = ?").bind(name)).await?.rows_affected();
+        let removed = tx.execute(sqlx::query("DELETE FROM metric_def WHERE name = ?").bind(name)).await?.rows_affected();
+        if removed == 0 {
+            return Err(Error::InvalidData(format!("No such metric: '{}'", name)));
+        }
+        tx.commit().await?;
+        Ok(res)
+    }
+    /// 设置（`value`为`None`时清除）某一天某个指标的取值，当天还没有记录时创建空记录，清除后为空时删除
+    ///
+    /// 指标必须已经定义，取值按定义的类型转换
+    pub async fn set_metric(&mut self, date: Date, name: &str, value: Option<MetricValue>) -> Result<Day, Error> {
+        let def = self.metric_defs().await?.into_iter()
+            .find(|d| d.name == name)
+            .ok_or_else(|| Error::InvalidData(format!("No such metric: '{}'", name)))?;
+        let mut day = self.read_day(date).await?
+            .unwrap_or_else(|| Day { date, event: String::new().into(), ..Default::default() });
+        match value.filter(|v| !v.is_empty()) {
+            Some(v) => { day.metrics.insert(def.name, def.kind.coerce(v)?); }
+            None => { day.metrics.remove(&def.name); }
+        }
+        self.save_or_remove(&day).await?;
+        Ok(day)
+    }
+    /// 读取习惯定义，按添加的先后排列
+    pub async fn habits(&mut self) -> Result<Vec<Habit>, Error> {
+        read_habits(&mut self.conn).await
+    }
+    /// 添加习惯，已有同名习惯时只修改目标频率
+    pub async fn define_habit(&mut self, habit: &Habit) -> Result<(), Error> {
+        crate::model::habit::check_name(&habit.name)?;
+        let query = sqlx::query("INSERT INTO habit (name, frequency, since) VALUES (?1, ?2, ?3) \
+            ON CONFLICT(name) DO UPDATE SET frequency = excluded.frequency")
+            .bind(&habit.name)
+            .bind(habit.frequency.to_string())
+            .bind(habit.since.format(DATE_FORMAT1).unwrap());
+        self.conn.execute(query).await?;
+        Ok(())
+    }
+    /// 删除习惯及其全部打卡记录，返回删除的打卡数
+    pub async fn remove_habit(&mut self, name: &str) -> Result<u64, Error> {
+        let mut tx = self.conn.begin().await?;
+        let res = tx.execute(sqlx::query("DELETE FROM habit_check WHERE name = ?").bind(name)).await?.rows_affected();
+        let removed = tx.execute(sqlx::query("DELETE FROM habit WHERE name = ?").bind(name)).await?.rows_affected();
+        if removed == 0 {
+            return Err(Error::InvalidData(format!("No such habit: '{}'", name)));
+        }
+        tx.commit().await?;
+        Ok(res)
+    }
+    /// 标记某一天完成（`done`为`false`时取消）某个习惯，当天还没有记录时创建空记录，取消后为空时删除
+    pub async fn check_habit(&mut self, date: Date, name: &str, done: bool) -> Result<Day, Error> {
+        if !self.habits().await?.iter().any(|h| h.name == name) {
+            return Err(Error::InvalidData(format!("No such habit: '{}'", name)));
+        }
+        let mut day = self.read_day(date).await?
+            .unwrap_or_else(|| Day { date, event: String::new().into(), ..Default::default() });
+        if done {
+            day.habits.insert(name.to_string());
+        } else {
+            day.habits.remove(name);
+        }
+        self.save_or_remove(&day).await?;
+        Ok(day)
+    }
+    /// 保存某一天，清除指标或习惯后已经没有任何内容时删除这一天
+    async fn save_or_remove(&mut self, day: &Day) -> Result<u64, Error> {
+        if day.is_empty() {
+            self.remove_day(day.date).await
+        } else {
+            self.add_day(day).await
+        }
+    }
+    /// 每个习惯完成的日期，按时间升序
+    pub async fn habit_checks(&mut self) -> Result<HashMap<String, Vec<Date>>, Error> {
+        let rows = self.conn.fetch_all(sqlx::query("SELECT date, name FROM habit_check ORDER BY date ASC")).await?;
+        let mut res: HashMap<String, Vec<Date>> = HashMap::new();
+        for row in rows {
+            if let Ok(date) = time::Date::parse(row.get("date"), DATE_FORMAT1) {
+                res.entry(row.get("name")).or_default().push(date.into());
+            }
+        }
+        Ok(res)
+    }
+    /// 为读取到的记录填上指标与完成的习惯
+    pub(crate) async fn attach_details(&mut self, days: &mut [Day]) -> Result<(), Error> {
+        let (Some(from), Some(to)) = (days.iter().map(|d| d.date).min(), days.iter().map(|d| d.date).max()) else {
+            return Ok(());
+        };
+        let mut values = read_metrics_between(&mut self.conn, from, to).await?;
+        let mut checks = read_checks_between(&mut self.conn, from, to).await?;
+        for day in days {
+            if let Some(metrics) = values.remove(&day.date) {
+                day.metrics = metrics;
+            }
+            if let Some(habits) = checks.remove(&day.date) {
+                day.habits = habits;
+            }
+        }
+        Ok(())
+    }
+    /// 把某一天的记录移动到另一天，整个过程在一个事务中完成，返回移动后的记录
+    pub async fn move_day(&mut self, from: Date, to: Date, mode: MoveMode) -> Result<Day, Error> {
+        self.transfer(from, to, mode, false).await
     }
+    /// 把某一天的正文作为模板复制到另一天，天气、心情、指标与习惯属于当天，不复制
+    pub async fn copy_day(&mut self, from: Date, to: Date, mode: MoveMode) -> Result<Day, Error> {
+        self.transfer(from, to, mode, true).await
+    }
+    async fn transfer(&mut self, from: Date, to: Date, mode: MoveMode, keep_source: bool) -> Result<Day, Error> {
+        if from == to {
+            return Err(Error::InvalidData("Source and target are the same date".to_string()));
+        }
+        let mut tx = self.conn.begin().await?;
+        let source = read_day_in(&mut tx, from).await?
+            .ok_or_else(|| Error::InvalidData(format!("No entry on {}", *from)))?;
+        let target = read_day_in(&mut tx, to).await?;
+        let mut day = Day { date: to, ..source };
+        if keep_source {
+            day.weather = None;
+            day.mood = None;
+            day.metrics.clear();
+            day.habits.clear();
+        }
+        let day = match (target, mode) {
+            (None, _) | (Some(_), MoveMode::Overwrite) => day,
+            (Some(old), MoveMode::Merge) => day.merge_into(old),
+            (Some(_), MoveMode::Fail) => return Err(Error::InvalidData(format!("{} already has an entry", *to))),
+        };
+        if !keep_source {
+            delete_day(&mut tx, from).await?;
+        }
+        write_day(&mut tx, &day).await?;
+        tx.commit().await?;
+        Ok(day)
+    }
+}
+/// 数据库结构的版本，保存在`PRAGMA user_version`中
+///
+/// - 1：天气改写为规范形式
+const SCHEMA_VERSION: i64 = 1;
+
+/// 按`user_version`执行尚未执行过的迁移，每个迁移只执行一次
+async fn migrate(conn: &mut SqliteConnection) -> Result<(), Error> {
+    let version: i64 = conn.fetch_one(sqlx::query("PRAGMA user_version")).await?.get(0);
+    if version >= SCHEMA_VERSION { return Ok(()) }
+    let mut tx = conn.begin().await?;
+    if version < 1 {
+        normalize_weather(&mut tx).await?;
+    }
+    tx.execute(sqlx::query(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))).await?;
+    tx.commit().await?;
+    Ok(())
+}
+/// 把旧数据中的自由文本天气改写为规范形式，使按状况搜索与统计时不会遗漏
+async fn normalize_weather(conn: &mut SqliteConnection) -> Result<(), Error> {
+    let rows = conn.fetch_all(sqlx::query("SELECT DISTINCT weather FROM day WHERE weather IS NOT NULL")).await?;
+    for row in rows {
+        let raw: String = row.get("weather");
+        let normalized = Weather::parse(&raw).map(|w| w.to_string());
+        if normalized.as_deref() != Some(raw.as_str()) {
+            let query = sqlx::query("UPDATE day SET weather = ?1 WHERE weather = ?2")
+                .bind(normalized)
+                .bind(raw);
+            conn.execute(query).await?;
+        }
+    }
+    Ok(())
+}
+/// 删除某一天的记录及其指标与打卡
+async fn delete_day(conn: &mut SqliteConnection, date: Date) -> Result<u64, Error> {
+    let date = date.format(DATE_FORMAT1).unwrap();
+    conn.execute(sqlx::query("DELETE FROM metric WHERE date = ?").bind(&date)).await?;
+    conn.execute(sqlx::query("DELETE FROM habit_check WHERE date = ?").bind(&date)).await?;
+    Ok(conn.execute(sqlx::query("DELETE FROM day WHERE date = ?").bind(&date)).await?.rows_affected())
+}
+async fn read_defs(conn: &mut SqliteConnection) -> Result<Vec<MetricDef>, Error> {
+    let rows = conn.fetch_all(sqlx::query("SELECT name, kind, unit FROM metric_def ORDER BY rowid ASC")).await?;
+    Ok(rows.iter()
+        .map(|row| MetricDef {
+            name: row.get("name"),
+            kind: row.get::<String, _>("kind").parse().unwrap_or_default(),
+            unit: row.get("unit"),
+        })
+        .collect())
+}
+async fn read_habits(conn: &mut SqliteConnection) -> Result<Vec<Habit>, Error> {
+    let rows = conn.fetch_all(sqlx::query("SELECT name, frequency, since FROM habit ORDER BY rowid ASC")).await?;
+    Ok(rows.iter()
+        .map(|row| Habit {
+            name: row.get("name"),
+            frequency: row.get::<String, _>("frequency").parse().unwrap_or_default(),
+            since: time::Date::parse(row.get("since"), DATE_FORMAT1).map(Into::into).unwrap_or_default(),
+        })
+        .collect())
+}
+/// 读取某一天及其指标与完成的习惯
+pub(crate) async fn read_day_in(conn: &mut SqliteConnection, date: Date) -> Result<Option<Day>, Error> {
+    let query = sqlx::query("SELECT date,event,weather,mood FROM day WHERE date = ?")
+        .bind(date.format(DATE_FORMAT1).unwrap());
+    let Some(mut day) = conn.fetch_optional(query).await?.map(|t| Day::from(&t)) else {
+        return Ok(None);
+    };
+    read_details(conn, &mut day).await?;
+    Ok(Some(day))
+}
+/// 为某一天填上指标与完成的习惯
+async fn read_details(conn: &mut SqliteConnection, day: &mut Day) -> Result<(), Error> {
+    day.metrics = read_metrics_between(conn, day.date, day.date).await?.remove(&day.date).unwrap_or_default();
+    day.habits = read_checks_between(conn, day.date, day.date).await?.remove(&day.date).unwrap_or_default();
+    Ok(())
+}
+/// 读取范围内（含首尾）完成的习惯，按日期分组
+async fn read_checks_between(conn: &mut SqliteConnection, from: Date, to: Date) -> Result<HashMap<Date, BTreeSet<String>>, Error> {
+    let query = sqlx::query("SELECT date, name FROM habit_check WHERE date BETWEEN ?1 AND ?2")
+        .bind(from.format(DATE_FORMAT1).unwrap())
+        .bind(to.format(DATE_FORMAT1).unwrap());
+    let mut res: HashMap<Date, BTreeSet<String>> = HashMap::new();
+    for row in conn.fetch_all(query).await? {
+        if let Ok(date) = time::Date::parse(row.get("date"), DATE_FORMAT1) {
+            res.entry(date.into()).or_default().insert(row.get("name"));
+        }
+    }
+    Ok(res)
+}
+/// 读取范围内（含首尾）的指标，按日期分组
+async fn read_metrics_between(conn: &mut SqliteConnection, from: Date, to: Date) -> Result<HashMap<Date, BTreeMap<String, MetricValue>>, Error> {
+    let query = sqlx::query("SELECT m.date, m.name, m.value, d.kind FROM metric m \
+        JOIN metric_def d ON d.name = m.name WHERE m.date BETWEEN ?1 AND ?2")
+        .bind(from.format(DATE_FORMAT1).unwrap())
+        .bind(to.format(DATE_FORMAT1).unwrap());
+    let mut res: HashMap<Date, BTreeMap<String, MetricValue>> = HashMap::new();
+    for row in conn.fetch_all(query).await? {
+        let Ok(date) = time::Date::parse(row.get("date"), DATE_FORMAT1) else { continue };
+        let kind = row.get::<String, _>("kind").parse().unwrap_or_default();
+        // 列没有声明类型，按定义的类型读取
+        let value = match kind {
+            MetricKind::Number => row.try_get::<f64, _>("value").ok().map(MetricValue::Number),
+            MetricKind::Bool => row.try_get::<i64, _>("value").ok().map(|v| MetricValue::Bool(v != 0)),
+            MetricKind::Text => row.try_get::<String, _>("value").ok().map(MetricValue::Text),
+        };
+        if let Some(value) = value {
+            res.entry(date.into()).or_default().insert(row.get("name"), value);
+        }
+    }
+    Ok(res)
+}
+/// 把`days`中打卡了但还没有定义的习惯定义为每天的习惯，从最早的打卡日期开始
+pub(crate) async fn define_missing_habits(conn: &mut SqliteConnection, days: &[Day]) -> Result<(), Error> {
+    let mut first: BTreeMap<&str, Date> = BTreeMap::new();
+    for day in days {
+        for name in &day.habits {
+            let date = first.entry(name).or_insert(day.date);
+            if day.date < *date { *date = day.date }
+        }
+    }
+    for (name, since) in first {
+        crate::model::habit::check_name(name)?;
+        let query = sqlx::query("INSERT OR IGNORE INTO habit (name, frequency, since) VALUES (?1, ?2, ?3)")
+            .bind(name)
+            .bind(crate::model::Frequency::Daily.to_string())
+            .bind(since.format(DATE_FORMAT1).unwrap());
+        conn.execute(query).await?;
+    }
+    Ok(())
+}
+/// 保存某一天，已有记录时覆盖，返回写入的行数；不开启事务，由调用方决定
+pub(crate) async fn write_day(conn: &mut SqliteConnection, day: &Day) -> Result<u64, Error> {
+    let res = conn.execute(insert_query(day)).await?.rows_affected();
+    write_details(conn, day).await?;
+    Ok(res)
+}
+/// 以`day`中的指标与完成的习惯替换当天已有的
+pub(crate) async fn write_details(conn: &mut SqliteConnection, day: &Day) -> Result<(), Error> {
+    write_metrics(conn, day).await?;
+    write_checks(conn, day).await
+}
+/// 以`day`中的指标替换当天已有的指标
+///
+/// 未定义的指标按取值的类型自动定义，类型不同时按定义的类型转换
+async fn write_metrics(conn: &mut SqliteConnection, day: &Day) -> Result<(), Error> {
+    let date = day.date.format(DATE_FORMAT1).unwrap();
+    conn.execute(sqlx::query("DELETE FROM metric WHERE date = ?").bind(&date)).await?;
+    if day.metrics.is_empty() {
+        return Ok(());
+    }
+    let defs = read_defs(conn).await?;
+    for (name, value) in day.metrics.iter().filter(|(_, v)| !v.is_empty()) {
+        let value = match defs.iter().find(|d| d.name == *name) {
+            Some(def) => def.kind.coerce(value.clone())
+                .map_err(|e| Error::InvalidData(format!("{}: {}", name, e)))?,
+            None => {
+                crate::model::metric::check_name(name)?;
+                let query = sqlx::query("INSERT INTO metric_def (name, kind) VALUES (?1, ?2)")
+                    .bind(name)
+                    .bind(value.kind().code());
+                conn.execute(query).await?;
+                value.clone()
+            }
+        };
+        let query = sqlx::query("INSERT INTO metric (date, name, value) VALUES (?1, ?2, ?3)")
+            .bind(&date)
+            .bind(name);
+        let query = match value {
+            MetricValue::Number(v) => query.bind(v),
+            MetricValue::Bool(b) => query.bind(b as i64),
+            MetricValue::Text(s) => query.bind(s),
+        };
+        conn.execute(query).await?;
+    }
+    Ok(())
+}
+/// 以`day`中完成的习惯替换当天已有的打卡
+///
+/// 不检查习惯是否已定义：打卡由[`DatabaseManager::check_habit`]检查，导入时先补上未定义的习惯
+async fn write_checks(conn: &mut SqliteConnection, day: &Day) -> Result<(), Error> {
+    let date = day.date.format(DATE_FORMAT1).unwrap();
+    conn.execute(sqlx::query("DELETE FROM habit_check WHERE date = ?").bind(&date)).await?;
+    for name in &day.habits {
+        conn.execute(sqlx::query("INSERT INTO habit_check (date, name) VALUES (?1, ?2)").bind(&date).bind(name)).await?;
+    }
+    Ok(())
+}
+fn insert_query(day: &Day) -> sqlx::query::Query<'_, Sqlite, sqlx::sqlite::SqliteArguments<'_>> {
+    sqlx::query("INSERT OR REPLACE INTO day (date, event, weather, mood) VALUES (?1, ?2, ?3, ?4)")
+        .bind(day.date.format(DATE_FORMAT1).unwrap())
+        .bind(&day.event.instruct)
+        .bind(day.weather.as_ref().map(ToString::to_string))
+        .bind(day.mood.map(|m| m.value()))
 }
 impl DerefMut for DatabaseManager {
     fn deref_mut(&mut self) -> &mut Self::Target {
@@ -79,18 +480,158 @@ impl From<&SqliteRow> for Day {
         let date_raw: String = row.get("date");
         let date = time::Date::parse(&date_raw, DATE_FORMAT1).unwrap();
         let event_str: String = row.get("event");
-        let weather = row.get("weather");
-        let mood_str: Option<String> = row.get("mood");
-        let mood = mood_str
-            .map(|mood| mood.parse::<f64>())
-            .transpose()
-            .unwrap_or_default();
+        let weather = row.get::<Option<String>, _>("weather").as_deref().and_then(Weather::parse);
+        // 列声明为DOUBLE，但旧数据可能以文本保存，两种都接受；空文本视为没有心情
+        let mood = row.try_get::<Option<f64>, _>("mood").ok().flatten()
+            .or_else(|| row.try_get::<Option<String>, _>("mood").ok().flatten()
+                .and_then(|mood| mood.trim().parse::<f64>().ok()))
+            .map(Mood::clamped);
         // Obj
         Day {
             date: date.into(),
             event: event_str.into(),
             weather,
             mood,
+            metrics: BTreeMap::new(),
+            habits: BTreeSet::new(),
         }
     }
 }
+
+/// 在新的内存数据库上执行测试，供各模块的测试共用
+#[cfg(test)]
+pub(crate) fn with_memory_db<T>(test: impl AsyncFnOnce(&mut DatabaseManager) -> T) -> T {
+    tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+        let mut db = DatabaseManager::open(std::path::Path::new(":memory:")).await.unwrap();
+        test(&mut db).await
+    })
+}
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+    use crate::model::{Condition, Frequency};
+
+    const FROM: time::Date = date!(2024-12-31);
+    const TO: time::Date = date!(2025-01-01);
+
+    fn run(test: impl AsyncFnOnce(&mut DatabaseManager)) {
+        with_memory_db(async |db| {
+            let mut read = Habit::new("read", Frequency::Daily).unwrap();
+            read.since = date!(2024-01-01).into();
+            db.define_habit(&read).await.unwrap();
+            test(db).await
+        })
+    }
+    fn day(date: time::Date, event: &str, mood: f64) -> Day {
+        Day { date: date.into(), event: event.to_string().into(), mood: Some(Mood::new(mood).unwrap()), ..Default::default() }
+    }
+    async fn event(db: &mut DatabaseManager, date: time::Date) -> Option<String> {
+        db.read_day(date.into()).await.unwrap().map(|d| d.event.instruct)
+    }
+
+    #[test]
+    fn move_to_empty_date() {
+        run(async |db| {
+            let mut source = day(FROM, "source", 6.);
+            source.habits.insert("read".to_string());
+            db.add_day(&source).await.unwrap();
+            let moved = db.move_day(FROM.into(), TO.into(), MoveMode::Fail).await.unwrap();
+            assert_eq!(*moved.date, TO);
+            assert!(moved.habits.contains("read"));
+            assert_eq!(event(db, FROM).await, None);
+            assert_eq!(db.read_day(TO.into()).await.unwrap().unwrap().habits, moved.habits);
+        });
+    }
+
+    #[test]
+    fn move_onto_existing_date_fails_by_default() {
+        run(async |db| {
+            db.add_day(&day(FROM, "source", 6.)).await.unwrap();
+            db.add_day(&day(TO, "target", 2.)).await.unwrap();
+            assert!(db.move_day(FROM.into(), TO.into(), MoveMode::Fail).await.is_err());
+            assert_eq!(event(db, FROM).await.as_deref(), Some("source"));
+            assert_eq!(event(db, TO).await.as_deref(), Some("target"));
+        });
+    }
+
+    #[test]
+    fn move_and_merge() {
+        run(async |db| {
+            db.add_day(&day(FROM, "source", 6.)).await.unwrap();
+            db.add_day(&day(TO, "target", 2.)).await.unwrap();
+            db.move_day(FROM.into(), TO.into(), MoveMode::Merge).await.unwrap();
+            let merged = db.read_day(TO.into()).await.unwrap().unwrap();
+            assert_eq!(merged.event.instruct, "target\nsource");
+            assert_eq!(merged.mood, Some(Mood::new(2.).unwrap()));
+            assert_eq!(event(db, FROM).await, None);
+        });
+    }
+
+    #[test]
+    fn move_and_overwrite() {
+        run(async |db| {
+            db.add_day(&day(FROM, "source", 6.)).await.unwrap();
+            db.add_day(&day(TO, "target", 2.)).await.unwrap();
+            db.move_day(FROM.into(), TO.into(), MoveMode::Overwrite).await.unwrap();
+            let moved = db.read_day(TO.into()).await.unwrap().unwrap();
+            assert_eq!(moved.event.instruct, "source");
+            assert_eq!(moved.mood, Some(Mood::new(6.).unwrap()));
+            assert_eq!(event(db, FROM).await, None);
+        });
+    }
+
+    #[test]
+    fn copy_keeps_source_and_drops_day_details() {
+        run(async |db| {
+            let mut source = day(FROM, "template", 6.);
+            source.weather = Some(Condition::Sunny.into());
+            source.habits.insert("read".to_string());
+            db.add_day(&source).await.unwrap();
+            let copied = db.copy_day(FROM.into(), TO.into(), MoveMode::Fail).await.unwrap();
+            assert_eq!(copied.event.instruct, "template");
+            assert!(copied.weather.is_none() && copied.mood.is_none() && copied.habits.is_empty());
+            assert_eq!(db.read_day(FROM.into()).await.unwrap().unwrap().habits, source.habits);
+        });
+    }
+
+    #[test]
+    fn transfer_needs_a_source_and_another_date() {
+        run(async |db| {
+            assert!(db.move_day(FROM.into(), TO.into(), MoveMode::Overwrite).await.is_err());
+            db.add_day(&day(FROM, "source", 6.)).await.unwrap();
+            assert!(db.copy_day(FROM.into(), FROM.into(), MoveMode::Overwrite).await.is_err());
+        });
+    }
+
+    #[test]
+    fn weather_is_normalized_once() {
+        run(async |db| {
+            let weather = async |db: &mut DatabaseManager| -> Vec<Option<String>> {
+                db.fetch_all(sqlx::query("SELECT weather FROM day ORDER BY date")).await.unwrap()
+                    .iter().map(|row| row.get(0)).collect()
+            };
+            db.execute("PRAGMA user_version = 0").await.unwrap();
+            db.execute("INSERT INTO day (date, event, weather) VALUES ('20240101', '', '晴 23度'), ('20240102', '', ' ')").await.unwrap();
+            migrate(db).await.unwrap();
+            assert_eq!(weather(db).await, [Some("sunny, 23°C".to_string()), None]);
+
+            db.execute("INSERT INTO day (date, event, weather) VALUES ('20240103', '', 'Rain')").await.unwrap();
+            migrate(db).await.unwrap();
+            assert_eq!(weather(db).await[2].as_deref(), Some("Rain"));
+        });
+    }
+
+    #[test]
+    fn checks_need_a_defined_habit() {
+        run(async |db| {
+            assert!(db.check_habit(FROM.into(), "swim", true).await.is_err());
+            assert_eq!(event(db, FROM).await, None);
+            let day = db.check_habit(FROM.into(), "read", true).await.unwrap();
+            assert!(day.habits.contains("read"));
+            // 取消唯一的打卡后这一天没有内容，不再保留
+            db.check_habit(FROM.into(), "read", false).await.unwrap();
+            assert!(db.read_day(FROM.into()).await.unwrap().is_none());
+        });
+    }
+}
@@ -0,0 +1,181 @@
+//! 习惯的连续完成情况与完成率
+//!
+//! 每天的习惯按天计算连续天数，每周若干次的习惯按周计算连续达标的周数，一周从`week_start`开始。
+//! 完成率按统计窗口内每周的天数折算目标次数，本周尚未结束时只要求与已过去的天数相称的次数。
+use std::collections::{BTreeMap, HashSet};
+use time::Duration;
+use crate::base::config::WeekStart;
+use crate::base::date::Date;
+use crate::base::error::Error;
+use crate::model::{Frequency, Habit};
+use crate::storage::DatabaseManager;
+use crate::utils::stats::streaks;
+
+/// 默认统计最近多少天的完成率
+pub const DEFAULT_WINDOW: usize = 30;
+
+/// 某个习惯截止到某天的完成情况
+#[derive(Debug, Clone, PartialEq)]
+pub struct HabitProgress {
+    pub habit: Habit,
+    /// 当前连续完成的天数（每天的习惯）或达标的周数（每周的习惯）
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// 统计窗口内计入的完成次数，每周超出目标的部分不计
+    pub done: usize,
+    /// 统计窗口内的目标次数
+    pub target: usize,
+}
+impl HabitProgress {
+    /// 根据完成的日期计算，`window`天的统计窗口以`today`结束，且不早于习惯的开始日期
+    pub fn compute(habit: &Habit, checks: &[Date], today: Date, window: usize, week_start: WeekStart) -> Self {
+        let checks = checks.iter().copied().filter(|d| *d <= today).collect::<Vec<_>>();
+        let (current_streak, longest_streak) = match habit.frequency {
+            Frequency::Daily => streaks(&checks, today),
+            Frequency::Weekly(n) => weekly_streaks(&checks, n, today, week_start),
+        };
+
+        let from = today.saturating_sub(Duration::days(window.max(1) as i64 - 1));
+        let from = if *habit.since > from { *habit.since } else { from };
+        let per_week = habit.frequency.per_week() as usize;
+        // 每周在窗口内的天数与完成次数
+        let mut weeks: BTreeMap<Date, (usize, usize)> = BTreeMap::new();
+        let mut day = from;
+        while day <= *today {
+            weeks.entry(week_start.start_of(day.into())).or_default().0 += 1;
+            match day.next_day() {
+                Some(next) => day = next,
+                None => break,
+            }
+        }
+        for d in checks.iter().filter(|d| ***d >= from) {
+            if let Some(week) = weeks.get_mut(&week_start.start_of(*d)) {
+                week.1 += 1;
+            }
+        }
+        let (done, target) = weeks.values().fold((0, 0), |(done, target), (days, count)| {
+            (done + count.min(&per_week), target + (per_week * days).div_ceil(7))
+        });
+
+        Self { habit: habit.clone(), current_streak, longest_streak, done, target }
+    }
+    /// 完成率（0~1），没有目标时为0
+    pub fn rate(&self) -> f64 {
+        if self.target == 0 { 0. } else { (self.done as f64 / self.target as f64).min(1.) }
+    }
+}
+impl DatabaseManager {
+    /// 全部习惯截止到`today`的完成情况，按习惯添加的先后排列
+    pub async fn habit_progress(&mut self, today: Date, window: usize, week_start: WeekStart) -> Result<Vec<HabitProgress>, Error> {
+        let mut checks = self.habit_checks().await?;
+        Ok(self.habits().await?.iter()
+            .map(|h| HabitProgress::compute(h, &checks.remove(&h.name).unwrap_or_default(), today, window, week_start))
+            .collect())
+    }
+}
+
+/// 计算每周至少完成`times`次的（当前连续周数，最长连续周数）
+///
+/// 本周还没有达标时，当前连续周数从上周开始往前数
+fn weekly_streaks(dates: &[Date], times: u8, today: Date, week_start: WeekStart) -> (usize, usize) {
+    let mut counts: BTreeMap<Date, usize> = BTreeMap::new();
+    for d in dates.iter().collect::<HashSet<_>>() {
+        *counts.entry(week_start.start_of(*d)).or_default() += 1;
+    }
+    let met = counts.into_iter()
+        .filter(|(_, n)| *n >= times as usize)
+        .map(|(week, _)| week)
+        .collect::<Vec<_>>();
+    let week = Duration::weeks(1);
+
+    let mut longest = 0;
+    let mut len = 0;
+    let mut prev: Option<Date> = None;
+    for &w in &met {
+        len = if prev.is_some_and(|p| p.saturating_add(week) == *w) { len + 1 } else { 1 };
+        longest = longest.max(len);
+        prev = Some(w);
+    }
+
+    let this_week = week_start.start_of(today);
+    let mut cur = if met.contains(&this_week) { *this_week } else { this_week.saturating_sub(week) };
+    let mut current = 0;
+    while met.contains(&cur.into()) {
+        current += 1;
+        cur = cur.saturating_sub(week);
+    }
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+
+    fn dates(list: &[time::Date]) -> Vec<Date> {
+        list.iter().copied().map(Into::into).collect()
+    }
+    fn habit(frequency: Frequency, since: time::Date) -> Habit {
+        Habit { name: "read".to_string(), frequency, since: since.into() }
+    }
+
+    #[test]
+    fn week_start_of_date() {
+        let sunday: Date = date!(2025-01-05).into();
+        assert_eq!(WeekStart::Monday.start_of(sunday), date!(2024-12-30).into());
+        assert_eq!(WeekStart::Sunday.start_of(sunday), sunday);
+        assert_eq!(WeekStart::Sunday.start_of(date!(2025-01-04).into()), date!(2024-12-29).into());
+    }
+
+    #[test]
+    fn weekly_streaks_of_nothing() {
+        assert_eq!(weekly_streaks(&[], 2, date!(2025-01-01).into(), WeekStart::Monday), (0, 0));
+    }
+
+    #[test]
+    fn weekly_streaks_across_year() {
+        let checks = dates(&[date!(2024-12-23), date!(2024-12-25), date!(2024-12-31), date!(2025-01-02)]);
+        // 本周还没有达标，从上周往前数
+        assert_eq!(weekly_streaks(&checks, 2, date!(2025-01-06).into(), WeekStart::Monday), (2, 2));
+        assert_eq!(weekly_streaks(&checks, 2, date!(2025-01-13).into(), WeekStart::Monday), (0, 2));
+        assert_eq!(weekly_streaks(&checks, 3, date!(2025-01-06).into(), WeekStart::Monday), (0, 0));
+    }
+
+    #[test]
+    fn weekly_streaks_follow_week_start() {
+        // 周日与周一在周日开始的一周里属于同一周
+        let checks = dates(&[date!(2024-12-29), date!(2024-12-30)]);
+        let today = date!(2025-01-01).into();
+        assert_eq!(weekly_streaks(&checks, 2, today, WeekStart::Monday), (0, 0));
+        assert_eq!(weekly_streaks(&checks, 2, today, WeekStart::Sunday), (1, 1));
+    }
+
+    #[test]
+    fn daily_progress_counts_from_since() {
+        let checks = dates(&[date!(2024-12-31), date!(2025-01-01), date!(2025-01-02), date!(2025-01-03),
+            date!(2025-01-04), date!(2025-01-05), date!(2025-01-09), date!(2025-01-10), date!(2025-01-11)]);
+        let p = HabitProgress::compute(&habit(Frequency::Daily, date!(2025-01-01)), &checks,
+                                       date!(2025-01-10).into(), DEFAULT_WINDOW, WeekStart::Monday);
+        assert_eq!((p.current_streak, p.longest_streak, p.done, p.target), (2, 6, 7, 10));
+        assert_eq!(p.rate(), 0.7);
+    }
+
+    #[test]
+    fn weekly_progress_follows_week_start() {
+        let checks = dates(&[date!(2025-01-03), date!(2025-01-04), date!(2025-01-05), date!(2025-01-06)]);
+        let compute = |week_start| {
+            let p = HabitProgress::compute(&habit(Frequency::Weekly(2), date!(2025-01-01)), &checks,
+                                           date!(2025-01-07).into(), DEFAULT_WINDOW, week_start);
+            (p.current_streak, p.longest_streak, p.done, p.target)
+        };
+        assert_eq!(compute(WeekStart::Monday), (1, 1, 3, 3));
+        assert_eq!(compute(WeekStart::Sunday), (2, 2, 4, 3));
+    }
+
+    #[test]
+    fn progress_before_since() {
+        let p = HabitProgress::compute(&habit(Frequency::Daily, date!(2025-02-01)), &[],
+                                       date!(2025-01-10).into(), DEFAULT_WINDOW, WeekStart::Monday);
+        assert_eq!((p.done, p.target, p.rate()), (0, 0, 0.));
+    }
+}
@@ -1,11 +1,12 @@
+use std::collections::BTreeSet;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use crate::base::error::Error;
 use crate::model::Day;
 use crate::storage::DatabaseManager;
 use crate::utils::io::format::Format;
-use crate::utils::io::model::Record;
+use crate::utils::io::model::{Record, HABITS_COLUMN, METRIC_COLUMN_PREFIX};
 
 pub struct Exporter<'a> {
     db_mgr: &'a mut DatabaseManager,
@@ -23,24 +24,77 @@ impl<'a> Exporter<'a> {
     }
 
     pub async fn all_export(&mut self) -> Result<(), Error> {
+        self.all_export_with_progress(|_, _| true).await
+    }
+    /// 导出全部记录，每写入一条以(已完成, 总数)调用`progress`，返回`false`时删除未写完的文件并返回[`Error::Cancelled`]
+    pub async fn all_export_with_progress(&mut self, progress: impl FnMut(usize, usize) -> bool) -> Result<(), Error> {
         let res = self.db_mgr.read_all().await?;
-        self.export(res)?;
-        Ok(())
+        self.export_with_progress(res, progress)
     }
     pub fn export(&mut self, days: Vec<Day>) -> Result<(), Error> {
-        let days: Vec<Record> = days.into_iter().map(|x| x.into()).collect::<Vec<_>>();
-        match self.mode {
+        self.export_with_progress(days, |_, _| true)
+    }
+    pub fn export_with_progress(&mut self, days: Vec<Day>, mut progress: impl FnMut(usize, usize) -> bool) -> Result<(), Error> {
+        let total = days.len();
+        // CSV中每个出现过的指标占一列
+        let metric_names = days.iter()
+            .flat_map(|d| d.metrics.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        // 没有任何习惯时不输出习惯列，与旧版本的格式相同
+        let has_habits = days.iter().any(|d| !d.habits.is_empty());
+        let days = days.into_iter().map(Record::from).enumerate();
+        let res = match self.mode {
             Format::Csv => {
                 let mut csv_writer = csv::Writer::from_path(&self.path)?;
-                for record in days { csv_writer.serialize(record)?; }
+                let mut res = Ok(());
+                let mut header = vec!["date".to_string(), "event".to_string(), "weather".to_string(), "mood".to_string()];
+                if has_habits {
+                    header.push(HABITS_COLUMN.to_string());
+                }
+                header.extend(metric_names.iter().map(|name| format!("{}{}", METRIC_COLUMN_PREFIX, name)));
+                csv_writer.write_record(&header)?;
+                for (i, record) in days {
+                    let mut row = vec![
+                        record.date,
+                        record.event,
+                        record.weather.unwrap_or_default(),
+                        record.mood.unwrap_or_default(),
+                    ];
+                    if has_habits {
+                        row.push(record.habits.into_iter().collect::<Vec<_>>().join(";"));
+                    }
+                    row.extend(metric_names.iter()
+                        .map(|name| record.metrics.get(name).map(ToString::to_string).unwrap_or_default()));
+                    csv_writer.write_record(&row)?;
+                    if !progress(i + 1, total) {
+                        res = Err(Error::Cancelled);
+                        break;
+                    }
+                }
                 csv_writer.flush()?;
+                res
             }
             Format::Json => {
-                let json = serde_json::to_string(&days).unwrap();
-                File::create(&self.path)?.write_all(json.as_bytes())?;
+                // 逐条写出数组元素，结果与整体序列化相同
+                let mut writer = BufWriter::new(File::create(&self.path)?);
+                let mut res = Ok(());
+                writer.write_all(b"[")?;
+                for (i, record) in days {
+                    if i > 0 { writer.write_all(b",")?; }
+                    serde_json::to_writer(&mut writer, &record).map_err(|e| Error::InvalidData(e.to_string()))?;
+                    if !progress(i + 1, total) {
+                        res = Err(Error::Cancelled);
+                        break;
+                    }
+                }
+                writer.write_all(b"]")?;
+                writer.flush()?;
+                res
             }
+        };
+        if res.is_err() {
+            let _ = std::fs::remove_file(&self.path);
         }
-
-        Ok(())
+        res
     }
 }
@@ -1,13 +1,16 @@
-use std::fs::File;
+use std::collections::HashSet;
+use std::fmt::Display;
 use std::ops::DerefMut;
 use std::path::Path;
-use sqlx::{Connection,};
-use crate::base::date::DATE_FORMAT1;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use sqlx::Connection;
+use crate::base::date::{Date, DATE_FORMAT1};
 use crate::base::error::Error;
-use crate::model::Day;
-use crate::storage::DatabaseManager;
+use crate::model::{Day, MetricValue};
+use crate::storage::{define_missing_habits, read_day_in, write_day, write_details, DatabaseManager};
 use crate::utils::io::format::Format;
-use crate::utils::io::model::Record;
+use crate::utils::io::model::{Record, HABITS_COLUMN, METRIC_COLUMN_PREFIX};
 
 pub struct Importer<'a> {
     db_mgr: &'a mut DatabaseManager,
@@ -16,74 +19,256 @@ impl<'a> Importer<'a> {
     pub fn new(db_mgr: &'a mut DatabaseManager) -> Self {
         Importer { db_mgr, }
     }
+    /// 读取文件中的全部记录，无法解析的记录跳过并记下错误信息
     pub fn read_from_file<P: AsRef<Path>>(path: P, format: Format) -> Result<(Vec<Day>, Vec<String>), Error> {
         let mut days = Vec::new();
         let mut errors = Vec::new();
-        match format {
-            Format::Json => {
-                let res = serde_json::from_reader::<_, Vec<Record>>(File::open(&path)?);
-                if let Ok(r) = res {
-                    for i in r {
-                        days.push(i.try_into()?);
-                    }
-                }else {
-                    errors.push(res.unwrap_err().to_string());
-                }
-
-            }
-            Format::Csv => {
-                let mut csv_reader = csv::Reader::from_path(path)?;
-                for r in csv_reader.deserialize::<Record>() {
-                    if let Ok(record) = r {
-                        days.push(record.try_into()?);
-                    }else {
-                        errors.push(r.unwrap_err().to_string());
-                    }
-                }
-
+        let records: Vec<Result<Record, String>> = match format {
+            // 部分编辑器保存的文件带有BOM，serde_json不接受
+            Format::Json => match serde_json::from_str::<Vec<Record>>(std::fs::read_to_string(&path)?.trim_start_matches('\u{feff}')) {
+                Ok(records) => records.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e.to_string())],
+            },
+            Format::Csv => read_csv(path)?,
+        };
+        for record in records {
+            let res = record.and_then(|record| {
+                let date = record.date.clone();
+                Day::try_from(record).map_err(|e| format!("{}: {}", date, e))
+            });
+            match res {
+                Ok(day) => days.push(day),
+                Err(e) => errors.push(e),
             }
         }
         Ok((days, errors))
     }
-    pub async fn import_to_db(&mut self, data: Vec<Day>, mode: DuplicateStrategy) -> Result<(), Error> {
-        match mode {
-            DuplicateStrategy::Replace => {
-                for r in &data { self.db_mgr.add_day(r).await?; }
+    pub async fn import_to_db(&mut self, data: Vec<Day>, mode: DuplicateStrategy) -> Result<ImportSummary, (ImportSummary, Error)> {
+        self.import_with_progress(data, mode, |_, _| true).await
+    }
+    /// 逐条导入，每条之后以(已完成, 总数)调用`progress`，返回`false`时停止并返回[`Error::Cancelled`]
+    ///
+    /// 每条记录在各自的事务中写入，失败或取消时与错误一起返回此前已写入的数量；
+    /// [`DuplicateStrategy::Fail`]则在一个事务中整体写入，失败时什么也不保留。
+    /// 打卡了但还没有定义的习惯先按每天的习惯定义。返回实际写入数据库的各类记录数量
+    pub async fn import_with_progress(&mut self, data: Vec<Day>, mode: DuplicateStrategy, progress: impl FnMut(usize, usize) -> bool) -> Result<ImportSummary, (ImportSummary, Error)> {
+        let mut summary = ImportSummary::default();
+        match self.write_records(data, mode, &mut summary, progress).await {
+            Ok(()) => Ok(summary),
+            Err(e) => Err((summary, e)),
+        }
+    }
+    async fn write_records(&mut self, data: Vec<Day>, mode: DuplicateStrategy, summary: &mut ImportSummary, mut progress: impl FnMut(usize, usize) -> bool) -> Result<(), Error> {
+        let total = data.len();
+        let mut step = |done: usize| match progress(done, total) {
+            true => Ok(()),
+            false => Err(Error::Cancelled),
+        };
+        if mode == DuplicateStrategy::Fail {
+            let mut bg = self.db_mgr.begin().await?;
+            define_missing_habits(&mut bg, &data).await?;
+            for (i, r) in data.into_iter().enumerate() {
+                let query = sqlx::query("INSERT INTO day (date, event, weather, mood) VALUES (?1, ?2, ?3, ?4)")
+                    .bind(r.date.format(DATE_FORMAT1).unwrap())
+                    .bind(&r.event.instruct)
+                    .bind(r.weather.as_ref().map(|w| w.to_string()))
+                    .bind(r.mood.map(|m| m.value()));
+                let _ = query.execute(&mut *bg).await?;
+                write_details(&mut bg, &r).await?;
+                step(i + 1)?;
             }
-            DuplicateStrategy::Ignore => {
-                for r in data {
-                    let query = sqlx::query("INSERT OR IGNORE INTO day (date, event, weather, mood) VALUES (?1, ?2, ?3, ?4)");
-                    query.bind(r.date.format(DATE_FORMAT1).unwrap())
-                        .bind(r.event.instruct)
-                        .bind(r.weather)
-                        .bind(r.mood)
-                        .execute(self.db_mgr.deref_mut()).await?;
-
+            bg.commit().await?;
+            // 有重复时上面已经整体失败
+            summary.added = total;
+            return Ok(());
+        }
+        define_missing_habits(self.db_mgr.deref_mut(), &data).await?;
+        for (i, r) in data.into_iter().enumerate() {
+            let mut tx = self.db_mgr.begin().await?;
+            let count = match (read_day_in(&mut tx, r.date).await?, mode) {
+                (None, _) => {
+                    write_day(&mut tx, &r).await?;
+                    &mut summary.added
+                }
+                (Some(_), DuplicateStrategy::Replace) => {
+                    write_day(&mut tx, &r).await?;
+                    &mut summary.replaced
+                }
+                // 已有记录时把新内容接在原内容之后
+                (Some(old), DuplicateStrategy::Append) => {
+                    write_day(&mut tx, &r.merge_into(old)).await?;
+                    &mut summary.merged
                 }
+                // 保留原样的日期，指标也不动
+                (Some(_), DuplicateStrategy::Ignore | DuplicateStrategy::Fail) => &mut summary.skipped,
+            };
+            tx.commit().await?;
+            *count += 1;
+            step(i + 1)?;
+        }
+        Ok(())
+    }
+}
 
-            }
-            DuplicateStrategy::Fail => {
-                let mut bg = self.db_mgr.begin().await?;
-                for r in data {
-                    let query = sqlx::query("INSERT INTO day (date, event, weather, mood) VALUES (?1, ?2, ?3, ?4)")
-                        .bind(r.date.format(DATE_FORMAT1).unwrap())
-                        .bind(r.event.instruct)
-                        .bind(r.weather)
-                        .bind(r.mood);
-                    let _ = query.execute(&mut *bg).await?;
+/// 读取CSV，`metric.`开头的列作为指标，按内容推断类型；`habits`列按`;`分隔
+fn read_csv<P: AsRef<Path>>(path: P) -> Result<Vec<Result<Record, String>>, Error> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let mut headers = reader.headers()?.clone();
+    let habits_column = headers.iter().position(|h| h == HABITS_COLUMN);
+    // 习惯列不按`Record`的字段反序列化
+    if habits_column.is_some() {
+        headers = headers.iter().map(|h| if h == HABITS_COLUMN { "" } else { h }).collect();
+    }
+    let metric_columns = headers.iter().enumerate()
+        .filter_map(|(i, h)| Some((i, h.strip_prefix(METRIC_COLUMN_PREFIX)?.to_string())))
+        .collect::<Vec<_>>();
+    Ok(reader.records()
+        .map(|row| {
+            let row = row.map_err(|e| e.to_string())?;
+            let mut record = row.deserialize::<Record>(Some(&headers)).map_err(|e| e.to_string())?;
+            for (i, name) in &metric_columns {
+                if let Some(value) = row.get(*i).filter(|v| !v.trim().is_empty()) {
+                    record.metrics.insert(name.clone(), MetricValue::infer(value));
                 }
-                bg.commit().await?;
             }
-            DuplicateStrategy::Append => {todo!()}
-        }
+            if let Some(habits) = habits_column.and_then(|i| row.get(i)) {
+                record.habits = habits.split(';').map(str::trim).filter(|h| !h.is_empty()).map(str::to_string).collect();
+            }
+            Ok(record)
+        })
+        .collect())
+}
 
-        Ok(())
+/// 导入结果中各类记录的数量
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ImportSummary {
+    /// 原本没有记录的日期
+    pub added: usize,
+    /// 被覆盖的已有记录
+    pub replaced: usize,
+    /// 追加到已有记录之后
+    pub merged: usize,
+    /// 因已有记录而保留原样
+    pub skipped: usize,
+}
+impl ImportSummary {
+    /// 按`existing`中已有的日期推算导入`days`的结果，文件中重复的日期也按已有处理，仅用于导入前的预览
+    ///
+    /// 对[`DuplicateStrategy::Fail`]，重复的日期计入`skipped`，但实际导入时会整体失败
+    pub fn plan(days: &[Day], existing: &HashSet<Date>, mode: DuplicateStrategy) -> Self {
+        let mut seen = existing.clone();
+        let mut summary = Self::default();
+        for day in days {
+            let count = match (seen.insert(day.date), mode) {
+                (true, _) => &mut summary.added,
+                (false, DuplicateStrategy::Replace) => &mut summary.replaced,
+                (false, DuplicateStrategy::Append) => &mut summary.merged,
+                (false, DuplicateStrategy::Ignore | DuplicateStrategy::Fail) => &mut summary.skipped,
+            };
+            *count += 1;
+        }
+        summary
+    }
+}
+impl Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} added, {} replaced, {} merged, {} skipped", self.added, self.replaced, self.merged, self.skipped)
     }
 }
-#[derive(Debug, Clone)]
+/// 导入的记录与已有日期重复时的处理方式
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DuplicateStrategy {
+    /// 以导入的记录覆盖
     Replace,
+    /// 保留已有的记录
     Ignore,
+    /// 出现重复时整体失败
     Fail,
+    /// 把导入的内容追加到已有记录之后
     Append,
-}
\ No newline at end of file
+}
+impl FromStr for DuplicateStrategy {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "replace" => Ok(DuplicateStrategy::Replace),
+            "ignore" => Ok(DuplicateStrategy::Ignore),
+            "fail" => Ok(DuplicateStrategy::Fail),
+            "append" => Ok(DuplicateStrategy::Append),
+            _ => Err(Error::Config(format!("Unknown import strategy: '{}'", s))),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+    use crate::storage::with_memory_db;
+
+    fn days(dates: &[time::Date]) -> Vec<Day> {
+        dates.iter().map(|d| Day { date: (*d).into(), event: "imported".to_string().into(), ..Default::default() }).collect()
+    }
+    fn existing() -> HashSet<Date> {
+        HashSet::from([date!(2024-12-31).into()])
+    }
+    fn summary(added: usize, replaced: usize, merged: usize, skipped: usize) -> ImportSummary {
+        ImportSummary { added, replaced, merged, skipped }
+    }
+
+    #[test]
+    fn plan_nothing() {
+        assert_eq!(ImportSummary::plan(&[], &existing(), DuplicateStrategy::Replace), ImportSummary::default());
+    }
+
+    #[test]
+    fn plan_each_strategy() {
+        // 文件中重复的2025-01-01也按已有处理
+        let days = days(&[date!(2024-12-31), date!(2025-01-01), date!(2025-01-01)]);
+        let plan = |mode| ImportSummary::plan(&days, &existing(), mode);
+        assert_eq!(plan(DuplicateStrategy::Replace), summary(1, 2, 0, 0));
+        assert_eq!(plan(DuplicateStrategy::Append), summary(1, 0, 2, 0));
+        assert_eq!(plan(DuplicateStrategy::Ignore), summary(1, 0, 0, 2));
+        assert_eq!(plan(DuplicateStrategy::Fail), summary(1, 0, 0, 2));
+    }
+
+    #[test]
+    fn import_counts_what_was_written() {
+        let import = |mode| with_memory_db(async |db| {
+            db.add_day(&days(&[date!(2024-12-31)])[0]).await.unwrap();
+            let res = Importer::new(db).import_to_db(days(&[date!(2024-12-31), date!(2025-01-01)]), mode).await;
+            (res.map_err(|(summary, _)| summary), db.read_all().await.unwrap().len())
+        });
+        assert_eq!(import(DuplicateStrategy::Replace), (Ok(summary(1, 1, 0, 0)), 2));
+        assert_eq!(import(DuplicateStrategy::Append), (Ok(summary(1, 0, 1, 0)), 2));
+        assert_eq!(import(DuplicateStrategy::Ignore), (Ok(summary(1, 0, 0, 1)), 2));
+        assert_eq!(import(DuplicateStrategy::Fail), (Err(ImportSummary::default()), 1));
+    }
+
+    #[test]
+    fn cancel_keeps_what_was_written() {
+        let import = |mode| with_memory_db(async |db| {
+            let data = days(&[date!(2025-01-01), date!(2025-01-02), date!(2025-01-03)]);
+            let res = Importer::new(db).import_with_progress(data, mode, |done, _| done < 2).await;
+            let Err((summary, Error::Cancelled)) = res else { panic!("import was not cancelled") };
+            (summary, db.read_all().await.unwrap().len())
+        });
+        assert_eq!(import(DuplicateStrategy::Replace), (summary(2, 0, 0, 0), 2));
+        assert_eq!(import(DuplicateStrategy::Fail), (ImportSummary::default(), 0));
+    }
+
+    #[test]
+    fn import_defines_missing_habits() {
+        for mode in [DuplicateStrategy::Replace, DuplicateStrategy::Fail] {
+            with_memory_db(async |db| {
+                let mut data = days(&[date!(2025-01-02), date!(2024-12-30)]);
+                data.iter_mut().for_each(|d| { d.habits.insert("swim".to_string()); });
+                Importer::new(db).import_to_db(data, mode).await.unwrap();
+                let habits = db.habits().await.unwrap();
+                assert_eq!(habits.len(), 1);
+                assert_eq!(*habits[0].since, date!(2024-12-30));
+                assert_eq!(db.habit_checks().await.unwrap()["swim"].len(), 2);
+            });
+        }
+    }
+}
@@ -27,34 +27,39 @@ pub mod import;
 /// - 写入文件或输出流
 pub mod export;
 
-/// 导入导出所使用的数据模型。
-///
-/// 该模块定义专用于数据交换的结构体，
-/// 不直接等同于核心领域模型。
-///
-/// 通过 `From` / `TryFrom` 实现与领域模型的双向转换。
 pub mod model {
-    //! 导入导出数据实体结构体。
+    //! 导入导出所使用的数据模型。
+    //!
+    //! 该模块定义专用于数据交换的结构体，
+    //! 不直接等同于核心领域模型。
     //!
     //! `Record` 表示对外交换的数据格式，
     //! 字段以可序列化形式存在（如日期为字符串）。
     //!
     //! 与内部 `Day` 结构体之间通过：
     //! - `TryFrom<Record>`
-    /// - `From<Day>`
-    ///
-    /// 实现安全转换。
+    //! - `From<Day>`
+    //!
+    //! 实现安全转换。
 
+    use std::collections::{BTreeMap, BTreeSet};
     use serde::{Deserialize, Serialize};
     use crate::base::date::DATE_FORMAT1;
     use crate::base::error::Error;
-    use crate::model::Day;
+    use crate::model::{Day, MetricValue, Mood, Weather};
+
+    /// CSV中指标列的列名前缀，如`metric.sleep`
+    pub const METRIC_COLUMN_PREFIX: &str = "metric.";
+    /// CSV中完成的习惯所在的列，多个习惯以`;`分隔
+    pub const HABITS_COLUMN: &str = "habits";
 
     /// 导入导出使用的数据记录结构。
     ///
     /// 说明：
     /// - `date` 采用字符串形式存储，便于序列化
     /// - `weather` 与 `mood` 为可选字段
+    /// - `metrics` 在 JSON 中为对象，在 CSV 中每个指标一列（见[`METRIC_COLUMN_PREFIX`]）
+    /// - `habits` 在 JSON 中为数组，在 CSV 中为一列（见[`HABITS_COLUMN`]）
     #[derive(Serialize, Deserialize, Debug, Clone)]
     #[derive(sqlx::FromRow)]
     pub struct Record {
@@ -62,6 +67,12 @@ pub mod model {
         pub event: String,
         pub weather: Option<String>,
         pub mood: Option<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        #[sqlx(skip)]
+        pub metrics: BTreeMap<String, MetricValue>,
+        #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+        #[sqlx(skip)]
+        pub habits: BTreeSet<String>,
     }
 
     impl TryFrom<Record> for Day {
@@ -71,19 +82,31 @@ pub mod model {
         ///
         /// 可能失败的情况：
         /// - 日期格式解析失败
+        /// - 心情无法解析或不在0~10之间
+        /// - 指标或习惯名称不合法
         fn try_from(record: Record) -> Result<Self, Self::Error> {
             let date = time::Date::parse(&record.date, DATE_FORMAT1)?;
             let event = record.event;
+            // 空字符串表示没有心情，其它无法解析或超出范围的值报错
             let mood = record.mood
-                .map(|m| m.parse())
-                .transpose()
-                .unwrap_or(None);
+                .filter(|m| !m.trim().is_empty())
+                .map(|m| m.parse::<Mood>())
+                .transpose()?;
+            let metrics = record.metrics.into_iter()
+                .filter(|(_, v)| !v.is_empty())
+                .map(|(name, v)| crate::model::metric::check_name(&name).map(|_| (name, v)))
+                .collect::<Result<_, _>>()?;
+            for name in &record.habits {
+                crate::model::habit::check_name(name)?;
+            }
 
             Ok(Self {
                 date: date.into(),
                 event: event.into(),
-                weather: record.weather,
-                mood: mood,
+                weather: record.weather.as_deref().and_then(Weather::parse),
+                mood,
+                metrics,
+                habits: record.habits,
             })
         }
     }
@@ -94,28 +117,53 @@ pub mod model {
             Self {
                 date: value.date.format(DATE_FORMAT1).unwrap().to_string(),
                 event: value.event.instruct,
-                weather: value.weather,
+                weather: value.weather.map(|w| w.to_string()),
                 mood: value.mood.map(|t| t.to_string()),
+                metrics: value.metrics,
+                habits: value.habits,
             }
         }
     }
 }
 
-/// 支持的导入导出格式。
-///
-/// 用于统一管理可解析或可导出的数据类型。
 pub mod format {
-    //! 导入导出格式枚举及解析实现。
+    //! 支持的导入导出格式。
+    //!
+    //! 用于统一管理可解析或可导出的数据类型，
+    //! 包括格式枚举及解析实现。
 
+    use std::io::Read;
+    use std::path::Path;
     use std::str::FromStr;
     use crate::base::error::Error;
 
     /// 支持的数据格式。
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
     pub enum Format {
         Json,
         Csv,
     }
 
+    impl Format {
+        /// 推断文件的格式。
+        ///
+        /// 优先使用扩展名；扩展名无法识别时读取文件开头，
+        /// 以`[`或`{`开始的视为 JSON，其余视为 CSV。
+        pub fn detect<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+            let ext = path.as_ref().extension().and_then(|e| e.to_str());
+            if let Some(format) = ext.and_then(|e| e.parse().ok()) {
+                return Ok(format);
+            }
+            let mut head = [0u8; 512];
+            let n = std::fs::File::open(path)?.read(&mut head)?;
+            let head = head[..n].strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&head[..n]);
+            match head.iter().find(|b| !b.is_ascii_whitespace()) {
+                Some(b'[' | b'{') => Ok(Format::Json),
+                _ => Ok(Format::Csv),
+            }
+        }
+    }
+
     impl FromStr for Format {
         type Err = Error;
 
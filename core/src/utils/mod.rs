@@ -1,2 +1,5 @@
 pub mod search;
-pub mod io;
\ No newline at end of file
+pub mod io;
+pub mod stats;
+pub mod template;
+pub mod onthisday;pub mod habits;
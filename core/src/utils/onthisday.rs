@@ -0,0 +1,133 @@
+//! “那年今日”：往年同一天（或同一周、同一月）的记录
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::base::date::{Date, DateRange};
+use crate::base::error::Error;
+use crate::model::Day;
+use crate::storage::DatabaseManager;
+
+/// 与往年比较的范围
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Span {
+    /// 同月同日
+    #[default]
+    Day,
+    /// 同月同日所在的一周（周一至周日）
+    Week,
+    /// 同一个月
+    Month,
+}
+impl Span {
+    pub const ALL: [Span; 3] = [Span::Day, Span::Week, Span::Month];
+    pub const KEYWORDS: &'static [&'static str] = &["day", "week", "month"];
+
+    /// `date`在`year`年对应的日期范围，2月29日在平年视为2月28日
+    pub fn range_in(&self, date: Date, year: i32) -> Result<DateRange, Error> {
+        if let Span::Month = self {
+            return DateRange::month(year, date.month());
+        }
+        let day = date.day().min(time::util::days_in_month(date.month(), year));
+        let anniversary: Date = time::Date::from_calendar_date(year, date.month(), day)
+            .map_err(|e| Error::InvalidData(e.to_string()))?
+            .into();
+        Ok(match self {
+            Span::Week => DateRange::week_of(anniversary),
+            _ => DateRange::day(anniversary),
+        })
+    }
+}
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Span::Day => "day",
+            Span::Week => "week",
+            Span::Month => "month",
+        };
+        write!(f, "{}", s)
+    }
+}
+impl FromStr for Span {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "day" | "d" => Ok(Span::Day),
+            "week" | "w" => Ok(Span::Week),
+            "month" | "m" => Ok(Span::Month),
+            _ => Err(Error::InvalidData(format!("Unknown span: '{}'", s))),
+        }
+    }
+}
+
+impl DatabaseManager {
+    /// `date`之前各年份中落在同一范围内的记录及其相隔的年数，按日期降序
+    ///
+    /// 年数取自查询的年份，跨年的一周中的记录仍归入该年
+    pub async fn on_this_day(&mut self, date: Date, span: Span) -> Result<Vec<(i32, Day)>, Error> {
+        let Some(first) = self.read_dates().await?.first().copied() else {
+            return Ok(Vec::new());
+        };
+        let mut res = Vec::new();
+        for year in (first.year()..date.year()).rev() {
+            let range = span.range_in(date, year)?;
+            let days = self.read_range(&range).await?;
+            res.extend(days.into_iter().rev().map(|day| (date.year() - year, day)));
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+    use crate::storage::with_memory_db;
+
+    fn range(from: time::Date, to: time::Date) -> DateRange {
+        DateRange::new(Some(from.into()), Some(to.into()))
+    }
+    fn day(date: time::Date, event: &str) -> Day {
+        Day { date: date.into(), event: event.to_string().into(), ..Default::default() }
+    }
+
+    #[test]
+    fn day_span_clamps_leap_day() {
+        let leap: Date = date!(2024-02-29).into();
+        assert_eq!(Span::Day.range_in(leap, 2023).unwrap(), DateRange::day(date!(2023-02-28).into()));
+        assert_eq!(Span::Day.range_in(leap, 2020).unwrap(), DateRange::day(date!(2020-02-29).into()));
+    }
+
+    #[test]
+    fn week_span_is_calendar_week_across_years() {
+        // 2025-01-01是周三，所在的一周从2024-12-30开始
+        let new_year: Date = date!(2026-01-01).into();
+        assert_eq!(Span::Week.range_in(new_year, 2025).unwrap(), range(date!(2024-12-30), date!(2025-01-05)));
+    }
+
+    #[test]
+    fn month_span_covers_whole_month() {
+        let date: Date = date!(2025-02-10).into();
+        assert_eq!(Span::Month.range_in(date, 2024).unwrap(), range(date!(2024-02-01), date!(2024-02-29)));
+    }
+
+    #[test]
+    fn parse_span() {
+        assert_eq!("W".parse::<Span>().unwrap(), Span::Week);
+        assert_eq!(" month ".parse::<Span>().unwrap(), Span::Month);
+        assert!("year".parse::<Span>().is_err());
+    }
+
+    #[test]
+    fn on_this_day_keeps_queried_year_offset() {
+        with_memory_db(async |db| {
+            assert!(db.on_this_day(date!(2026-01-01).into(), Span::Week).await.unwrap().is_empty());
+            for d in [day(date!(2024-12-31), "a"), day(date!(2025-01-01), "b"), day(date!(2024-01-03), "c")] {
+                db.add_day(&d).await.unwrap();
+            }
+            let found = db.on_this_day(date!(2026-01-01).into(), Span::Week).await.unwrap()
+                .into_iter()
+                .map(|(n, d)| (n, d.event.instruct))
+                .collect::<Vec<_>>();
+            assert_eq!(found, [(1, "b".to_string()), (1, "a".to_string()), (2, "c".to_string())]);
+        });
+    }
+}
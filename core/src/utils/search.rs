@@ -1,10 +1,11 @@
 use regex::Regex;
 use sqlx::{FromRow, QueryBuilder, Sqlite};
 use sqlx::sqlite::SqliteRow;
-use crate::base::date::Date;
+use crate::base::date::{Date, DATE_FORMAT1};
 use crate::base::error::Error;
-use crate::model::Day;
+use crate::model::{Condition, Day, Mood};
 use crate::storage::DatabaseManager;
+#[derive(Default)]
 pub struct SearchCondition {
     keyword: Option<String>,
     regex: Option<Regex>,
@@ -12,8 +13,27 @@ pub struct SearchCondition {
     date_from: Option<Date>,
     date_to: Option<Date>,
     weather_like: Option<String>,
-    mood_from: Option<i32>,
-    mood_to: Option<i32>,
+    condition: Option<Condition>,
+    mood_from: Option<Mood>,
+    mood_to: Option<Mood>,
+    /// 正文中必须全部出现的`#标签`，不含`#`
+    tags: Vec<String>,
+    /// 指标名称及其取值须满足的条件
+    metrics: Vec<(String, MetricFilter)>,
+}
+/// 对某个自定义指标取值的筛选
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricFilter {
+    /// 有取值即可
+    Present,
+    /// 数值不小于
+    AtLeast(f64),
+    /// 数值不大于
+    AtMost(f64),
+    /// 是/否等于
+    Is(bool),
+    /// 文字包含
+    Contains(String),
 }
 impl SearchCondition {
     pub fn with_keyword<S: Into<String>>(mut self, keyword: S) -> Self {
@@ -36,27 +56,46 @@ impl SearchCondition {
         self.weather_like = Some(weather_like.into());
         self
     }
-    pub fn with_mood_from(mut self, mood_from: i32) -> Self {
+    /// 只保留天气状况为`condition`的记录
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+    pub fn with_mood_from(mut self, mood_from: Mood) -> Self {
         self.mood_from = Some(mood_from);
         self
     }
-    pub fn with_mood_to(mut self, mood_to: i32) -> Self {
+    pub fn with_mood_to(mut self, mood_to: Mood) -> Self {
         self.mood_to = Some(mood_to);
         self
     }
+    /// 只保留指标`name`满足`filter`的记录，同一指标可以叠加多个条件
+    pub fn with_metric<S: Into<String>>(mut self, name: S, filter: MetricFilter) -> Self {
+        self.metrics.push((name.into(), filter));
+        self
+    }
+    pub fn with_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        let tag = tag.into();
+        self.tags.push(tag.trim_start_matches('#').to_string());
+        self
+    }
 }
-impl Default for SearchCondition {
-    fn default() -> Self {
-        Self {
-            keyword: None,
-            regex: None,
-            date_from: None,
-            date_to: None,
-            weather_like: None,
-            mood_from: None,
-            mood_to: None,
+
+/// 正文中的`#标签`（不含`#`），按出现顺序去重；Markdown标题的`# `不算标签
+pub fn tags_in(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for (i, _) in text.match_indices('#') {
+        if text[..i].chars().next_back().is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let tag = text[i + 1..].split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .next()
+            .unwrap_or_default();
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
         }
     }
+    tags
 }
 impl DatabaseManager {
     pub async fn search_in_condition(&mut self, condition: SearchCondition) -> Result<Vec<Day>, Error> {
@@ -65,34 +104,65 @@ impl DatabaseManager {
             query.push(" AND event like ");
             query.push_bind(format!("%{}%", keyword).to_string());
         }
-        if let Some(regex) = condition.regex {
-            query.push(" AND event regex ");
-            query.push_bind(regex.to_string());
-        }
         if let Some(date_from) = condition.date_from {
             query.push(" AND date >= ");
-            query.push_bind(date_from.to_string());
+            query.push_bind(date_from.format(DATE_FORMAT1).unwrap());
         }
         if let Some(date_to) = condition.date_to {
             query.push(" AND date <= ");
-            query.push_bind(date_to.to_string());
+            query.push_bind(date_to.format(DATE_FORMAT1).unwrap());
         }
         if let Some(weather_like) = condition.weather_like {
             query.push(" AND weather like ");
             query.push_bind(format!("%{}%", weather_like).to_string());
         }
+        if let Some(c) = condition.condition {
+            // 规范形式中状况总在最前面
+            query.push(" AND (weather = ");
+            query.push_bind(c.code());
+            query.push(" OR weather like ");
+            query.push_bind(format!("{},%", c.code()));
+            query.push(")");
+        }
         if let Some(mood_from) = condition.mood_from {
             query.push(" AND mood >= ");
-            query.push_bind(mood_from);
+            query.push_bind(mood_from.value());
         }
         if let Some(mood_to) = condition.mood_to {
             query.push(" AND mood <= ");
-            query.push_bind(mood_to);
+            query.push_bind(mood_to.value());
+        }
+        for (name, filter) in &condition.metrics {
+            query.push(" AND EXISTS (SELECT 1 FROM metric m WHERE m.date = day.date AND m.name = ");
+            query.push_bind(name.clone());
+            match filter {
+                MetricFilter::Present => {}
+                MetricFilter::AtLeast(v) => { query.push(" AND m.value >= "); query.push_bind(*v); }
+                MetricFilter::AtMost(v) => { query.push(" AND m.value <= "); query.push_bind(*v); }
+                MetricFilter::Is(b) => { query.push(" AND m.value = "); query.push_bind(*b as i64); }
+                MetricFilter::Contains(s) => { query.push(" AND m.value like "); query.push_bind(format!("%{}%", s)); }
+            }
+            query.push(")");
         }
-        query.push(" ORDER BY ");
-        query.push_bind("date DESC ");
+        for tag in &condition.tags {
+            // 先粗略匹配，再在下面精确比较标签
+            query.push(" AND event like ");
+            query.push_bind(format!("%#{}%", tag));
+        }
+        query.push(" ORDER BY date DESC");
         let query = query.build_query_as();
-        let res = query.fetch_all(&mut self.conn).await?;
+        let mut res: Vec<Day> = query.fetch_all(&mut self.conn).await?;
+        self.attach_details(&mut res).await?;
+        // SQLite没有内置正则，正则与标签在读取后过滤
+        if let Some(regex) = &condition.regex {
+            res.retain(|day| regex.is_match(&day.event.instruct));
+        }
+        if !condition.tags.is_empty() {
+            res.retain(|day| {
+                let tags = tags_in(&day.event.instruct);
+                condition.tags.iter().all(|t| tags.contains(t))
+            });
+        }
         Ok(res)
     }
 }
@@ -106,3 +176,60 @@ impl From<regex::Error> for Error {
         Error::InvalidData(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+    use crate::storage::with_memory_db;
+
+    #[test]
+    fn tags_skip_headings_and_repeats() {
+        // 紧跟在文字后的`#`不是标签的开头
+        assert_eq!(tags_in("# 标题\n#公园 去了#海边 #跑步 #公园 #"), ["公园", "跑步"]);
+    }
+
+    #[test]
+    fn conditions_combine() {
+        let dates = with_memory_db(async |db| {
+            let days = [
+                (date!(2025-01-01), "#跑步 公园", Some(Condition::Sunny), 8.),
+                (date!(2025-01-02), "#跑步 #雨天 公园", Some(Condition::Rainy), 8.),
+                (date!(2025-01-03), "#跑步者 公园", Some(Condition::Sunny), 8.),
+                (date!(2025-01-04), "#跑步 公园", Some(Condition::Sunny), 2.),
+            ];
+            for (date, text, condition, mood) in days {
+                let day = Day {
+                    date: date.into(),
+                    event: text.to_string().into(),
+                    weather: condition.map(Into::into),
+                    mood: Some(Mood::new(mood).unwrap()),
+                    ..Default::default()
+                };
+                db.add_day(&day).await.unwrap();
+            }
+            let condition = SearchCondition::default()
+                .with_keyword("公园")
+                .with_tag("#跑步")
+                .with_condition(Condition::Sunny)
+                .with_mood_from(Mood::new(5.).unwrap());
+            db.search_in_condition(condition).await.unwrap()
+                .into_iter().map(|day| day.date).collect::<Vec<_>>()
+        });
+        // 1月3日的`#跑步者`不是`#跑步`
+        assert_eq!(dates, [date!(2025-01-01).into()]);
+    }
+
+    #[test]
+    fn regex_filters_and_results_are_newest_first() {
+        let dates = with_memory_db(async |db| {
+            for (date, text) in [(date!(2025-01-01), "a1"), (date!(2025-01-02), "b"), (date!(2025-01-03), "a2")] {
+                db.add_day(&Day { date: date.into(), event: text.to_string().into(), ..Default::default() }).await.unwrap();
+            }
+            let condition = SearchCondition::default().with_regex(Regex::new(r"a\d").unwrap());
+            db.search_in_condition(condition).await.unwrap()
+                .into_iter().map(|day| day.date).collect::<Vec<_>>()
+        });
+        assert_eq!(dates, [date!(2025-01-03).into(), date!(2025-01-01).into()]);
+    }
+}
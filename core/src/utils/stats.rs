@@ -0,0 +1,350 @@
+//! 写作习惯统计
+//!
+//! 基于一段时间内的`Day`计算连续写作天数、每周/每月篇数、字数、
+//! 心情概况、天气分布以及自定义指标的概况。统计本身不访问数据库，
+//! 可以对任意来源的`Day`列表使用。
+use std::collections::{BTreeMap, HashMap, HashSet};
+use time::{Duration, Weekday};
+use crate::base::config::WeekStart;
+use crate::base::date::{Date, DateRange};
+use crate::base::error::Error;
+use crate::model::{Condition, Day, MetricValue};
+use crate::storage::DatabaseManager;
+
+/// 统计的时间粒度
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Period {
+    Week,
+    Month,
+}
+impl Period {
+    /// 返回`date`所在周期的第一天，一周从`week_start`开始
+    pub fn start_of(&self, date: Date, week_start: WeekStart) -> Date {
+        match self {
+            Period::Week => week_start.start_of(date),
+            Period::Month => date.replace_day(1).unwrap().into(),
+        }
+    }
+}
+
+/// 一组心情值的概况
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoodSummary {
+    pub average: f64,
+    pub min: f64,
+    pub max: f64,
+    pub count: usize,
+}
+impl MoodSummary {
+    /// 没有任何心情值时返回`None`
+    pub fn from_values(values: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut count = 0;
+        let mut sum = 0.;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        for v in values {
+            count += 1;
+            sum += v;
+            min = min.min(v);
+            max = max.max(v);
+        }
+        (count > 0).then(|| Self { average: sum / count as f64, min, max, count })
+    }
+}
+
+/// 某个自定义指标在统计范围内的概况
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricSummary {
+    /// 数值的平均、最小与最大值
+    Number(MoodSummary),
+    /// 记录了的天数及其中为“是”的天数
+    Bool { yes: usize, count: usize },
+    /// 记录了的天数
+    Text { count: usize },
+}
+impl MetricSummary {
+    /// 记录了该指标的天数
+    pub fn count(&self) -> usize {
+        match self {
+            MetricSummary::Number(s) => s.count,
+            MetricSummary::Bool { count, .. } | MetricSummary::Text { count } => *count,
+        }
+    }
+    /// 按取值汇总，类型以第一个取值为准，类型不同的取值不计入
+    fn from_values(values: &[&MetricValue]) -> Option<Self> {
+        match values.first()? {
+            MetricValue::Number(_) => MoodSummary::from_values(values.iter()
+                .filter_map(|v| match v { MetricValue::Number(n) => Some(*n), _ => None }))
+                .map(MetricSummary::Number),
+            MetricValue::Bool(_) => {
+                let bools = values.iter().filter_map(|v| match v { MetricValue::Bool(b) => Some(*b), _ => None });
+                let (yes, count) = bools.fold((0, 0), |(yes, count), b| (yes + b as usize, count + 1));
+                Some(MetricSummary::Bool { yes, count })
+            }
+            MetricValue::Text(_) => Some(MetricSummary::Text {
+                count: values.iter().filter(|v| matches!(v, MetricValue::Text(_))).count(),
+            }),
+        }
+    }
+}
+
+/// 某一周期（周或月）内的汇总
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodSummary {
+    /// 周期的第一天
+    pub start: Date,
+    pub entries: usize,
+    pub words: usize,
+    pub chars: usize,
+    pub mood: Option<MoodSummary>,
+}
+
+/// 一段时间内的完整统计结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub range: DateRange,
+    pub entries: usize,
+    pub words: usize,
+    pub chars: usize,
+    /// 截止到今天（或昨天，如果今天还没写）的连续天数
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub mood: Option<MoodSummary>,
+    /// 按周汇总，按时间升序
+    pub weekly: Vec<PeriodSummary>,
+    /// 按月汇总，按时间升序
+    pub monthly: Vec<PeriodSummary>,
+    /// 从一周的第一天起每天的心情概况
+    pub mood_by_weekday: Vec<(Weekday, Option<MoodSummary>)>,
+    /// 天气状况及其出现次数，按次数降序
+    pub weather: Vec<(Condition, usize)>,
+    /// 各自定义指标的概况，按名称排序
+    pub metrics: Vec<(String, MetricSummary)>,
+}
+impl Statistics {
+    /// 根据给定的记录计算统计结果，`today`用于计算当前连续天数，按周汇总时一周从`week_start`开始
+    pub fn from_days(days: &[Day], range: DateRange, today: Date, week_start: WeekStart) -> Self {
+        let mut days = days.iter()
+            .filter(|d| range.contains(d.date))
+            .collect::<Vec<_>>();
+        days.sort_by_key(|d| d.date);
+
+        let dates = days.iter().map(|d| d.date).collect::<Vec<_>>();
+        let (current_streak, longest_streak) = streaks(&dates, today);
+
+        let mut weather: HashMap<Condition, usize> = HashMap::new();
+        for c in days.iter().filter_map(|d| d.weather.as_ref()?.condition) {
+            *weather.entry(c).or_default() += 1;
+        }
+        let mut weather = weather.into_iter().collect::<Vec<_>>();
+        weather.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut metric_values: BTreeMap<&str, Vec<&MetricValue>> = BTreeMap::new();
+        for (name, value) in days.iter().flat_map(|d| &d.metrics) {
+            metric_values.entry(name).or_default().push(value);
+        }
+        let metrics = metric_values.into_iter()
+            .filter_map(|(name, values)| Some((name.to_string(), MetricSummary::from_values(&values)?)))
+            .collect();
+
+        let mood_by_weekday = (0..7)
+            .map(|i| week_start.first_day().nth_next(i))
+            .map(|wd| (wd, MoodSummary::from_values(days.iter()
+                .filter(|d| d.date.weekday() == wd)
+                .filter_map(|d| d.mood.map(|m| m.value())))))
+            .collect();
+
+        Self {
+            range,
+            entries: days.len(),
+            words: days.iter().map(|d| word_count(&d.event.instruct)).sum(),
+            chars: days.iter().map(|d| char_count(&d.event.instruct)).sum(),
+            current_streak,
+            longest_streak,
+            mood: MoodSummary::from_values(days.iter().filter_map(|d| d.mood.map(|m| m.value()))),
+            weekly: summarize(&days, Period::Week, week_start),
+            monthly: summarize(&days, Period::Month, week_start),
+            mood_by_weekday,
+            weather,
+            metrics,
+        }
+    }
+}
+impl DatabaseManager {
+    /// 统计范围内的记录
+    pub async fn statistics(&mut self, range: DateRange, week_start: WeekStart) -> Result<Statistics, Error> {
+        let days = self.read_range(&range).await?;
+        Ok(Statistics::from_days(&days, range, Date::native_time(), week_start))
+    }
+}
+
+/// 以`window`天为窗口计算心情的滑动平均
+///
+/// 只为有心情值的日期输出结果，结果按日期升序
+pub fn rolling_mood(days: &[Day], window: usize) -> Vec<(Date, f64)> {
+    let mut moods = days.iter()
+        .filter_map(|d| d.mood.map(|m| (d.date, m.value())))
+        .collect::<Vec<_>>();
+    moods.sort_by_key(|t| t.0);
+    let window = Duration::days(window.max(1) as i64 - 1);
+    moods.iter()
+        .map(|&(date, _)| {
+            let from = date.saturating_sub(window);
+            let values = moods.iter()
+                .filter(|(d, _)| **d >= from && *d <= date)
+                .map(|t| t.1);
+            (date, MoodSummary::from_values(values).unwrap().average)
+        })
+        .collect()
+}
+
+/// 按周期汇总已按日期排序的记录
+fn summarize(days: &[&Day], period: Period, week_start: WeekStart) -> Vec<PeriodSummary> {
+    let mut groups: BTreeMap<Date, Vec<&Day>> = BTreeMap::new();
+    for d in days {
+        groups.entry(period.start_of(d.date, week_start)).or_default().push(d);
+    }
+    groups.into_iter()
+        .map(|(start, days)| PeriodSummary {
+            start,
+            entries: days.len(),
+            words: days.iter().map(|d| word_count(&d.event.instruct)).sum(),
+            chars: days.iter().map(|d| char_count(&d.event.instruct)).sum(),
+            mood: MoodSummary::from_values(days.iter().filter_map(|d| d.mood.map(|m| m.value()))),
+        })
+        .collect()
+}
+
+/// 计算（当前连续天数，最长连续天数）
+///
+/// 今天还没有记录时，当前连续天数从昨天开始往前数
+pub fn streaks(dates: &[Date], today: Date) -> (usize, usize) {
+    let set = dates.iter().copied().collect::<HashSet<_>>();
+
+    let mut longest = 0;
+    for &d in &set {
+        // 只从每段连续区间的第一天开始计数
+        if d.previous_day().is_some_and(|p| set.contains(&p.into())) { continue }
+        let mut len = 1;
+        let mut cur = *d;
+        while let Some(next) = cur.next_day() && set.contains(&next.into()) {
+            len += 1;
+            cur = next;
+        }
+        longest = longest.max(len);
+    }
+
+    let mut cur = if set.contains(&today) {
+        *today
+    } else {
+        today.saturating_sub(Duration::days(1))
+    };
+    let mut current = 0;
+    while set.contains(&cur.into()) {
+        current += 1;
+        match cur.previous_day() {
+            Some(p) => cur = p,
+            None => break,
+        }
+    }
+    (current, longest)
+}
+
+/// 统计字数：连续的字母数字记为一个词，每个汉字、假名或谚文各记为一个词
+pub fn word_count(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() && !is_cjk(c) {
+            if !in_word { count += 1 }
+            in_word = true;
+        } else {
+            in_word = false;
+            if is_cjk(c) { count += 1 }
+        }
+    }
+    count
+}
+
+/// 统计非空白字符数
+pub fn char_count(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // 平假名、片假名
+        | 0x3400..=0x4DBF    // 扩展A
+        | 0x4E00..=0x9FFF    // 基本汉字
+        | 0xAC00..=0xD7AF    // 谚文
+        | 0xF900..=0xFAFF    // 兼容汉字
+        | 0x20000..=0x2FFFF) // 扩展B及以后
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+    use crate::model::Mood;
+
+    fn dates(list: &[time::Date]) -> Vec<Date> {
+        list.iter().copied().map(Into::into).collect()
+    }
+
+    #[test]
+    fn streaks_of_nothing() {
+        assert_eq!(streaks(&[], date!(2025-03-01).into()), (0, 0));
+    }
+
+    #[test]
+    fn streaks_across_month_and_year() {
+        let days = dates(&[date!(2024-12-30), date!(2024-12-31), date!(2025-01-01), date!(2025-01-02)]);
+        assert_eq!(streaks(&days, date!(2025-01-02).into()), (4, 4));
+        let days = dates(&[date!(2024-02-28), date!(2024-02-29), date!(2024-03-01)]);
+        assert_eq!(streaks(&days, date!(2024-03-01).into()), (3, 3));
+    }
+
+    #[test]
+    fn streaks_without_today() {
+        // 今天还没写不中断，前天断开则从头算
+        let days = dates(&[date!(2025-01-01), date!(2025-01-02), date!(2025-01-04), date!(2025-01-05)]);
+        assert_eq!(streaks(&days, date!(2025-01-06).into()), (2, 2));
+        assert_eq!(streaks(&days, date!(2025-01-07).into()), (0, 2));
+    }
+
+    #[test]
+    fn streaks_ignore_duplicates() {
+        let days = dates(&[date!(2025-01-01), date!(2025-01-01), date!(2025-01-02)]);
+        assert_eq!(streaks(&days, date!(2025-01-02).into()), (2, 2));
+    }
+
+    #[test]
+    fn periods_start_on_week_start_and_first_of_month() {
+        let sunday: Date = date!(2025-01-05).into();
+        assert_eq!(Period::Week.start_of(sunday, WeekStart::Monday), date!(2024-12-30).into());
+        assert_eq!(Period::Week.start_of(sunday, WeekStart::Sunday), sunday);
+        assert_eq!(Period::Month.start_of(sunday, WeekStart::Sunday), date!(2025-01-01).into());
+    }
+
+    #[test]
+    fn weekly_summary_follows_week_start() {
+        // 2025-01-04是周六，2025-01-05是周日
+        let days = [date!(2025-01-04), date!(2025-01-05), date!(2025-01-06)]
+            .map(|d| Day { date: d.into(), event: "x".to_string().into(), ..Default::default() });
+        let weeks = |week_start| Statistics::from_days(&days, DateRange::default(), date!(2025-01-06).into(), week_start)
+            .weekly.iter().map(|p| (*p.start, p.entries)).collect::<Vec<_>>();
+        assert_eq!(weeks(WeekStart::Monday), [(date!(2024-12-30), 2), (date!(2025-01-06), 1)]);
+        assert_eq!(weeks(WeekStart::Sunday), [(date!(2024-12-29), 1), (date!(2025-01-05), 2)]);
+        let stats = Statistics::from_days(&days, DateRange::default(), date!(2025-01-06).into(), WeekStart::Sunday);
+        assert_eq!(stats.mood_by_weekday.first().map(|t| t.0), Some(Weekday::Sunday));
+    }
+    #[test]
+    fn rolling_mood_averages_the_window_and_skips_days_without_mood() {
+        let days = [(date!(2025-01-03), Some(8.)), (date!(2025-01-01), Some(2.)), (date!(2025-01-02), None), (date!(2025-01-05), Some(6.))]
+            .map(|(d, mood)| Day { date: d.into(), mood: mood.map(|m| Mood::new(m).unwrap()), ..Default::default() });
+        let rolling = |window| rolling_mood(&days, window).into_iter().map(|(d, m)| (*d, m)).collect::<Vec<_>>();
+        assert_eq!(rolling(3), [(date!(2025-01-01), 2.), (date!(2025-01-03), 5.), (date!(2025-01-05), 7.)]);
+        // 窗口为0时按1天处理
+        assert_eq!(rolling(0), [(date!(2025-01-01), 2.), (date!(2025-01-03), 8.), (date!(2025-01-05), 6.)]);
+    }
+}
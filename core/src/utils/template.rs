@@ -0,0 +1,201 @@
+//! 新日记的模板与每日提示
+//!
+//! 模板是保存在数据目录`templates`文件夹中的Markdown文件：
+//! `monday.md`等按星期生效，`default.md`对其余日期生效；
+//! 放在`templates/<笔记本名>/`中的模板只对该笔记本生效并优先使用。
+//! `prompts.txt`中每行一个提示，按日期轮换，通过`{{prompt}}`插入。
+//!
+//! 支持的占位符：`{{date}}`、`{{weekday}}`、`{{yesterday_summary}}`、`{{prompt}}`，
+//! 其余内容原样保留。
+use std::path::{Path, PathBuf};
+use time::Weekday;
+use crate::base::date::Date;
+use crate::base::i18n::weekday_name;
+use crate::base::env::default_project_path;
+use crate::base::error::Error;
+use crate::storage::DatabaseManager;
+
+const TEMPLATE_DIR: &str = "templates";
+const DEFAULT_TEMPLATE: &str = "default";
+const PROMPT_FILE: &str = "prompts.txt";
+/// 只有提示、没有模板时使用的内容
+const PROMPT_ONLY_TEMPLATE: &str = "> {{prompt}}\n\n";
+
+/// 渲染模板所需的数据
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    pub date: String,
+    pub weekday: String,
+    pub yesterday_summary: String,
+    pub prompt: String,
+}
+impl TemplateContext {
+    /// 替换模板中的占位符
+    pub fn render(&self, template: &str) -> String {
+        template.replace("{{date}}", &self.date)
+            .replace("{{weekday}}", &self.weekday)
+            .replace("{{yesterday_summary}}", &self.yesterday_summary)
+            .replace("{{prompt}}", &self.prompt)
+    }
+}
+
+/// 模板文件夹
+pub struct Templates {
+    dir: PathBuf,
+}
+impl Default for Templates {
+    fn default() -> Self {
+        Self::new(default_project_path().join(TEMPLATE_DIR))
+    }
+}
+impl Templates {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+    /// 按优先级排列的候选文件夹
+    fn dirs(&self, notebook: Option<&str>) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        if let Some(name) = notebook {
+            dirs.push(self.dir.join(name));
+        }
+        dirs.push(self.dir.clone());
+        dirs
+    }
+    fn read(path: &Path) -> Result<Option<String>, Error> {
+        if !path.is_file() { return Ok(None) }
+        Ok(Some(std::fs::read_to_string(path)?))
+    }
+    /// 某星期几适用的模板，笔记本自己的模板优先于通用模板，星期模板优先于默认模板
+    pub fn find(&self, notebook: Option<&str>, weekday: Weekday) -> Result<Option<String>, Error> {
+        let weekday = weekday.to_string().to_lowercase();
+        for dir in self.dirs(notebook) {
+            for name in [weekday.as_str(), DEFAULT_TEMPLATE] {
+                if let Some(t) = Self::read(&dir.join(name).with_extension("md"))? {
+                    return Ok(Some(t));
+                }
+            }
+        }
+        Ok(None)
+    }
+    /// 全部提示，忽略空行与`#`开头的注释
+    pub fn prompts(&self, notebook: Option<&str>) -> Result<Vec<String>, Error> {
+        for dir in self.dirs(notebook) {
+            if let Some(content) = Self::read(&dir.join(PROMPT_FILE))? {
+                return Ok(content.lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                    .map(str::to_string)
+                    .collect());
+            }
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// 按日期轮换的提示，同一天总是得到同一个提示
+pub fn prompt_for(prompts: &[String], date: Date) -> Option<&str> {
+    if prompts.is_empty() { return None }
+    let idx = date.to_julian_day().rem_euclid(prompts.len() as i32) as usize;
+    Some(&prompts[idx])
+}
+
+/// 一篇日记的摘要，即第一行正文，跳过模板常用的标题与引用行
+pub fn summary(text: &str) -> &str {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    lines.clone()
+        .find(|l| !l.starts_with('#') && !l.starts_with('>'))
+        .or_else(|| lines.next())
+        .unwrap_or_default()
+}
+
+impl DatabaseManager {
+    /// 为还没有记录的日期生成初始内容，没有模板与提示时返回`None`
+    pub async fn render_template(&mut self, templates: &Templates, notebook: Option<&str>, date: Date) -> Result<Option<String>, Error> {
+        let prompts = templates.prompts(notebook)?;
+        let prompt = prompt_for(&prompts, date);
+        let template = match templates.find(notebook, date.weekday())? {
+            Some(t) => t,
+            None if prompt.is_some() => PROMPT_ONLY_TEMPLATE.to_string(),
+            None => return Ok(None),
+        };
+        let yesterday = match date.previous_day() {
+            Some(d) => self.read_day(d.into()).await?,
+            None => None,
+        };
+        let ctx = TemplateContext {
+            date: date.to_string(),
+            weekday: weekday_name(date.weekday()).to_string(),
+            yesterday_summary: yesterday.map(|d| summary(&d.event.instruct).to_string()).unwrap_or_default(),
+            prompt: prompt.unwrap_or_default().to_string(),
+        };
+        Ok(Some(ctx.render(&template)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+    use super::*;
+    use crate::model::Day;
+    use crate::storage::with_memory_db;
+
+    fn templates(files: &[(&str, &str)]) -> (tempfile::TempDir, Templates) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let templates = Templates::new(dir.path().to_path_buf());
+        (dir, templates)
+    }
+
+    #[test]
+    fn notebook_and_weekday_templates_come_first() {
+        let (_dir, templates) = templates(&[("default.md", "default"), ("monday.md", "monday"), ("work/default.md", "work")]);
+        assert_eq!(templates.find(None, Weekday::Monday).unwrap().as_deref(), Some("monday"));
+        assert_eq!(templates.find(None, Weekday::Tuesday).unwrap().as_deref(), Some("default"));
+        assert_eq!(templates.find(Some("work"), Weekday::Monday).unwrap().as_deref(), Some("work"));
+        assert_eq!(templates.find(Some("travel"), Weekday::Monday).unwrap().as_deref(), Some("monday"));
+    }
+
+    #[test]
+    fn prompts_rotate_by_date() {
+        let (_dir, templates) = templates(&[("prompts.txt", "# comment\nfirst\n\n  second  \n")]);
+        let prompts = templates.prompts(None).unwrap();
+        assert_eq!(prompts, ["first", "second"]);
+        let today = prompt_for(&prompts, date!(2025-01-01).into());
+        assert_eq!(prompt_for(&prompts, date!(2025-01-01).into()), today);
+        assert_ne!(prompt_for(&prompts, date!(2025-01-02).into()), today);
+        assert_eq!(prompt_for(&[], date!(2025-01-01).into()), None);
+    }
+
+    #[test]
+    fn summary_skips_headings_and_quotes() {
+        assert_eq!(summary("# Title\n> prompt\n\n  first line \nsecond"), "first line");
+        assert_eq!(summary("# Only a title\n"), "# Only a title");
+        assert_eq!(summary(""), "");
+    }
+
+    #[test]
+    fn render_fills_placeholders() {
+        let (_dir, templates) = templates(&[("default.md", "{{date}}|{{yesterday_summary}}|{{prompt}}|{{unknown}}"), ("prompts.txt", "why")]);
+        let rendered = with_memory_db(async |db| {
+            let yesterday = Day { date: date!(2025-01-01).into(), event: "# Title\nwent hiking".to_string().into(), ..Default::default() };
+            db.add_day(&yesterday).await.unwrap();
+            db.render_template(&templates, None, date!(2025-01-02).into()).await.unwrap()
+        });
+        assert_eq!(rendered.as_deref(), Some("2025-01-02|went hiking|why|{{unknown}}"));
+    }
+
+    #[test]
+    fn render_without_template() {
+        let (_dir, only_prompts) = templates(&[("prompts.txt", "why")]);
+        let (_empty, nothing) = templates(&[]);
+        with_memory_db(async |db| {
+            let date = date!(2025-01-02).into();
+            assert_eq!(db.render_template(&only_prompts, None, date).await.unwrap().as_deref(), Some("> why\n\n"));
+            assert_eq!(db.render_template(&nothing, None, date).await.unwrap(), None);
+        });
+    }
+}
@@ -0,0 +1,224 @@
+//! 界面中可以通过菜单、快捷键或命令面板执行的操作
+use std::collections::BTreeMap;
+use eframe::egui::{Context, Key, KeyboardShortcut, Modifiers};
+use diary_core::base::error::Error;
+use diary_core::t;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Action {
+    Commit,
+    PreviousDay,
+    NextDay,
+    Today,
+    DeleteDay,
+    MoveDay,
+    CopyDay,
+    Search,
+    Export,
+    Import,
+    OpenDiary,
+    NewDiary,
+    NewNotebook,
+    Statistics,
+    Charts,
+    ToggleEntries,
+    ToggleOnThisDay,
+    Settings,
+    CommandPalette,
+    About,
+    Exit,
+}
+impl Action {
+    pub const ALL: [Action; 21] = [
+        Action::Commit, Action::PreviousDay, Action::NextDay, Action::Today,
+        Action::DeleteDay, Action::MoveDay, Action::CopyDay, Action::Search,
+        Action::Export, Action::Import, Action::OpenDiary, Action::NewDiary, Action::NewNotebook,
+        Action::Statistics, Action::Charts, Action::ToggleEntries, Action::ToggleOnThisDay,
+        Action::Settings, Action::CommandPalette, Action::About, Action::Exit,
+    ];
+
+    /// 配置文件`[shortcuts]`中使用的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Commit => "commit",
+            Action::PreviousDay => "previous-day",
+            Action::NextDay => "next-day",
+            Action::Today => "today",
+            Action::DeleteDay => "delete-day",
+            Action::MoveDay => "move-day",
+            Action::CopyDay => "copy-day",
+            Action::Search => "search",
+            Action::Export => "export",
+            Action::Import => "import",
+            Action::OpenDiary => "open-diary",
+            Action::NewDiary => "new-diary",
+            Action::NewNotebook => "new-notebook",
+            Action::Statistics => "statistics",
+            Action::Charts => "charts",
+            Action::ToggleEntries => "toggle-entries",
+            Action::ToggleOnThisDay => "toggle-on-this-day",
+            Action::Settings => "settings",
+            Action::CommandPalette => "command-palette",
+            Action::About => "about",
+            Action::Exit => "exit",
+        }
+    }
+    /// 菜单与命令面板中展示的名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Commit => t!("gui.action.commit"),
+            Action::PreviousDay => t!("gui.action.previous_day"),
+            Action::NextDay => t!("gui.action.next_day"),
+            Action::Today => t!("gui.action.today"),
+            Action::DeleteDay => t!("gui.action.delete_day"),
+            Action::MoveDay => t!("gui.action.move_day"),
+            Action::CopyDay => t!("gui.action.copy_day"),
+            Action::Search => t!("gui.action.search"),
+            Action::Export => t!("gui.action.export"),
+            Action::Import => t!("gui.action.import"),
+            Action::OpenDiary => t!("gui.action.open_diary"),
+            Action::NewDiary => t!("gui.action.new_diary"),
+            Action::NewNotebook => t!("gui.action.new_notebook"),
+            Action::Statistics => t!("gui.action.statistics"),
+            Action::Charts => t!("gui.action.charts"),
+            Action::ToggleEntries => t!("gui.action.toggle_entries"),
+            Action::ToggleOnThisDay => t!("gui.action.toggle_on_this_day"),
+            Action::Settings => t!("gui.action.settings"),
+            Action::CommandPalette => t!("gui.action.command_palette"),
+            Action::About => t!("gui.action.about"),
+            Action::Exit => t!("gui.action.exit"),
+        }
+    }
+    fn default_shortcut(&self) -> Option<KeyboardShortcut> {
+        let shortcut = |modifiers, key| Some(KeyboardShortcut::new(modifiers, key));
+        match self {
+            Action::Commit => shortcut(Modifiers::COMMAND, Key::S),
+            Action::PreviousDay => shortcut(Modifiers::ALT, Key::ArrowLeft),
+            Action::NextDay => shortcut(Modifiers::ALT, Key::ArrowRight),
+            Action::Today => shortcut(Modifiers::COMMAND, Key::T),
+            Action::Search => shortcut(Modifiers::COMMAND, Key::F),
+            Action::Export => shortcut(Modifiers::COMMAND, Key::E),
+            Action::Settings => shortcut(Modifiers::COMMAND, Key::Comma),
+            Action::CommandPalette => shortcut(Modifiers::COMMAND, Key::P),
+            Action::Exit => shortcut(Modifiers::COMMAND, Key::Q),
+            _ => None,
+        }
+    }
+}
+
+/// 解析形如`ctrl+shift+s`的快捷键，`ctrl`与`cmd`都表示各平台的主修饰键
+pub fn parse_shortcut(s: &str) -> Option<KeyboardShortcut> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+    for part in s.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => modifiers |= Modifiers::COMMAND,
+            "alt" | "option" => modifiers |= Modifiers::ALT,
+            "shift" => modifiers |= Modifiers::SHIFT,
+            _ if key.is_none() => {
+                // `Key::from_name`区分大小写，依次尝试原样、首字母大写与全大写
+                let mut chars = part.chars();
+                let capitalized = chars.next()
+                    .map(|c| c.to_uppercase().chain(chars.as_str().chars()).collect::<String>())
+                    .unwrap_or_default();
+                key = Some(Key::from_name(part)
+                    .or_else(|| Key::from_name(&capitalized))
+                    .or_else(|| Key::from_name(&part.to_ascii_uppercase()))?);
+            }
+            _ => return None,
+        }
+    }
+    key.map(|key| KeyboardShortcut::new(modifiers, key))
+}
+
+/// 各操作对应的快捷键
+pub struct Shortcuts {
+    map: Vec<(Action, KeyboardShortcut)>,
+}
+impl Default for Shortcuts {
+    fn default() -> Self {
+        let map = Action::ALL.iter()
+            .filter_map(|a| a.default_shortcut().map(|s| (*a, s)))
+            .collect();
+        Self { map }
+    }
+}
+impl Shortcuts {
+    /// 用配置覆盖默认快捷键，空字符串表示取消该快捷键；无法识别的项保留默认值并返回错误
+    pub fn from_config(config: &BTreeMap<String, String>) -> (Self, Option<Error>) {
+        let mut shortcuts = Self::default();
+        let mut errors = Vec::new();
+        for (name, value) in config {
+            let Some(action) = Action::ALL.iter().find(|a| a.name() == name) else {
+                errors.push(format!("unknown action '{}'", name));
+                continue;
+            };
+            if value.trim().is_empty() {
+                shortcuts.map.retain(|(a, _)| a != action);
+                continue;
+            }
+            match parse_shortcut(value) {
+                Some(shortcut) => {
+                    shortcuts.map.retain(|(a, _)| a != action);
+                    shortcuts.map.push((*action, shortcut));
+                }
+                None => errors.push(format!("invalid shortcut '{}' for '{}'", value, name)),
+            }
+        }
+        let error = (!errors.is_empty())
+            .then(|| Error::Config(format!("Shortcuts: {}", errors.join(", "))));
+        (shortcuts, error)
+    }
+    pub fn get(&self, action: Action) -> Option<KeyboardShortcut> {
+        self.map.iter().find(|(a, _)| *a == action).map(|t| t.1)
+    }
+    /// 快捷键的展示文字，没有快捷键时为空
+    pub fn text(&self, ctx: &Context, action: Action) -> String {
+        self.get(action).map(|s| ctx.format_shortcut(&s)).unwrap_or_default()
+    }
+    /// 取出本帧按下的快捷键对应的操作，修饰键多的优先匹配
+    pub fn consume(&self, ctx: &Context) -> Option<Action> {
+        let mut map = self.map.iter().collect::<Vec<_>>();
+        map.sort_by_key(|(_, s)| std::cmp::Reverse(
+            s.modifiers.command as u8 + s.modifiers.alt as u8 + s.modifiers.shift as u8));
+        ctx.input_mut(|i| map.into_iter().find(|(_, s)| i.consume_shortcut(s)).map(|t| t.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcuts_parse_in_any_case() {
+        let shortcut = |modifiers, key| Some(KeyboardShortcut::new(modifiers, key));
+        assert_eq!(parse_shortcut("ctrl+shift+s"), shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::S));
+        assert_eq!(parse_shortcut("Cmd + ArrowLeft"), shortcut(Modifiers::COMMAND, Key::ArrowLeft));
+        assert_eq!(parse_shortcut("alt+f5"), shortcut(Modifiers::ALT, Key::F5));
+        assert_eq!(parse_shortcut("escape"), shortcut(Modifiers::NONE, Key::Escape));
+        assert_eq!(parse_shortcut("ctrl"), None);
+        assert_eq!(parse_shortcut("ctrl+a+b"), None);
+        assert_eq!(parse_shortcut("ctrl+nokey"), None);
+    }
+
+    #[test]
+    fn config_overrides_defaults() {
+        let config = [("today", ""), ("search", "ctrl+k"), ("about", "f1"), ("export", "ctrl+"), ("fly", "f2")]
+            .into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let (shortcuts, error) = Shortcuts::from_config(&config);
+        assert_eq!(shortcuts.get(Action::Today), None);
+        assert_eq!(shortcuts.get(Action::Search), parse_shortcut("ctrl+k"));
+        assert_eq!(shortcuts.get(Action::About), parse_shortcut("f1"));
+        // 无法识别的项保留默认值
+        assert_eq!(shortcuts.get(Action::Export), Action::Export.default_shortcut());
+        let error = error.unwrap().to_string();
+        assert!(error.contains("'export'") && error.contains("'fly'"));
+        assert!(Shortcuts::from_config(&BTreeMap::new()).1.is_none());
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let names = Action::ALL.map(|a| a.name()).into_iter().collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(names.len(), Action::ALL.len());
+    }
+}
@@ -1,168 +1,581 @@
-use std::process::exit;
+use std::path::PathBuf;
+use std::time::{Duration as StdDuration, Instant};
 use chrono::{Duration, NaiveDate};
 use eframe::egui::*;
 use eframe::Frame;
-use egui_extras::DatePickerButton;
+use diary_core::base::config::Config;
+use diary_core::base::env::DEFAULT_DB_NAME;
 use diary_core::base::error::Error;
-use diary_core::model::Day;
-use diary_core::utils::io::export::Exporter;
-use diary_core::utils::io::import::{DuplicateStrategy, Importer};
-use diary_core::utils::io::format::Format::Json;
-use crate::app::component::face::mood_to_face;
-use crate::service::executor::GuiService;
+use diary_core::base::notebook::{Notebooks, DEFAULT_NOTEBOOK};
+use diary_core::model::{Day, MetricDef};
+use diary_core::utils::habits::HabitProgress;
+use diary_core::utils::stats::Statistics;
+use diary_core::utils::io::import::ImportSummary;
+use diary_core::utils::io::format::Format;
+use diary_core::base::i18n::set_locale;
+use diary_core::t;
+use crate::app::component::charts::{charts_ui, ChartsState};
+use crate::app::component::editor::{editor_ui, EditorState};
+use crate::app::component::entries::{entries_ui, EntriesState};
+use crate::app::component::onthisday::{on_this_day_ui, OnThisDayState};
+use crate::app::component::settings::{apply_appearance, settings_ui};
+use crate::app::component::stats::statistics_ui;
+use crate::app::component::palette::{palette_ui, PaletteState};
+use crate::app::component::datepicker::date_picker;
+use crate::app::component::weather::weather_ui;
+use crate::app::component::mood::mood_ui;
+use crate::app::component::metrics::{metrics_ui, MetricsEvent, MetricsState};
+use crate::app::component::habits::{habits_ui, HabitsEvent, HabitsState};
+use crate::app::component::dayops::{day_op_ui, DayOpKind, DayOpState};
+use crate::app::component::import::{import_ui, ImportAction, ImportState};
+use crate::app::action::{Action, Shortcuts};
+use crate::service::executor::{GuiService, Pending, Query};
+use crate::service::worker::{Job, JobKind, JobOutput};
 use crate::model::date::Date;
-use crate::model::day::{GuiDayState, DEFAULT_MOOD};
+use crate::model::day::GuiDayState;
+
+/// 有未保存的修改时，需要用户确认后才能执行的操作
+enum PendingAction {
+    Date(NaiveDate),
+    Close,
+    Notebook(String),
+    DbFile(PathBuf),
+}
 
 pub struct App {
     executor: GuiService,
+    config: Config,
+    /// 最近一次从配置文件读取或写入的配置，用于判断设置是否已保存
+    saved_config: Config,
+    in_settings_page: bool,
     day: GuiDayState,
+    /// 正在读取的日期及其内容
+    loading_day: Option<(NaiveDate, Pending<GuiDayState>)>,
+    /// 当前数据库中定义的指标
+    metric_defs: Vec<MetricDef>,
+    metric_defs_query: Query<(), Vec<MetricDef>>,
+    metrics: MetricsState,
+    /// 各习惯的完成情况
+    habits: Vec<HabitProgress>,
+    habits_query: Query<(), Vec<HabitProgress>>,
+    habit_form: HabitsState,
     date_selected: NaiveDate,
     error: Option<Error>,
     in_about_page: bool,
+    in_stats_page: bool,
+    stats_range: String,
+    stats: Option<Statistics>,
+    /// 正在计算的统计数据
+    stats_query: Option<Pending<Statistics>>,
+    in_charts_page: bool,
+    charts: ChartsState,
+    show_on_this_day: bool,
+    on_this_day: OnThisDayState,
+    show_entries: bool,
+    entries: EntriesState,
+    editor: EditorState,
+    shortcuts: Shortcuts,
+    palette: PaletteState,
+    /// 正在确认的删除、移动或复制操作
+    day_op: Option<DayOpState>,
+    /// 正在进行的导入向导
+    import: Option<ImportState>,
+    /// 上一次导入的结果，展示后清空
+    import_summary: Option<ImportSummary>,
+    /// 当前打开的笔记本，直接打开数据库文件时为`None`
+    notebook: Option<String>,
+    /// 新建笔记本窗口中输入的名称，为`None`时窗口关闭
+    new_notebook: Option<String>,
+    /// 最后一次修改的时间，自动保存后清空
+    last_edit: Option<Instant>,
+    /// 等待用户选择保存或放弃修改的操作
+    pending: Option<PendingAction>,
+    /// 后台任务执行期间请求关闭窗口，任务结束后再关闭
+    close_after_job: bool,
 }
 impl App {
-    pub fn new(executor: GuiService) -> Self {
+    pub fn new(executor: GuiService, config: Config, notebook: Option<String>) -> Self {
         let td = Date::default().into();
         // 初始化 day.date 为一个特殊值，保证第一次刷新会读取数据库
         let day = Day {
             date: Date::from(td-Duration::days(1)).into(),
             ..Day::default()
         }.into();
+        // 配置中无法识别的快捷键在启动时提示
+        let (shortcuts, error) = Shortcuts::from_config(&config.shortcuts);
         Self {
             executor,
+            saved_config: config.clone(),
+            config,
+            in_settings_page: false,
             day,
+            loading_day: None,
+            metric_defs: Vec::new(),
+            metric_defs_query: Query::default(),
+            metrics: MetricsState::default(),
+            habits: Vec::new(),
+            habits_query: Query::default(),
+            habit_form: HabitsState::default(),
             date_selected: td,
-            error: None,
+            error,
             in_about_page: false,
+            in_stats_page: false,
+            stats_range: "all".to_string(),
+            stats: None,
+            stats_query: None,
+            in_charts_page: false,
+            charts: ChartsState::default(),
+            show_on_this_day: true,
+            on_this_day: OnThisDayState::default(),
+            show_entries: true,
+            entries: EntriesState::default(),
+            editor: EditorState::default(),
+            shortcuts,
+            palette: PaletteState::default(),
+            day_op: None,
+            import: None,
+            import_summary: None,
+            notebook,
+            new_notebook: None,
+            last_edit: None,
+            pending: None,
+            close_after_job: false,
         }
     }
+    /// 按配置设置主题与字体，启动时调用
+    pub fn apply_appearance(&self, ctx: &Context) {
+        apply_appearance(ctx, &self.config);
+    }
 }
 impl eframe::App for App {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
+        self.poll_job(ctx);
+        // 后台任务执行期间先取消任务，结束后再关闭
+        if ctx.input(|i| i.viewport().close_requested()) && self.executor.job().is_some() {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.executor.cancel();
+            self.close_after_job = true;
+        }
+        // 关闭窗口前保存修改，无法自动保存时询问用户
+        else if ctx.input(|i| i.viewport().close_requested())
+            && self.day.is_dirty()
+            && !(self.config.autosave > 0 && self.save_day()) {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.pending = Some(PendingAction::Close);
+        }
+        // 有弹窗等待处理时不响应快捷键
+        let blocked = self.error.is_some() || self.pending.is_some()
+            || self.executor.job().is_some() || self.palette.open || self.day_op.is_some() || self.import.is_some();
+        if !blocked && let Some(action) = self.shortcuts.consume(ctx) {
+            self.run_action(ctx, action);
+        }
         TopBottomPanel::top("top_panel").show(ctx, |ui| {
             MenuBar::new() .ui(ui, |ui| {
-                ui.menu_button("File", |ui| {
+                ui.menu_button(t!("gui.menu.file"), |ui| {
+                    self.action_button(ui, Action::OpenDiary);
+                    self.action_button(ui, Action::NewDiary);
                     ui.separator();
-                    if ui.button("Export").clicked() {
-                        let f = rfd::FileDialog::default()
-                            .set_file_name(chrono::Local::now().format("%Y-%m-%d").to_string())
-                            .add_filter("JSON", &["json"])
-                            .add_filter("CSV", &["csv"])
-                            .add_filter("Sqlite3 DataBase File", &["db"])
-                            .save_file();
-                        if let Some(file) = f {
-                            // 根据保存文件后缀判断使用格式
-                            let format = file.extension().unwrap().to_str().unwrap().to_ascii_lowercase().as_str()
-                                .parse();
-                            if let Ok(format) = format {
-                                self.error = async_std::task::block_on(async {
-                                    let mut exp = Exporter::new(self.executor.executor.conn_mut(), file, format);
-                                    exp.all_export().await.err()
-                                });
-                            }
-                        }else {
-                            eprintln!("Please select a folder");
-                        }
-                    }
-                    if ui.button("Import").clicked() {
-                        if let Some(file) = rfd::FileDialog::new().pick_file() {
-                            let mut imp = Importer::new(self.executor.executor.conn_mut());
-                            match Importer::read_from_file(file, Json) {
-                                Ok(r) => {
-                                    let mut ers = String::new();
-                                    r.1.iter().for_each(|x| ers.push_str(x));
-                                    if !ers.is_empty() {
-                                        self.error = Some(Error::InvalidData(ers.into()));
-                                    }
-                                    self.error = async_std::task::block_on(async {
-                                        imp.import_to_db(r.0, DuplicateStrategy::Replace).await.err()
-                                    });
-
-                                }
-                                Err(err) => self.error = Some(err),
-                            }
-                        }
-                    }
+                    self.action_button(ui, Action::Export);
+                    self.action_button(ui, Action::Import);
+                    ui.separator();
+                    self.action_button(ui, Action::CommandPalette);
+                    self.action_button(ui, Action::Exit);
                 });
-                // ui.menu_button("Settings", |ui| {});
-                ui.menu_button("Help", |ui| {
-                    if ui.button("About Diary").clicked() {
-                        self.in_about_page = true;
-                    }
+                ui.menu_button(t!("gui.menu.day"), |ui| {
+                    self.action_button(ui, Action::Commit);
+                    self.action_button(ui, Action::Today);
+                    ui.separator();
+                    self.action_button(ui, Action::MoveDay);
+                    self.action_button(ui, Action::CopyDay);
+                    self.action_button(ui, Action::DeleteDay);
+                });
+                ui.menu_button(t!("gui.menu.view"), |ui| {
+                    self.action_button(ui, Action::Statistics);
+                    self.action_button(ui, Action::Charts);
+                    self.action_button(ui, Action::Search);
+                    ui.checkbox(&mut self.show_entries, t!("gui.menu.entries"));
+                    ui.checkbox(&mut self.show_on_this_day, t!("gui.menu.on_this_day"));
                 });
+                ui.menu_button(t!("gui.menu.notebook"), |ui| self.notebook_menu(ui));
+                ui.menu_button(t!("gui.menu.settings"), |ui| self.action_button(ui, Action::Settings));
+                ui.menu_button(t!("gui.menu.help"), |ui| self.action_button(ui, Action::About));
             })
         });
         SidePanel::left("side_panel").resizable(false)
             .min_width(130.)
             .show(ctx, |ui| {
-                ui.add(DatePickerButton::new(&mut self.date_selected));
+                date_picker(ui, "date_selected", &mut self.date_selected, self.config.week_start);
                 ui.horizontal_wrapped(|ui| {
-                    if ui.add(Button::new("Commit")).clicked() {
-                        match self.executor.update_day(&self.day) {
-                            Ok(_) => {println!("{}, COMMIT COMPLETE", self.date_selected)},
-                            Err(e) => self.error = Some(e.into()),
-                        }
-                    }
-                    if ui.add(Button::new("Exit")).clicked() {
-                        exit(0);
-                    }
+                    self.side_button(ui, t!("gui.side.commit"), Action::Commit);
+                    self.side_button(ui, t!("gui.side.exit"), Action::Exit);
                 });
-                let weather_input = TextEdit::singleline(&mut self.day.weather)
-                    .hint_text("Weather")
-                    .desired_width(100.);
-                ui.add(weather_input);
-
-                // Mood
-                ui.horizontal(|ui| {
-                    let (face, color) = mood_to_face(self.day.mood);
+                if self.day.is_dirty() {
+                    ui.label(RichText::new(format!("● {}", t!("gui.side.unsaved"))).color(ui.visuals().warn_fg_color));
+                } else {
+                    ui.label(RichText::new(t!("gui.side.saved")).weak());
+                }
+                if weather_ui(ui, &mut self.day.weather) {
+                    self.last_edit = Some(Instant::now());
+                }
 
-                    let mood_input = DragValue::new(&mut self.day.mood)
-                        .max_decimals(1)
-                        .range(0..=10)
-                        .speed(0.015)
-                        .prefix("Mood: ");
-                    ui.add(mood_input);
-                    let face = Label::new(RichText::new(face).color(color));
-                    let face_rec = ui.allocate_exact_size([65., 15.].into(), Sense::empty());
-                    ui.put(face_rec.0, face)
-                });
+                if mood_ui(ui, &mut self.day.mood, self.config.mood) {
+                    self.last_edit = Some(Instant::now());
+                }
+                self.habits_section(ui);
+                self.metrics_section(ui);
                 ui.horizontal_wrapped(|ui| {
-                    if ui.add(Button::new("Before")).clicked() {
-                        self.date_selected -= Duration::days(1);
-                    }
-                    if ui.add(Button::new("Next")).clicked() {
-                        self.date_selected += Duration::days(1);
-                    }
+                    self.side_button(ui, t!("gui.side.before"), Action::PreviousDay);
+                    self.side_button(ui, t!("gui.side.next"), Action::NextDay);
                 });
             });
+        self.entries_panel(ctx);
+        self.on_this_day_panel(ctx);
         CentralPanel::default().show(ctx, |ui| {
-            ui.centered_and_justified(|ui| {
-                ScrollArea::both()
-                    .auto_shrink([true; 2])
-                    .show(ui, |ui| {
-                    if ui.text_edit_multiline(&mut self.day.event.instruct).changed() {
-                        // When input text, what happen?
-                    }
-                })
-            });
+            if editor_ui(ui, &mut self.day.event.instruct, &mut self.editor) {
+                self.last_edit = Some(Instant::now());
+            }
         });
         self.may_modal(ctx);
-        self.update_day();
+        self.autosave(ctx);
+        self.update_day(ctx);
     }
 }
 impl App {
-    fn update_day(&mut self) {
+    /// 当前状态下操作是否可用
+    fn enabled(&self, action: Action) -> bool {
+        match action {
+            // 还没有写过的一天没有可删除、移动或复制的内容
+            Action::DeleteDay | Action::MoveDay | Action::CopyDay =>
+                self.day.is_stored() || self.day.is_dirty(),
+            _ => true,
+        }
+    }
+    fn run_action(&mut self, ctx: &Context, action: Action) {
+        if !self.enabled(action) { return }
+        match action {
+            Action::Commit => { self.save_day(); }
+            Action::PreviousDay => self.date_selected -= Duration::days(1),
+            Action::NextDay => self.date_selected += Duration::days(1),
+            Action::Today => self.date_selected = Date::default().into(),
+            Action::DeleteDay => self.day_op = Some(DayOpState::new(DayOpKind::Delete, self.date_selected)),
+            Action::MoveDay => self.day_op = Some(DayOpState::new(DayOpKind::Move, self.date_selected)),
+            Action::CopyDay => self.day_op = Some(DayOpState::new(DayOpKind::Copy, self.date_selected)),
+            Action::Search => {
+                self.show_entries = true;
+                self.entries.focus_search = true;
+            }
+            Action::Export => self.export(),
+            Action::Import => self.import(),
+            Action::OpenDiary => if let Some(file) = rfd::FileDialog::new()
+                .add_filter(t!("gui.file.database"), &["db"])
+                .pick_file() {
+                self.request(ctx, PendingAction::DbFile(file));
+            },
+            Action::NewDiary => if let Some(file) = rfd::FileDialog::new()
+                .set_file_name(DEFAULT_DB_NAME)
+                .add_filter(t!("gui.file.database"), &["db"])
+                .save_file() {
+                self.request(ctx, PendingAction::DbFile(file));
+            },
+            Action::NewNotebook => self.new_notebook = Some(String::new()),
+            Action::Statistics => {
+                self.in_stats_page = true;
+                self.refresh_stats();
+            }
+            Action::Charts => self.in_charts_page = true,
+            Action::ToggleEntries => self.show_entries = !self.show_entries,
+            Action::ToggleOnThisDay => self.show_on_this_day = !self.show_on_this_day,
+            Action::Settings => self.in_settings_page = true,
+            Action::CommandPalette => self.palette.show(),
+            Action::About => self.in_about_page = true,
+            Action::Exit => ctx.send_viewport_cmd(ViewportCommand::Close),
+        }
+    }
+    /// 菜单中的操作，右侧显示快捷键
+    fn action_button(&mut self, ui: &mut Ui, action: Action) {
+        let button = Button::new(action.label()).shortcut_text(self.shortcuts.text(ui.ctx(), action));
+        if ui.add_enabled(self.enabled(action), button).clicked() {
+            self.run_action(ui.ctx(), action);
+        }
+    }
+    /// 侧栏中的按钮，悬停时提示快捷键
+    fn side_button(&mut self, ui: &mut Ui, text: &str, action: Action) {
+        let mut resp = ui.add(Button::new(text));
+        if let Some(shortcut) = self.shortcuts.get(action) {
+            resp = resp.on_hover_text(ui.ctx().format_shortcut(&shortcut));
+        }
+        if resp.clicked() {
+            self.run_action(ui.ctx(), action);
+        }
+    }
+    fn export(&mut self) {
+        let Some(mut file) = rfd::FileDialog::default()
+            .set_file_name(chrono::Local::now().format("%Y-%m-%d").to_string())
+            .add_filter("JSON", &["json"])
+            .add_filter("CSV", &["csv"])
+            .save_file() else { return };
+        // 根据保存文件后缀判断使用格式，没有后缀时导出为JSON
+        let format = match file.extension() {
+            Some(ext) => ext.to_string_lossy().parse(),
+            None => {
+                file.set_extension("json");
+                Ok(Format::Json)
+            }
+        };
+        let res = format.and_then(|format| self.executor.start(Job::Export { file, format }));
+        if let Err(e) = res {
+            self.error = Some(e);
+        }
+    }
+    fn import(&mut self) {
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter("JSON / CSV", &["json", "csv"])
+            .add_filter(t!("gui.file.all"), &["*"])
+            .pick_file() else { return };
+        self.error = self.executor.start(Job::Read { file, format: None }).err();
+    }
+    fn import_modal(&mut self, ctx: &Context) {
+        if self.error.is_some() { return }
+        // 重新读取文件期间只显示任务进度
+        if self.import.is_none() || self.executor.job().is_some() { return }
+        self.open_modal(ctx);
+        let Some(state) = self.import.as_mut() else { return };
+        match import_ui(ctx, state) {
+            Some(ImportAction::Confirm) => {
+                let mut state = self.import.take().unwrap();
+                let job = Job::Import { days: state.take_days(), strategy: state.strategy };
+                self.error = self.executor.start(job).err();
+            }
+            Some(ImportAction::Cancel) => self.import = None,
+            Some(ImportAction::Reread(file, format)) => {
+                self.error = self.executor.start(Job::Read { file, format: Some(format) }).err();
+            }
+            None => {}
+        }
+    }
+    fn import_summary_modal(&mut self, ctx: &Context) {
+        let Some(summary) = self.import_summary else { return };
+        if self.error.is_some() { return }
+        Window::new(t!("gui.import.finished"))
+            .resizable(false)
+            .collapsible(false)
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                Grid::new("import_summary").num_columns(2).show(ui, |ui| {
+                    for (label, count) in [(t!("gui.import.added"), summary.added), (t!("gui.import.replaced"), summary.replaced),
+                                           (t!("gui.import.merged"), summary.merged), (t!("gui.import.skipped"), summary.skipped)] {
+                        ui.label(label);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+                if ui.button(t!("gui.common.ok")).clicked() {
+                    self.import_summary = None;
+                }
+            });
+    }
+    fn update_day(&mut self, ctx: &Context) {
         let date = Date::from(self.date_selected).into();
+        if self.day.date != date && self.day.is_dirty() {
+            // 先回到正在编辑的日期，保存或放弃修改后再切换
+            let target = self.date_selected;
+            self.date_selected = Date::from(self.day.date).into();
+            self.request(ctx, PendingAction::Date(target));
+            return;
+        }
         if self.day.date != date {
-            // 刷新展示内容
-            let day = GuiDayState {
-                date,
-                event: "".to_string().into(),
-                weather: "".to_string(),
-                mood: DEFAULT_MOOD,
-            };
-            self.day = self.executor.read_day(date).ok().unwrap_or_default()
-                .unwrap_or(day);
+            // 刷新展示内容，读取完成前仍展示原来的内容
+            if self.loading_day.as_ref().is_none_or(|(loading, _)| *loading != self.date_selected) {
+                let pending = self.executor.load_day(self.notebook.clone(), date);
+                self.loading_day = Some((self.date_selected, pending));
+            }
+            if let Some(res) = self.loading_day.as_ref().and_then(|(_, pending)| pending.poll()) {
+                self.loading_day = None;
+                self.day = res.unwrap_or_else(|e| {
+                    self.error = Some(e);
+                    GuiDayState::new(date, String::new())
+                });
+            }
+        }
+    }
+    /// 保存当前编辑的内容，成功时返回`true`
+    fn save_day(&mut self) -> bool {
+        match self.executor.update_day(&self.day) {
+            Ok(_) => {
+                self.day.mark_saved();
+                self.last_edit = None;
+                self.habits_query.invalidate();
+                self.charts.invalidate();
+                self.on_this_day.invalidate();
+                self.entries.invalidate();
+                true
+            }
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+    /// 停止修改一段时间后自动保存
+    fn autosave(&mut self, ctx: &Context) {
+        // 导入导出期间数据库忙，等任务结束后再保存
+        if self.config.autosave == 0 || !self.day.is_dirty() || self.executor.job().is_some() { return }
+        let delay = StdDuration::from_secs(self.config.autosave);
+        let Some(last_edit) = self.last_edit else { return };
+        let elapsed = last_edit.elapsed();
+        if elapsed >= delay {
+            // 保存失败时不再重试，直到下一次修改
+            if !self.save_day() {
+                self.last_edit = None;
+            }
+        } else {
+            ctx.request_repaint_after(delay - elapsed);
+        }
+    }
+    /// 执行会离开当前内容的操作，有未保存的修改时先自动保存或询问用户
+    fn request(&mut self, ctx: &Context, action: PendingAction) {
+        if !self.day.is_dirty() || self.config.autosave > 0 && self.save_day() {
+            self.perform(ctx, action);
+        } else {
+            self.pending = Some(action);
+        }
+    }
+    fn perform(&mut self, ctx: &Context, action: PendingAction) {
+        match action {
+            PendingAction::Date(date) => self.date_selected = date,
+            PendingAction::Close => ctx.send_viewport_cmd(ViewportCommand::Close),
+            PendingAction::Notebook(name) => self.switch_notebook(ctx, name),
+            PendingAction::DbFile(file) => self.open_db_file(ctx, file),
+        }
+    }
+    /// 处理后台任务的进度与结果
+    fn poll_job(&mut self, ctx: &Context) {
+        let Some((kind, res)) = self.executor.poll() else {
+            if self.executor.job().is_some() {
+                ctx.request_repaint_after(StdDuration::from_millis(100));
+            }
+            return;
+        };
+        match res {
+            Ok(JobOutput::Read(preview)) => match self.import.as_mut() {
+                Some(state) => state.set_preview(preview),
+                None => self.import = Some(ImportState::new(preview, self.config.import_strategy)),
+            },
+            Ok(JobOutput::Imported(summary, error)) => {
+                // 先显示错误，关闭后再显示已写入的数量
+                self.import_summary = Some(summary);
+                self.error = error.filter(|e| !matches!(e, Error::Cancelled));
+            }
+            Ok(JobOutput::Exported) | Err(Error::Cancelled) => {}
+            Err(e) => self.error = Some(e),
+        }
+        if kind == JobKind::Import {
+            self.reload();
+        }
+        if self.close_after_job {
+            self.close_after_job = false;
+            self.import = None;
+            ctx.send_viewport_cmd(ViewportCommand::Close);
+        }
+    }
+    fn job_modal(&mut self, ctx: &Context) {
+        let Some(job) = self.executor.job() else { return };
+        let (label, done, total, cancelling) = (job.kind.label(), job.done, job.total, job.cancelling);
+        self.open_modal(ctx);
+        Window::new(label)
+            .resizable(false)
+            .collapsible(false)
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                if total == 0 {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("{}…", label));
+                    });
+                } else {
+                    ui.add(ProgressBar::new(done as f32 / total as f32)
+                        .text(format!("{} / {}", done, total))
+                        .desired_width(240.));
+                }
+                if ui.add_enabled(!cancelling, Button::new(t!("gui.common.cancel"))).clicked() {
+                    self.executor.cancel();
+                }
+            });
+    }
+    fn unsaved_modal(&mut self, ctx: &Context) {
+        if self.pending.is_none() || self.error.is_some() {return}
+        self.open_modal(ctx);
+        let mut choice = None;
+        Window::new(t!("gui.unsaved.title"))
+            .resizable(false)
+            .collapsible(false)
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(t!("gui.unsaved.message", date = *self.day.date));
+                ui.horizontal(|ui| {
+                    if ui.button(t!("gui.common.save")).clicked() { choice = Some(true) }
+                    if ui.button(t!("gui.unsaved.discard")).clicked() { choice = Some(false) }
+                    if ui.button(t!("gui.common.cancel")).clicked() { self.pending = None }
+                });
+            });
+        let Some(save) = choice else { return };
+        if save && !self.save_day() { return }
+        // 放弃修改：随后的操作会重新读取内容
+        self.day.discard();
+        self.last_edit = None;
+        if let Some(action) = self.pending.take() {
+            self.perform(ctx, action);
+        }
+    }
+    fn day_op_modal(&mut self, ctx: &Context) {
+        if self.error.is_some() { return }
+        let Some(op) = self.day_op.as_mut() else { return };
+        if let Some(target) = op.unchecked_target() {
+            op.check_target(target, self.executor.has_day(Date::from(target).into()));
+        }
+        if let Some(e) = op.poll() {
+            self.error = Some(e);
+            return;
+        }
+        self.open_modal(ctx);
+        let Some(op) = self.day_op.as_mut() else { return };
+        match day_op_ui(ctx, op, self.config.week_start) {
+            Some(true) => {
+                let op = self.day_op.take().unwrap();
+                self.apply_day_op(op);
+            }
+            Some(false) => self.day_op = None,
+            None => {}
+        }
+    }
+    /// 执行确认后的删除、移动或复制
+    fn apply_day_op(&mut self, op: DayOpState) {
+        let source = Date::from(op.source).into();
+        let res = match op.kind {
+            DayOpKind::Delete => self.executor.delete_day(source),
+            kind => {
+                // 先保存正在编辑的内容，移动或复制的是最新的内容
+                if self.day.is_dirty() && !self.save_day() { return }
+                let target = Date::from(op.target).into();
+                match kind {
+                    DayOpKind::Move => self.executor.move_day(source, target, op.mode()),
+                    _ => self.executor.copy_day(source, target, op.mode()),
+                }
+            }
+        };
+        if let Err(e) = res {
+            self.error = Some(e);
+            return;
+        }
+        // 删除时丢弃未保存的修改，随后重新读取；移动与复制后跳到目标日期
+        self.day.discard();
+        self.last_edit = None;
+        self.reload();
+        if op.kind != DayOpKind::Delete {
+            self.date_selected = op.target;
         }
     }
     /// enter modal mode
@@ -183,14 +596,356 @@ impl App {
                 );
             });
     }
+    fn notebook_menu(&mut self, ui: &mut Ui) {
+        let notebooks = Notebooks::default();
+        match notebooks.list() {
+            Ok(names) => {
+                let default = notebooks.default_notebook();
+                for name in names {
+                    let current = self.notebook.as_ref() == Some(&name);
+                    let label = if name == default { format!("{} ({})", name, t!("gui.notebook.default")) } else { name.clone() };
+                    if ui.radio(current, label).clicked() && !current {
+                        self.request(ui.ctx(), PendingAction::Notebook(name));
+                        ui.close();
+                    }
+                }
+            }
+            Err(e) => self.error = Some(e),
+        }
+        ui.separator();
+        if ui.button(Action::NewNotebook.label()).clicked() {
+            self.new_notebook = Some(String::new());
+            ui.close();
+        }
+        if let Some(name) = self.notebook.clone()
+            && ui.button(t!("gui.notebook.set_default")).clicked() {
+            if let Err(e) = notebooks.set_default(&name) {
+                self.error = Some(e);
+            }
+            ui.close();
+        }
+    }
+    /// 关闭当前的数据库并打开另一个笔记本
+    fn switch_notebook(&mut self, ctx: &Context, name: String) {
+        match self.executor.open_notebook(name.clone()) {
+            Ok(_) => {
+                let title = match name.as_str() {
+                    DEFAULT_NOTEBOOK => "Diary".to_string(),
+                    _ => format!("Diary - {}", name),
+                };
+                ctx.send_viewport_cmd(ViewportCommand::Title(title));
+                self.notebook = Some(name);
+                self.reload();
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+    /// 打开任意位置的数据库文件，不存在时创建
+    fn open_db_file(&mut self, ctx: &Context, file: PathBuf) {
+        match self.executor.open_db_file(file.clone()) {
+            Ok(_) => {
+                let name = file.file_name().unwrap_or(file.as_os_str()).to_string_lossy();
+                ctx.send_viewport_cmd(ViewportCommand::Title(format!("Diary - {}", name)));
+                self.notebook = None;
+                self.reload();
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+    /// 数据库改变后，重新读取所有展示的数据
+    fn reload(&mut self) {
+        // 使当前日期失效，下一次刷新时重新读取；有未保存的修改时保留正在编辑的内容
+        if !self.day.is_dirty() {
+            self.day.date = Date::from(self.date_selected - Duration::days(1)).into();
+        }
+        self.loading_day = None;
+        self.stats = None;
+        self.stats_query = None;
+        self.metric_defs_query.invalidate();
+        self.habits_query.invalidate();
+        self.charts.invalidate();
+        self.on_this_day.invalidate();
+        self.entries.invalidate();
+        if self.in_stats_page {
+            self.refresh_stats();
+        }
+    }
+    fn new_notebook_window(&mut self, ctx: &Context) {
+        let Some(name) = self.new_notebook.as_mut() else { return };
+        let mut open = true;
+        let mut create = false;
+        Window::new(t!("gui.notebook.new_title"))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let resp = ui.add(TextEdit::singleline(name).hint_text(t!("gui.notebook.name")));
+                create = resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                ui.label(RichText::new(t!("gui.notebook.name_rule")).weak());
+                create |= ui.button(t!("gui.notebook.create")).clicked();
+            });
+        if create {
+            let name = self.new_notebook.take().unwrap_or_default();
+            match self.executor.create_notebook(name.trim().to_string()) {
+                Ok(_) => self.request(ctx, PendingAction::Notebook(name.trim().to_string())),
+                Err(e) => {
+                    self.error = Some(e);
+                    self.new_notebook = Some(name);
+                }
+            }
+        } else if !open {
+            self.new_notebook = None;
+        }
+    }
+    /// 侧边栏中所选日期的习惯清单
+    fn habits_section(&mut self, ui: &mut Ui) {
+        if self.habits_query.needs_load(&()) {
+            let pending = self.executor.habit_progress(self.config.week_start);
+            self.habits_query.start((), pending);
+        }
+        match self.habits_query.poll() {
+            Some(Ok(progress)) => self.habits = progress,
+            Some(Err(e)) => {
+                self.error = Some(e);
+                self.habits.clear();
+            }
+            None => {}
+        }
+        let progress = &self.habits;
+        let event = ui.collapsing(t!("gui.side.habits"), |ui| {
+            habits_ui(ui, &mut self.habit_form, progress, &mut self.day.habits)
+        }).body_returned.flatten();
+        match event {
+            Some(HabitsEvent::Changed) => self.last_edit = Some(Instant::now()),
+            Some(HabitsEvent::Define(habit)) => {
+                if let Err(e) = self.executor.define_habit(habit) {
+                    self.error = Some(e);
+                }
+                self.habits_query.invalidate();
+            }
+            Some(HabitsEvent::Remove(name)) => {
+                match self.executor.remove_habit(name.clone()) {
+                    Ok(_) => self.day.forget_habit(&name),
+                    Err(e) => self.error = Some(e),
+                }
+                self.habits_query.invalidate();
+                self.entries.invalidate();
+            }
+            None => {}
+        }
+    }
+    /// 侧边栏中当天的指标
+    fn metrics_section(&mut self, ui: &mut Ui) {
+        if self.metric_defs_query.needs_load(&()) {
+            let pending = self.executor.metric_defs();
+            self.metric_defs_query.start((), pending);
+        }
+        match self.metric_defs_query.poll() {
+            Some(Ok(defs)) => self.metric_defs = defs,
+            Some(Err(e)) => {
+                self.error = Some(e);
+                self.metric_defs.clear();
+            }
+            None => {}
+        }
+        let defs = &self.metric_defs;
+        let event = ui.collapsing(t!("gui.side.metrics"), |ui| {
+            metrics_ui(ui, &mut self.metrics, defs, &mut self.day.metrics)
+        }).body_returned.flatten();
+        match event {
+            Some(MetricsEvent::Changed) => self.last_edit = Some(Instant::now()),
+            Some(MetricsEvent::Define(def)) => {
+                if let Err(e) = self.executor.define_metric(def) {
+                    self.error = Some(e);
+                }
+                self.metric_defs_query.invalidate();
+            }
+            Some(MetricsEvent::Remove(name)) => {
+                match self.executor.remove_metric(name.clone()) {
+                    Ok(_) => self.day.forget_metric(&name),
+                    Err(e) => self.error = Some(e),
+                }
+                self.metric_defs_query.invalidate();
+                self.stats = None;
+                self.stats_query = None;
+                self.entries.invalidate();
+            }
+            None => {}
+        }
+    }
+    fn entries_panel(&mut self, ctx: &Context) {
+        if self.show_entries && self.entries.needs_reload() {
+            let pending = self.executor.search(self.entries.condition(self.config.mood));
+            self.entries.start_load(pending);
+        }
+        if let Some(e) = self.entries.poll() {
+            self.error = Some(e);
+        }
+        SidePanel::left("entries_panel")
+            .default_width(220.)
+            .show_animated(ctx, self.show_entries, |ui| {
+                if let Some(date) = entries_ui(ui, &mut self.entries, self.date_selected, self.config.mood) {
+                    self.date_selected = date;
+                }
+            });
+    }
+    fn on_this_day_panel(&mut self, ctx: &Context) {
+        if self.show_on_this_day && self.on_this_day.needs_reload(self.date_selected) {
+            let pending = self.executor.on_this_day(Date::from(self.date_selected).into(), self.on_this_day.span);
+            self.on_this_day.start_load(self.date_selected, pending);
+        }
+        if let Some(e) = self.on_this_day.poll() {
+            self.error = Some(e);
+            self.show_on_this_day = false;
+        }
+        SidePanel::right("on_this_day_panel")
+            .default_width(220.)
+            .show_animated(ctx, self.show_on_this_day, |ui| {
+                if let Some(date) = on_this_day_ui(ui, &mut self.on_this_day) {
+                    self.date_selected = date;
+                }
+            });
+    }
     fn may_modal(&mut self, ctx: &Context) {
+        if let Some(action) = palette_ui(ctx, &mut self.palette, &self.shortcuts) {
+            self.run_action(ctx, action);
+        }
+        self.new_notebook_window(ctx);
+        self.job_modal(ctx);
+        self.unsaved_modal(ctx);
+        self.day_op_modal(ctx);
+        self.import_modal(ctx);
+        self.import_summary_modal(ctx);
         self.error_modal(ctx);
         self.about_modal(ctx);
+        self.stats_window(ctx);
+        self.charts_window(ctx);
+        self.settings_window(ctx);
+    }
+    fn charts_window(&mut self, ctx: &Context) {
+        if !self.in_charts_page {return}
+        let today = Date::default().into();
+        if self.charts.needs_reload(today) {
+            let (from, to) = self.charts.range(today);
+            let pending = self.executor.read_from_to(Date::from(from).into(), Date::from(to).into());
+            self.charts.start_load(today, pending);
+        }
+        if let Some(e) = self.charts.poll() {
+            self.error = Some(e);
+            self.in_charts_page = false;
+            return;
+        }
+        let mut open = true;
+        Window::new(t!("gui.charts.title"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(560.)
+            .show(ctx, |ui| charts_ui(ui, &mut self.charts, today, self.config.week_start));
+        self.in_charts_page = open;
+    }
+    fn settings_window(&mut self, ctx: &Context) {
+        if !self.in_settings_page {return}
+        let mut open = true;
+        let mut save = false;
+        let mut revert = false;
+        Window::new(t!("gui.settings.title"))
+            .id(Id::new("settings_window"))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let changed = settings_ui(ui, &mut self.config);
+                if changed.appearance {
+                    apply_appearance(ctx, &self.config);
+                }
+                if changed.language {
+                    set_locale(self.config.locale());
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let unsaved = self.config != self.saved_config;
+                    save = ui.add_enabled(unsaved, Button::new(t!("gui.common.save"))).clicked();
+                    revert = ui.add_enabled(unsaved, Button::new(t!("gui.settings.revert"))).clicked();
+                    if unsaved {
+                        ui.label(RichText::new(t!("gui.settings.unsaved_hint")).weak());
+                    }
+                });
+            });
+        self.in_settings_page = open;
+        if revert {
+            self.config = self.saved_config.clone();
+            apply_appearance(ctx, &self.config);
+            set_locale(self.config.locale());
+        }
+        if save {
+            self.save_settings(ctx);
+        }
+    }
+    /// 把设置写入配置文件，数据库位置改变时打开新的数据库
+    fn save_settings(&mut self, ctx: &Context) {
+        let res = self.config.validate()
+            .and_then(|_| self.config.save(&Config::resolve_path(None)));
+        if let Err(e) = res {
+            self.error = Some(e);
+            return;
+        }
+        let database = self.config.database.clone();
+        let moved = database != self.saved_config.database;
+        self.saved_config = self.config.clone();
+        if moved {
+            let action = match database {
+                Some(file) => PendingAction::DbFile(file),
+                None => PendingAction::Notebook(Notebooks::default().default_notebook()),
+            };
+            self.request(ctx, action);
+        }
+    }
+    /// 按`stats_range`重新计算统计数据，计算完成前仍展示上一次的结果
+    fn refresh_stats(&mut self) {
+        match self.stats_range.parse() {
+            Ok(range) => self.stats_query = Some(self.executor.statistics(range, self.config.week_start)),
+            Err(e) => self.error = Some(e),
+        }
+    }
+    fn stats_window(&mut self, ctx: &Context) {
+        if !self.in_stats_page {return}
+        if let Some(res) = self.stats_query.as_ref().and_then(Pending::poll) {
+            self.stats_query = None;
+            match res {
+                Ok(stats) => self.stats = Some(stats),
+                Err(e) => self.error = Some(e),
+            }
+        }
+        let mut open = true;
+        Window::new(t!("gui.stats.title"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(420.)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let range_input = TextEdit::singleline(&mut self.stats_range)
+                        .hint_text(t!("gui.stats.range_hint"))
+                        .desired_width(200.);
+                    let resp = ui.add(range_input);
+                    if ui.button(t!("gui.stats.refresh")).clicked()
+                        || resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                        self.refresh_stats();
+                    }
+                    if ui.button(t!("gui.charts.title")).clicked() {
+                        self.in_charts_page = true;
+                    }
+                });
+                ui.separator();
+                if let Some(stats) = &self.stats {
+                    statistics_ui(ui, stats);
+                }
+            });
+        self.in_stats_page = open;
     }
-    #[warn(deprecated)]
     fn about_modal(&mut self, ctx: &Context) {
         if !self.in_about_page {return}
-        Window::new("About")
+        Window::new(t!("gui.about.title"))
             .resizable(false)
             .collapsible(false)
             .order(Order::Foreground)
@@ -207,27 +962,26 @@ impl App {
                                 [50., 50.].into(),
                             );
 
-                            if ui.put(icon_rec, ImageButton::new(github_icon).frame(false)).clicked() {
-                                println!("Clicked!");
+                            if ui.put(icon_rec, Button::image(github_icon).frame(false)).clicked() {
                                 ctx.open_url(OpenUrl::new_tab("https://github.com/x0710/Diary"))
                             }
 
                         });
                     });
                     ui.label(RichText::new(
-                        format!("v{}, Core Version: {}" , env!("CARGO_PKG_VERSION"), diary_core::base::env::version())).weak());
+                        t!("gui.about.version", version = env!("CARGO_PKG_VERSION"), core = diary_core::base::env::version())).weak());
                     ui.separator();
                     ui.add_space(4.0);
-                    ui.label("A high-performance tool built with Rust and egui.");
-                    ui.label("BTW, it's always used in writing diary.");
+                    ui.label(t!("gui.about.line1"));
+                    ui.label(t!("gui.about.line2"));
                     ui.add_space(10.0);
                     ui.horizontal(|ui| {
-                        ui.label("Created by");
+                        ui.label(t!("gui.about.created_by"));
                         ui.hyperlink_to("JinhangGao", "https://github.com/x0710");
                     });
                     ui.add_space(20.0);
                     ui.with_layout(Layout::top_down(Align::Center), |ui| {
-                        if ui.button("  OK  ").clicked() {
+                        if ui.button(format!("  {}  ", t!("gui.common.ok"))).clicked() {
                             self.in_about_page = false;
                         }
                     });
@@ -238,14 +992,14 @@ impl App {
         if self.error.is_none() {return}
         self.open_modal(ctx);
 
-        Window::new("Error")
+        Window::new(t!("gui.error.title"))
             .resizable(false)
             .collapsible(false)
             .order(Order::Foreground)
             .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
             .show(ctx, |ui| {
                 ui.label(format!("{}", self.error.as_ref().unwrap()));
-                if ui.button("OK").clicked() {
+                if ui.button(t!("gui.common.ok")).clicked() {
                     self.error.take();
                 }
             });
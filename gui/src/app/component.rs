@@ -1 +1,16 @@
-pub mod face;
\ No newline at end of file
+pub mod face;
+pub mod stats;
+pub mod charts;
+pub mod onthisday;
+pub mod markdown;
+pub mod editor;
+pub mod entries;
+pub mod settings;
+pub mod palette;
+pub mod dayops;
+pub mod import;
+pub mod datepicker;
+pub mod weather;
+pub mod mood;
+pub mod metrics;
+pub mod habits;
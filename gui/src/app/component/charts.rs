@@ -0,0 +1,216 @@
+//! 心情与写作情况的图表
+use std::collections::HashMap;
+use chrono::{Datelike, Duration, NaiveDate};
+use eframe::egui::{Color32, ComboBox, DragValue, ProgressBar, Rect, Sense, Ui, vec2};
+use egui_plot::{Legend, Line, Plot, PlotPoints, Points};
+use diary_core::model::Day;
+use diary_core::base::config::WeekStart;
+use diary_core::t;
+use diary_core::base::date::DateRange;
+use diary_core::base::error::Error;
+use diary_core::utils::stats::{rolling_mood, Statistics};
+use crate::app::component::face::mood_to_face;
+use crate::model::date::Date;
+use crate::service::executor::{Pending, Query};
+
+/// 热力图单元格边长
+const CELL: f32 = 12.;
+/// 热力图单元格间距
+const GAP: f32 = 2.;
+
+/// 图表展示的时间跨度（截止到今天）
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ChartSpan {
+    Month,
+    Quarter,
+    HalfYear,
+    Year,
+}
+impl ChartSpan {
+    pub const ALL: [ChartSpan; 4] = [ChartSpan::Month, ChartSpan::Quarter, ChartSpan::HalfYear, ChartSpan::Year];
+
+    pub fn days(&self) -> i64 {
+        match self {
+            ChartSpan::Month => 30,
+            ChartSpan::Quarter => 91,
+            ChartSpan::HalfYear => 182,
+            ChartSpan::Year => 365,
+        }
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartSpan::Month => t!("gui.charts.month"),
+            ChartSpan::Quarter => t!("gui.charts.quarter"),
+            ChartSpan::HalfYear => t!("gui.charts.half_year"),
+            ChartSpan::Year => t!("gui.charts.year"),
+        }
+    }
+}
+
+/// 图表窗口的状态及已加载的数据
+pub struct ChartsState {
+    pub span: ChartSpan,
+    /// 滑动平均的窗口天数
+    pub window: usize,
+    pub days: Vec<Day>,
+    /// 按(截止日期, 跨度)读取的数据
+    load: Query<(NaiveDate, ChartSpan), Vec<Day>>,
+}
+impl ChartsState {
+    /// 截止到`today`的展示区间
+    pub fn range(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        (today - Duration::days(self.span.days() - 1), today)
+    }
+    pub fn needs_reload(&self, today: NaiveDate) -> bool {
+        self.load.needs_load(&(today, self.span))
+    }
+    pub fn start_load(&mut self, today: NaiveDate, pending: Pending<Vec<Day>>) {
+        self.load.start((today, self.span), pending);
+    }
+    /// 取回查询结果，出错时返回错误
+    pub fn poll(&mut self) -> Option<Error> {
+        match self.load.poll()? {
+            Ok(days) => self.days = days,
+            Err(e) => return Some(e),
+        }
+        None
+    }
+    /// 数据库有变动时调用，下次展示时重新加载
+    pub fn invalidate(&mut self) {
+        self.load.invalidate();
+    }
+}
+impl Default for ChartsState {
+    fn default() -> Self {
+        Self {
+            span: ChartSpan::Quarter,
+            window: 7,
+            days: Vec::new(),
+            load: Query::default(),
+        }
+    }
+}
+
+/// 图表窗口的主体内容
+pub fn charts_ui(ui: &mut Ui, state: &mut ChartsState, today: NaiveDate, week_start: WeekStart) {
+    ui.horizontal(|ui| {
+        ComboBox::from_id_salt("chart_span")
+            .selected_text(state.span.label())
+            .show_ui(ui, |ui| {
+                for span in ChartSpan::ALL {
+                    ui.selectable_value(&mut state.span, span, span.label());
+                }
+            });
+        ui.add(DragValue::new(&mut state.window)
+            .range(1..=30)
+            .prefix(format!("{}: ", t!("gui.charts.rolling_average")))
+            .suffix(format!(" {}", t!("gui.common.days"))));
+    });
+    ui.separator();
+
+    ui.label(t!("gui.side.mood"));
+    mood_chart(ui, state);
+    ui.separator();
+
+    ui.label(t!("gui.charts.entries"));
+    let (from, to) = state.range(today);
+    heatmap(ui, &state.days, from, to, week_start);
+    ui.separator();
+
+    ui.label(t!("gui.side.weather"));
+    weather_breakdown(ui, &state.days, week_start);
+}
+
+/// 心情折线图：原始值为散点，滑动平均为折线
+fn mood_chart(ui: &mut Ui, state: &ChartsState) {
+    let x_of = |d: diary_core::base::date::Date| NaiveDate::from(Date::from(d)).num_days_from_ce() as f64;
+    let raw = state.days.iter()
+        .filter_map(|d| d.mood.map(|m| [x_of(d.date), m.value()]))
+        .collect::<Vec<_>>();
+    let avg = rolling_mood(&state.days, state.window).into_iter()
+        .map(|(d, m)| [x_of(d), m])
+        .collect::<Vec<_>>();
+    let date_label = |x: f64| NaiveDate::from_num_days_from_ce_opt(x.round() as i32)
+        .map(|d| d.format("%m-%d").to_string())
+        .unwrap_or_default();
+
+    Plot::new("mood_chart")
+        .height(180.)
+        .include_y(0.)
+        .include_y(10.)
+        .allow_scroll(false)
+        .legend(Legend::default())
+        .x_axis_formatter(move |mark, _| date_label(mark.value))
+        .label_formatter(move |_, p| format!("{}\n{:.1}", date_label(p.x), p.y))
+        .show(ui, |plot_ui| {
+            plot_ui.points(Points::new(t!("gui.side.mood"), PlotPoints::new(raw)).radius(2.5));
+            plot_ui.line(Line::new(t!("gui.charts.rolling_average"), PlotPoints::new(avg)).width(2.));
+        });
+}
+
+/// GitHub风格的日历热力图，每列为一周，每行为一周中的一天
+fn heatmap(ui: &mut Ui, days: &[Day], from: NaiveDate, to: NaiveDate, week_start: WeekStart) {
+    let by_date = days.iter()
+        .map(|d| (NaiveDate::from(Date::from(d.date)), d))
+        .collect::<HashMap<_, _>>();
+    let offset = week_start.days_from_start(from.weekday().num_days_from_monday() as u8);
+    let first = from - Duration::days(offset as i64);
+    let weeks = ((to - first).num_days() / 7 + 1) as usize;
+
+    let size = vec2(weeks as f32 * (CELL + GAP), 7. * (CELL + GAP));
+    let (rect, resp) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    let empty = ui.visuals().faint_bg_color;
+    let mut hovered = None;
+
+    for week in 0..weeks {
+        for wd in 0..7 {
+            let date = first + Duration::days((week * 7 + wd) as i64);
+            if date < from || date > to { continue }
+            let min = rect.min + vec2(week as f32 * (CELL + GAP), wd as f32 * (CELL + GAP));
+            let cell = Rect::from_min_size(min, vec2(CELL, CELL));
+            let day = by_date.get(&date);
+            let color = match day {
+                Some(Day { mood: Some(m), .. }) => mood_to_face(m.value()).1,
+                Some(_) => Color32::from_rgb(64, 160, 64),
+                None => empty,
+            };
+            painter.rect_filled(cell, 2., color);
+            if date == to {
+                painter.rect_stroke(cell, 2., ui.visuals().selection.stroke, eframe::egui::StrokeKind::Outside);
+            }
+            if resp.hover_pos().is_some_and(|p| cell.contains(p)) {
+                hovered = Some((date, day.copied()));
+            }
+        }
+    }
+    if let Some((date, day)) = hovered {
+        let text = match day {
+            Some(d) => match d.mood {
+                Some(m) => format!("{}\n{}: {:.1} {} {}", date, t!("gui.side.mood"), m.value(), m.label(), mood_to_face(m.value()).0),
+                None => format!("{}\n{}", date, t!("gui.charts.written")),
+            },
+            None => format!("{}\n{}", date, t!("gui.charts.no_entry")),
+        };
+        resp.on_hover_text_at_pointer(text);
+    }
+}
+
+/// 天气出现次数的条形图
+fn weather_breakdown(ui: &mut Ui, days: &[Day], week_start: WeekStart) {
+    let today = diary_core::base::date::Date::native_time();
+    let weather = Statistics::from_days(days, DateRange::default(), today, week_start).weather;
+    let Some(max) = weather.first().map(|t| t.1) else {
+        ui.weak(t!("gui.charts.no_weather"));
+        return;
+    };
+    for (c, n) in weather {
+        ui.horizontal(|ui| {
+            let label = format!("{} {}", c.icon(), c.label());
+            ui.add_sized([80., 14.], eframe::egui::Label::new(label).truncate());
+            ui.add(ProgressBar::new(n as f32 / max as f32)
+                .desired_width(220.)
+                .text(n.to_string()));
+        });
+    }
+}
@@ -0,0 +1,76 @@
+//! 按当前语言显示月份与星期的日期选择器
+use std::hash::Hash;
+use chrono::{Datelike, Months, NaiveDate};
+use eframe::egui::{Button, Grid, Popup, PopupCloseBehavior, RichText, Ui};
+use time::{Month, Weekday};
+use diary_core::base::config::WeekStart;
+use diary_core::base::i18n::{month_name, weekday_short};
+
+/// 从周一开始的星期
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday, Weekday::Tuesday, Weekday::Wednesday, Weekday::Thursday,
+    Weekday::Friday, Weekday::Saturday, Weekday::Sunday,
+];
+
+/// 绘制日期按钮，点击后弹出日历，选中日期时返回`true`
+pub fn date_picker(ui: &mut Ui, id_salt: impl Hash, date: &mut NaiveDate, week_start: WeekStart) -> bool {
+    ui.push_id(id_salt, |ui| {
+        let button = ui.button(format!("{} 📆", date.format("%Y-%m-%d")));
+        // 弹出窗口中正在查看的月份的第一天
+        let month_id = button.id.with("month");
+        if button.clicked() {
+            ui.data_mut(|d| d.insert_temp(month_id, date.with_day(1).unwrap_or(*date)));
+        }
+        let mut picked = false;
+        Popup::from_toggle_button_response(&button)
+            .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+            .show(|ui| {
+                let mut month = ui.data(|d| d.get_temp(month_id)).unwrap_or(*date);
+                ui.horizontal(|ui| {
+                    if ui.small_button("◀").clicked() {
+                        month = month.checked_sub_months(Months::new(1)).unwrap_or(month);
+                    }
+                    let name = Month::try_from(month.month() as u8).map(month_name).unwrap_or_default();
+                    ui.label(RichText::new(format!("{} {}", month.year(), name)).strong());
+                    if ui.small_button("▶").clicked() {
+                        month = month.checked_add_months(Months::new(1)).unwrap_or(month);
+                    }
+                });
+                Grid::new("days").num_columns(7).spacing([2., 2.]).show(ui, |ui| {
+                    let mut weekdays = WEEKDAYS;
+                    weekdays.sort_by_key(|wd| week_start.days_from_start(wd.number_days_from_monday()));
+                    for weekday in weekdays {
+                        ui.label(RichText::new(weekday_short(weekday)).weak());
+                    }
+                    ui.end_row();
+
+                    let offset = week_start.days_from_start(month.weekday().num_days_from_monday() as u8);
+                    for _ in 0..offset {
+                        ui.label("");
+                    }
+                    let mut day = month;
+                    let mut column = offset;
+                    while day.month() == month.month() {
+                        let text = RichText::new(format!("{:>2}", day.day())).monospace();
+                        if ui.add(Button::new(text).selected(day == *date)).clicked() {
+                            *date = day;
+                            picked = true;
+                        }
+                        column += 1;
+                        if column.is_multiple_of(7) {
+                            ui.end_row();
+                        }
+                        match day.succ_opt() {
+                            Some(next) => day = next,
+                            None => break,
+                        }
+                    }
+                });
+                ui.data_mut(|d| d.insert_temp(month_id, month));
+                if picked {
+                    ui.close();
+                }
+            });
+        picked
+    }).inner
+}
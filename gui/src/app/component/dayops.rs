@@ -0,0 +1,130 @@
+//! 删除、移动与复制某一天日记的对话框
+use chrono::NaiveDate;
+use eframe::egui::{Align2, Button, Context, Order, RichText, Window};
+use diary_core::base::config::WeekStart;
+use diary_core::base::error::Error;
+use diary_core::storage::MoveMode;
+use diary_core::t;
+use crate::app::component::datepicker::date_picker;
+use crate::service::executor::Pending;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum DayOpKind {
+    Delete,
+    Move,
+    /// 把正文作为模板复制到另一天
+    Copy,
+}
+impl DayOpKind {
+    fn title(&self) -> &'static str {
+        match self {
+            DayOpKind::Delete => t!("gui.dayop.delete_title"),
+            DayOpKind::Move => t!("gui.dayop.move_title"),
+            DayOpKind::Copy => t!("gui.dayop.copy_title"),
+        }
+    }
+}
+
+/// 对话框的状态，`source`为被操作的日期
+pub struct DayOpState {
+    pub kind: DayOpKind,
+    pub source: NaiveDate,
+    pub target: NaiveDate,
+    /// 已查询过的目标日期，以及它是否已有记录
+    checked: Option<(NaiveDate, bool)>,
+    /// 正在查询的目标日期
+    checking: Option<(NaiveDate, Pending<bool>)>,
+    /// 目标日期已有记录时的处理方式
+    merge: bool,
+}
+impl DayOpState {
+    pub fn new(kind: DayOpKind, source: NaiveDate) -> Self {
+        let target = source.succ_opt().unwrap_or(source);
+        Self { kind, source, target, checked: None, checking: None, merge: true }
+    }
+    /// 目标日期改变后需要重新查询是否已有记录
+    pub fn unchecked_target(&self) -> Option<NaiveDate> {
+        match (self.kind, self.checked, &self.checking) {
+            (DayOpKind::Delete, _, _) => None,
+            (_, Some((date, _)), _) if date == self.target => None,
+            (_, _, Some((date, _))) if *date == self.target => None,
+            _ => Some(self.target),
+        }
+    }
+    /// 开始查询目标日期是否已有记录
+    pub fn check_target(&mut self, date: NaiveDate, pending: Pending<bool>) {
+        self.checking = Some((date, pending));
+    }
+    /// 取回查询结果，出错时返回错误
+    pub fn poll(&mut self) -> Option<Error> {
+        let res = self.checking.as_ref()?.1.poll()?;
+        let (date, _) = self.checking.take()?;
+        match res {
+            Ok(exists) => self.checked = Some((date, exists)),
+            Err(e) => return Some(e),
+        }
+        None
+    }
+    fn target_exists(&self) -> bool {
+        matches!(self.checked, Some((date, true)) if date == self.target)
+    }
+    /// 目标日期没有记录时仍用`Fail`，避免查询后又被写入的记录被覆盖
+    pub fn mode(&self) -> MoveMode {
+        match (self.target_exists(), self.merge) {
+            (false, _) => MoveMode::Fail,
+            (true, true) => MoveMode::Merge,
+            (true, false) => MoveMode::Overwrite,
+        }
+    }
+}
+
+/// 绘制对话框，确认时返回`Some(true)`，取消时返回`Some(false)`
+pub fn day_op_ui(ctx: &Context, state: &mut DayOpState, week_start: WeekStart) -> Option<bool> {
+    let mut result = None;
+    Window::new(state.kind.title())
+        .resizable(false)
+        .collapsible(false)
+        .order(Order::Foreground)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            let confirm = match state.kind {
+                DayOpKind::Delete => {
+                    ui.label(t!("gui.dayop.delete_confirm", date = state.source));
+                    ui.label(RichText::new(t!("gui.dayop.irreversible")).weak());
+                    Button::new(t!("gui.dayop.delete"))
+                }
+                kind => {
+                    ui.horizontal(|ui| {
+                        ui.label(t!("gui.dayop.to", date = state.source));
+                        date_picker(ui, "day_op_target", &mut state.target, week_start);
+                    });
+                    if kind == DayOpKind::Copy {
+                        ui.label(RichText::new(t!("gui.dayop.copy_hint")).weak());
+                    }
+                    if state.target_exists() {
+                        ui.label(t!("gui.dayop.target_exists", date = state.target));
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut state.merge, true, t!("gui.import.merge"))
+                                .on_hover_text(t!("gui.import.merge_hint"));
+                            ui.radio_value(&mut state.merge, false, t!("gui.dayop.overwrite"))
+                                .on_hover_text(t!("gui.dayop.overwrite_hint"));
+                        });
+                    }
+                    Button::new(if kind == DayOpKind::Move { t!("gui.dayop.move") } else { t!("gui.dayop.copy") })
+                }
+            };
+            let same = state.kind != DayOpKind::Delete && state.target == state.source;
+            if same {
+                ui.label(RichText::new(t!("gui.dayop.same_date")).weak());
+            }
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!same && state.unchecked_target().is_none(), confirm).clicked() {
+                    result = Some(true);
+                }
+                if ui.button(t!("gui.common.cancel")).clicked() {
+                    result = Some(false);
+                }
+            });
+        });
+    result
+}
@@ -0,0 +1,234 @@
+//! 中央的日记编辑区：编辑、分栏与预览模式，以及Markdown格式工具栏
+use eframe::egui::{Context, Id, Key, KeyboardShortcut, Modifiers, ScrollArea, TextEdit, Ui};
+use eframe::egui::text::{CCursor, CCursorRange};
+use eframe::egui::text_edit::TextEditState;
+use diary_core::t;
+use crate::app::component::markdown::markdown_ui;
+
+const BOLD: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::B);
+const ITALIC: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::I);
+const LIST: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::L);
+
+/// 编辑区的展示方式
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum EditorMode {
+    #[default]
+    Edit,
+    Split,
+    Preview,
+}
+impl EditorMode {
+    pub const ALL: [EditorMode; 3] = [EditorMode::Edit, EditorMode::Split, EditorMode::Preview];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EditorMode::Edit => t!("gui.editor.edit"),
+            EditorMode::Split => t!("gui.editor.split"),
+            EditorMode::Preview => t!("gui.editor.preview"),
+        }
+    }
+}
+
+/// 工具栏与快捷键对应的格式操作
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+enum Format {
+    Bold,
+    Italic,
+    Strike,
+    Code,
+    Heading,
+    Bullet,
+    Numbered,
+    Task,
+    Quote,
+    Link,
+}
+impl Format {
+    /// 格式、按钮文字与提示在消息目录中的键
+    const TOOLBAR: [(Format, &'static str, &'static str); 10] = [
+        (Format::Bold, "B", "gui.editor.bold"),
+        (Format::Italic, "I", "gui.editor.italic"),
+        (Format::Strike, "S", "gui.editor.strike"),
+        (Format::Code, "<>", "gui.editor.code"),
+        (Format::Heading, "H", "gui.editor.heading"),
+        (Format::Bullet, "•", "gui.editor.bullet"),
+        (Format::Numbered, "1.", "gui.editor.numbered"),
+        (Format::Task, "☐", "gui.editor.task"),
+        (Format::Quote, "❝", "gui.editor.quote"),
+        (Format::Link, "🔗", "gui.editor.link"),
+    ];
+}
+
+#[derive(Default)]
+pub struct EditorState {
+    pub mode: EditorMode,
+}
+
+/// 字符下标对应的字节下标
+fn byte_idx(text: &str, char_idx: usize) -> usize {
+    text.char_indices().nth(char_idx).map(|t| t.0).unwrap_or(text.len())
+}
+
+/// 用`marker`包裹选中的文本，已被包裹时去掉，返回新的选区（字符下标）
+fn toggle_wrap(text: &mut String, (start, end): (usize, usize), marker: &str) -> (usize, usize) {
+    let len = marker.chars().count();
+    let (bs, be) = (byte_idx(text, start), byte_idx(text, end));
+    if text[..bs].ends_with(marker) && text[be..].starts_with(marker) {
+        text.replace_range(be..be + marker.len(), "");
+        text.replace_range(bs - marker.len()..bs, "");
+        return (start - len, end - len);
+    }
+    text.insert_str(be, marker);
+    text.insert_str(bs, marker);
+    (start + len, end + len)
+}
+
+/// 给选中的每一行加上前缀，所有行都已有该前缀时去掉；`numbered`时前缀为递增的序号
+fn toggle_prefix(text: &mut String, (start, end): (usize, usize), prefix: &str, numbered: bool) -> (usize, usize) {
+    let (bs, be) = (byte_idx(text, start), byte_idx(text, end));
+    let line_start = text[..bs].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = text[be..].find('\n').map(|i| be + i).unwrap_or(text.len());
+    let lines = text[line_start..line_end].split('\n').collect::<Vec<_>>();
+    let prefix_of = |i: usize| if numbered { format!("{}. ", i + 1) } else { prefix.to_string() };
+    let all_prefixed = lines.iter().enumerate().all(|(i, l)| l.starts_with(&prefix_of(i)));
+    let replaced = lines.iter().enumerate()
+        .map(|(i, l)| match all_prefixed {
+            true => l[prefix_of(i).len()..].to_string(),
+            false => format!("{}{}", prefix_of(i), l),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let old_chars = text[line_start..line_end].chars().count() as isize;
+    let new_chars = replaced.chars().count() as isize;
+    text.replace_range(line_start..line_end, &replaced);
+    let first = if all_prefixed { -(prefix_of(0).chars().count() as isize) } else { prefix_of(0).chars().count() as isize };
+    let start = (start as isize + first).max(text[..line_start].chars().count() as isize) as usize;
+    (start, (end as isize + new_chars - old_chars) as usize)
+}
+
+/// 对选区执行格式操作，返回新的选区
+fn apply(text: &mut String, selection: (usize, usize), format: Format) -> (usize, usize) {
+    match format {
+        Format::Bold => toggle_wrap(text, selection, "**"),
+        Format::Italic => toggle_wrap(text, selection, "*"),
+        Format::Strike => toggle_wrap(text, selection, "~~"),
+        Format::Code => toggle_wrap(text, selection, "`"),
+        Format::Heading => toggle_prefix(text, selection, "## ", false),
+        Format::Bullet => toggle_prefix(text, selection, "- ", false),
+        Format::Numbered => toggle_prefix(text, selection, "", true),
+        Format::Task => toggle_prefix(text, selection, "- [ ] ", false),
+        Format::Quote => toggle_prefix(text, selection, "> ", false),
+        Format::Link => {
+            // 选中文本作为链接文字，光标停在地址处
+            let (start, end) = selection;
+            let be = byte_idx(text, end);
+            text.insert_str(be, "](https://)");
+            text.insert(byte_idx(text, start), '[');
+            let url = end + 3;
+            (url, url + "https://".len())
+        }
+    }
+}
+
+/// 绘制格式工具栏与编辑区，内容被修改时返回`true`
+pub fn editor_ui(ui: &mut Ui, text: &mut String, state: &mut EditorState) -> bool {
+    let id = Id::new("diary_editor");
+    let ctx = ui.ctx().clone();
+    let mut format = None;
+    ui.horizontal(|ui| {
+        for mode in EditorMode::ALL {
+            ui.selectable_value(&mut state.mode, mode, mode.label());
+        }
+        if state.mode == EditorMode::Preview { return }
+        ui.separator();
+        for (f, label, tip) in Format::TOOLBAR {
+            if ui.small_button(label).on_hover_text(t!(tip)).clicked() {
+                format = Some(f);
+            }
+        }
+    });
+    ui.separator();
+    if state.mode != EditorMode::Preview && ctx.memory(|m| m.has_focus(id)) {
+        format = format.or_else(|| ctx.input_mut(|i| {
+            [(BOLD, Format::Bold), (ITALIC, Format::Italic), (LIST, Format::Bullet)].into_iter()
+                .find(|(s, _)| i.consume_shortcut(s))
+                .map(|t| t.1)
+        }));
+    }
+    let mut changed = false;
+    if let Some(format) = format {
+        apply_format(&ctx, id, text, format);
+        changed = true;
+    }
+
+    let edit = |ui: &mut Ui, text: &mut String| {
+        ScrollArea::vertical().id_salt("editor_scroll").show(ui, |ui| {
+            ui.add_sized(ui.available_size(), TextEdit::multiline(text).id(id)).changed()
+        }).inner
+    };
+    let preview = |ui: &mut Ui, text: &mut String| {
+        ScrollArea::vertical().id_salt("preview_scroll").show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            markdown_ui(ui, text)
+        }).inner
+    };
+    changed |= match state.mode {
+        EditorMode::Edit => edit(ui, text),
+        EditorMode::Preview => preview(ui, text),
+        EditorMode::Split => {
+            ui.columns(2, |cols| {
+                let a = edit(&mut cols[0], text);
+                let b = preview(&mut cols[1], text);
+                a | b
+            })
+        }
+    };
+    changed
+}
+
+/// 按编辑框保存的选区执行格式操作，并把光标放到新的选区
+fn apply_format(ctx: &Context, id: Id, text: &mut String, format: Format) {
+    let mut edit_state = TextEditState::load(ctx, id).unwrap_or_default();
+    let len = text.chars().count();
+    let selection = edit_state.cursor.char_range()
+        .map(|r| r.as_sorted_char_range())
+        .map(|r| (r.start.min(len), r.end.min(len)))
+        .unwrap_or((len, len));
+    let (start, end) = apply(text, selection, format);
+    edit_state.cursor.set_char_range(Some(CCursorRange::two(CCursor::new(start), CCursor::new(end))));
+    edit_state.store(ctx, id);
+    ctx.memory_mut(|m| m.request_focus(id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapping_toggles() {
+        let mut text = "今天 晴".to_string();
+        let selection = apply(&mut text, (0, 2), Format::Bold);
+        assert_eq!((text.as_str(), selection), ("**今天** 晴", (2, 4)));
+        let selection = apply(&mut text, selection, Format::Bold);
+        assert_eq!((text.as_str(), selection), ("今天 晴", (0, 2)));
+    }
+
+    #[test]
+    fn prefixes_apply_to_every_selected_line() {
+        let mut text = "a\nb\nc".to_string();
+        apply(&mut text, (0, 3), Format::Numbered);
+        assert_eq!(text, "1. a\n2. b\nc");
+        apply(&mut text, (0, 8), Format::Numbered);
+        assert_eq!(text, "a\nb\nc");
+        apply(&mut text, (2, 2), Format::Task);
+        assert_eq!(text, "a\n- [ ] b\nc");
+    }
+
+    #[test]
+    fn link_selects_the_url() {
+        let mut text = "see docs".to_string();
+        let (start, end) = apply(&mut text, (4, 8), Format::Link);
+        assert_eq!(text, "see [docs](https://)");
+        assert_eq!(text.chars().skip(start).take(end - start).collect::<String>(), "https://");
+    }
+}
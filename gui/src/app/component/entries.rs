@@ -0,0 +1,172 @@
+//! 日记列表与搜索侧栏
+use chrono::NaiveDate;
+use eframe::egui::{ComboBox, DragValue, Label, RichText, ScrollArea, Sense, TextEdit, Ui};
+use diary_core::base::error::Error;
+use diary_core::t;
+use diary_core::model::{Condition, Day, MoodScale};
+use diary_core::utils::search::{tags_in, SearchCondition};
+use diary_core::utils::template::summary;
+use crate::app::component::face::mood_to_face;
+use crate::model::date::Date;
+use crate::service::executor::{Pending, Query};
+
+/// 列表中每条摘要最多展示的字符数
+const SUMMARY_CHARS: usize = 60;
+
+/// 搜索条件及查询结果
+#[derive(Default)]
+pub struct EntriesState {
+    pub query: String,
+    /// 天气状况，为`None`时不按天气过滤
+    pub condition: Option<Condition>,
+    /// 空格分隔的标签，可带`#`
+    pub tags: String,
+    /// 按配置刻度的心情范围，为`None`时不按心情过滤
+    pub mood: Option<(f64, f64)>,
+    /// 下次绘制时把焦点移到搜索框
+    pub focus_search: bool,
+    pub days: Vec<Day>,
+    /// 条件改变或数据库有变动时失效
+    load: Query<(), Vec<Day>>,
+}
+impl EntriesState {
+    pub fn needs_reload(&self) -> bool {
+        self.load.needs_load(&())
+    }
+    pub fn start_load(&mut self, pending: Pending<Vec<Day>>) {
+        self.load.start((), pending);
+    }
+    /// 取回查询结果，出错时清空列表并返回错误
+    pub fn poll(&mut self) -> Option<Error> {
+        match self.load.poll()? {
+            Ok(days) => self.days = days,
+            Err(e) => {
+                self.days.clear();
+                return Some(e);
+            }
+        }
+        None
+    }
+    /// 数据库有变动时调用，下次展示时重新加载
+    pub fn invalidate(&mut self) {
+        self.load.invalidate();
+    }
+    /// 按当前输入生成查询条件
+    pub fn condition(&self, scale: MoodScale) -> SearchCondition {
+        let mut condition = SearchCondition::default();
+        if !self.query.trim().is_empty() {
+            condition = condition.with_keyword(self.query.trim());
+        }
+        if let Some(c) = self.condition {
+            condition = condition.with_condition(c);
+        }
+        for tag in self.tags.split_whitespace() {
+            condition = condition.with_tag(tag);
+        }
+        if let Some((from, to)) = self.mood
+            && let (Ok(from), Ok(to)) = (scale.from_scale(from), scale.from_scale(to)) {
+            condition = condition.with_mood_from(from).with_mood_to(to);
+        }
+        condition
+    }
+}
+
+/// 绘制侧栏，返回用户点击要跳转到的日期
+pub fn entries_ui(ui: &mut Ui, state: &mut EntriesState, selected: NaiveDate, scale: MoodScale) -> Option<NaiveDate> {
+    let mut jump = None;
+    let mut changed = false;
+    ui.heading(t!("gui.charts.entries"));
+    let search = ui.add(TextEdit::singleline(&mut state.query).hint_text(t!("gui.entries.search")));
+    if std::mem::take(&mut state.focus_search) {
+        search.request_focus();
+    }
+    changed |= search.changed();
+    ui.collapsing(t!("gui.entries.filters"), |ui| {
+        let current = state.condition.map_or(t!("gui.weather.any").to_string(), |c| format!("{} {}", c.icon(), c.label()));
+        ComboBox::from_id_salt("entries_condition")
+            .selected_text(current)
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(&mut state.condition, None, t!("gui.weather.any")).changed();
+                for c in Condition::ALL {
+                    changed |= ui.selectable_value(&mut state.condition, Some(c), format!("{} {}", c.icon(), c.label())).changed();
+                }
+            });
+        changed |= ui.add(TextEdit::singleline(&mut state.tags).hint_text(t!("gui.entries.tags"))).changed();
+        ui.horizontal(|ui| {
+            let mut enabled = state.mood.is_some();
+            if ui.checkbox(&mut enabled, t!("gui.side.mood")).changed() {
+                state.mood = enabled.then_some(scale.range());
+                changed = true;
+            }
+            if let Some((from, to)) = state.mood.as_mut() {
+                let (min, max) = scale.range();
+                changed |= ui.add(DragValue::new(from).range(min..=max).speed(scale.step())).changed();
+                ui.label("–");
+                changed |= ui.add(DragValue::new(to).range(min..=max).speed(scale.step())).changed();
+            }
+        });
+    });
+    if changed {
+        state.invalidate();
+    }
+    ui.label(RichText::new(t!("gui.entries.count", count = state.days.len())).weak());
+    ui.separator();
+    ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+        for day in &state.days {
+            let date: NaiveDate = Date::from(day.date).into();
+            let resp = ui.horizontal(|ui| {
+                let current = date == selected;
+                let mut title = RichText::new(format!("{}", *day.date));
+                if current { title = title.strong() }
+                ui.label(title);
+                if let Some(mood) = day.mood {
+                    let (face, color) = mood_to_face(mood.value());
+                    ui.label(RichText::new(face).color(color));
+                }
+            }).response;
+            let text = summary(&day.event.instruct);
+            let mut preview = text.chars().take(SUMMARY_CHARS).collect::<String>();
+            if preview.len() < text.len() { preview.push('…') }
+            let preview = ui.add(Label::new(RichText::new(preview).weak()).truncate().sense(Sense::click()));
+            let tags = tags_in(&day.event.instruct);
+            if !tags.is_empty() {
+                ui.label(RichText::new(tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" ")).small());
+            }
+            if resp.interact(Sense::click()).clicked() || preview.clicked() {
+                jump = Some(date);
+            }
+            ui.separator();
+        }
+    });
+    jump
+}
+
+#[cfg(test)]
+mod tests {
+    use diary_core::base::blocking::BlockingDiary;
+    use diary_core::model::Mood;
+    use super::*;
+
+    #[test]
+    fn filters_follow_the_mood_scale() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut diary = BlockingDiary::open(dir.path().join("diary.db")).unwrap();
+        for (d, text, mood) in [(1, "#跑步 公园", 10.), (2, "#跑步 公园", 5.), (3, "公园", 10.)] {
+            let date = diary_core::base::date::Date::new(2025, 1, d).unwrap();
+            let day = Day { date, event: text.to_string().into(), mood: Some(Mood::new(mood).unwrap()), ..Default::default() };
+            diary.add_day(&day).unwrap();
+        }
+        let state = EntriesState {
+            query: " 公园 ".to_string(),
+            tags: "#跑步".to_string(),
+            // 1~5分刻度上的4~5分即0~10分中的7.5~10分
+            mood: Some((4., 5.)),
+            ..Default::default()
+        };
+        let days = diary.search_in_condition(state.condition(MoodScale::OneToFive)).unwrap();
+        assert_eq!(days.iter().map(|day| day.date.day()).collect::<Vec<_>>(), [1]);
+        // 超出刻度的范围不作为条件
+        let state = EntriesState { mood: Some((0., 5.)), ..state };
+        assert_eq!(diary.search_in_condition(state.condition(MoodScale::OneToFive)).unwrap().len(), 2);
+    }
+}
@@ -1,9 +1,11 @@
 use eframe::egui::Color32;
 
+/// 没有记录心情时的表情
+pub const UNKNOWN_FACE: (&str, Color32) = ("(・_・?)", Color32::WHITE);
+
+/// 0~10的心情所对应的表情与颜色
 pub fn mood_to_face(mood: f64) -> (&'static str, Color32) {
     let (face, color) = match mood {
-        v if v == 0.0 => ("(・_・?)", Color32::WHITE), // 未知
-
         0.0..=0.5 => ("(._.)", Color32::DARK_GRAY),
         0.5..=1.0 => ("(-_-)", Color32::GRAY),
 
@@ -0,0 +1,95 @@
+//! 所选日期的习惯打卡清单与新建习惯
+use std::collections::BTreeSet;
+use eframe::egui::{Button, ComboBox, DragValue, RichText, TextEdit, Ui};
+use diary_core::model::{Frequency, Habit};
+use diary_core::t;
+use diary_core::utils::habits::{HabitProgress, DEFAULT_WINDOW};
+
+/// 新建习惯时输入的内容
+#[derive(Default)]
+pub struct HabitsState {
+    adding: bool,
+    name: String,
+    frequency: Frequency,
+}
+
+/// 需要访问数据库的操作，由调用方执行
+pub enum HabitsEvent {
+    /// 当天的打卡有修改
+    Changed,
+    Define(Habit),
+    /// 删除习惯及其全部打卡
+    Remove(String),
+}
+
+/// 绘制习惯清单，勾选即为当天完成
+pub fn habits_ui(ui: &mut Ui, state: &mut HabitsState, progress: &[HabitProgress], done: &mut BTreeSet<String>) -> Option<HabitsEvent> {
+    let mut event = None;
+    for p in progress {
+        let name = &p.habit.name;
+        ui.horizontal(|ui| {
+            let mut checked = done.contains(name);
+            let unit = match p.habit.frequency {
+                Frequency::Daily => t!("gui.common.days"),
+                Frequency::Weekly(_) => t!("gui.habit.weeks"),
+            };
+            let hover = t!("gui.habit.progress", frequency = p.habit.frequency.label(),
+                           current = p.current_streak, longest = p.longest_streak, unit = unit,
+                           rate = format!("{:.0}", p.rate() * 100.), days = DEFAULT_WINDOW);
+            let resp = ui.checkbox(&mut checked, name.as_str()).on_hover_text(hover);
+            if resp.changed() {
+                if checked { done.insert(name.clone()); } else { done.remove(name); }
+                event = Some(HabitsEvent::Changed);
+            }
+            resp.context_menu(|ui| {
+                if ui.button(t!("gui.habit.remove")).clicked() {
+                    event = Some(HabitsEvent::Remove(name.clone()));
+                }
+            });
+            if p.current_streak > 0 {
+                ui.label(RichText::new(format!("🔥{}", p.current_streak)).weak());
+            }
+        });
+    }
+    if !state.adding {
+        if ui.small_button(format!("+ {}", t!("gui.habit.new"))).clicked() {
+            state.adding = true;
+        }
+        return event;
+    }
+    ui.add(TextEdit::singleline(&mut state.name)
+        .hint_text(t!("gui.habit.name"))
+        .desired_width(100.));
+    ui.horizontal(|ui| {
+        let weekly = matches!(state.frequency, Frequency::Weekly(_));
+        ComboBox::from_id_salt("new_habit_frequency")
+            .width(60.)
+            .selected_text(if weekly { t!("gui.habit.weekly") } else { t!("habit.daily") })
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(!weekly, t!("habit.daily")).clicked() {
+                    state.frequency = Frequency::Daily;
+                }
+                if ui.selectable_label(weekly, t!("gui.habit.weekly")).clicked() && !weekly {
+                    state.frequency = Frequency::Weekly(3);
+                }
+            });
+        if let Frequency::Weekly(times) = &mut state.frequency {
+            ui.add(DragValue::new(times).range(1..=7).suffix("×"));
+        }
+    });
+    ui.horizontal(|ui| {
+        let name = state.name.trim();
+        let valid = Habit::new(name, state.frequency).ok()
+            .filter(|_| progress.iter().all(|p| p.habit.name != name));
+        if ui.add_enabled(valid.is_some(), Button::new(t!("gui.habit.add")))
+            .on_disabled_hover_text(t!("gui.habit.name_rule"))
+            .clicked() {
+            event = valid.map(HabitsEvent::Define);
+            *state = HabitsState::default();
+        }
+        if ui.button(t!("gui.common.cancel")).clicked() {
+            *state = HabitsState::default();
+        }
+    });
+    event
+}
@@ -0,0 +1,142 @@
+//! 导入向导：选择格式与重复处理方式，预览读取的记录后再导入
+use std::collections::HashSet;
+use std::path::PathBuf;
+use eframe::egui::{Align2, Button, Color32, Context, Grid, Order, RichText, ScrollArea, Window};
+use diary_core::base::date::Date;
+use diary_core::model::Day;
+use diary_core::utils::io::format::Format;
+use diary_core::utils::io::import::{DuplicateStrategy, ImportSummary};
+use diary_core::utils::template::summary;
+use diary_core::t;
+use crate::service::worker::ImportPreview;
+
+/// 预览中最多列出的记录数
+const PREVIEW_ROWS: usize = 100;
+/// 列表中每条摘要最多展示的字符数
+const SUMMARY_CHARS: usize = 40;
+
+/// 重复处理方式及其名称、说明在消息目录中的键
+const STRATEGIES: [(DuplicateStrategy, &str, &str); 4] = [
+    (DuplicateStrategy::Ignore, "gui.import.skip", "gui.import.skip_hint"),
+    (DuplicateStrategy::Append, "gui.import.merge", "gui.import.merge_hint"),
+    (DuplicateStrategy::Replace, "gui.import.replace", "gui.import.replace_hint"),
+    (DuplicateStrategy::Fail, "gui.import.abort", "gui.import.abort_hint"),
+];
+
+pub struct ImportState {
+    file: PathBuf,
+    format: Format,
+    pub strategy: DuplicateStrategy,
+    /// 数据库中已有记录的日期
+    existing: HashSet<Date>,
+    days: Vec<Day>,
+    errors: Vec<String>,
+}
+
+/// 向导中需要调用方处理的操作
+pub enum ImportAction {
+    Confirm,
+    Cancel,
+    /// 按新选择的格式重新读取文件
+    Reread(PathBuf, Format),
+}
+
+impl ImportState {
+    /// 由后台线程读取的内容创建向导
+    pub fn new(preview: ImportPreview, strategy: DuplicateStrategy) -> Self {
+        let ImportPreview { file, format, days, errors, existing } = preview;
+        Self { file, format, strategy, existing, days, errors }
+    }
+    /// 重新读取文件后更新预览，保留已选择的重复处理方式
+    pub fn set_preview(&mut self, preview: ImportPreview) {
+        *self = Self::new(preview, self.strategy);
+    }
+    fn summary(&self) -> ImportSummary {
+        ImportSummary::plan(&self.days, &self.existing, self.strategy)
+    }
+    /// 确认导入时取出读取到的记录
+    pub fn take_days(&mut self) -> Vec<Day> {
+        std::mem::take(&mut self.days)
+    }
+}
+
+/// 绘制导入向导，返回用户的操作
+pub fn import_ui(ctx: &Context, state: &mut ImportState) -> Option<ImportAction> {
+    let mut result = None;
+    Window::new(t!("gui.import.title"))
+        .resizable(false)
+        .collapsible(false)
+        .order(Order::Foreground)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            let name = state.file.file_name().unwrap_or(state.file.as_os_str()).to_string_lossy();
+            ui.label(RichText::new(name).strong());
+            Grid::new("import_options").num_columns(2).spacing([16., 8.]).show(ui, |ui| {
+                ui.label(t!("gui.import.format"));
+                ui.horizontal(|ui| {
+                    for (format, label) in [(Format::Json, "JSON"), (Format::Csv, "CSV")] {
+                        if ui.selectable_label(state.format == format, label).clicked() && state.format != format {
+                            result = Some(ImportAction::Reread(state.file.clone(), format));
+                        }
+                    }
+                });
+                ui.end_row();
+
+                ui.label(t!("gui.import.existing"));
+                ui.horizontal(|ui| {
+                    for (strategy, label, hint) in STRATEGIES {
+                        ui.selectable_value(&mut state.strategy, strategy, t!(label)).on_hover_text(t!(hint));
+                    }
+                });
+                ui.end_row();
+            });
+            ui.separator();
+
+            let plan = state.summary();
+            ui.label(t!("gui.import.read", count = state.days.len(), plan = plan));
+            let conflict = state.strategy == DuplicateStrategy::Fail && plan.skipped > 0;
+            if conflict {
+                ui.label(RichText::new(t!("gui.import.conflict", count = plan.skipped))
+                    .color(Color32::ORANGE));
+            }
+            ScrollArea::vertical().id_salt("import_preview").max_height(240.).show(ui, |ui| {
+                Grid::new("import_preview_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    for day in state.days.iter().take(PREVIEW_ROWS) {
+                        ui.label(format!("{}", *day.date));
+                        if state.existing.contains(&day.date) {
+                            ui.label(RichText::new(t!("gui.import.exists")).color(Color32::ORANGE));
+                        } else {
+                            ui.label(RichText::new(t!("gui.import.new")).weak());
+                        }
+                        let text = summary(&day.event.instruct);
+                        let mut preview = text.chars().take(SUMMARY_CHARS).collect::<String>();
+                        if preview.len() < text.len() { preview.push('…') }
+                        ui.label(preview);
+                        ui.end_row();
+                    }
+                });
+                if state.days.len() > PREVIEW_ROWS {
+                    ui.label(RichText::new(t!("gui.import.more", count = state.days.len() - PREVIEW_ROWS)).weak());
+                }
+            });
+            if !state.errors.is_empty() {
+                ui.collapsing(RichText::new(t!("gui.import.errors", count = state.errors.len())).color(Color32::RED), |ui| {
+                    ScrollArea::vertical().id_salt("import_errors").max_height(120.).show(ui, |ui| {
+                        for error in &state.errors {
+                            ui.label(RichText::new(error).small());
+                        }
+                    });
+                });
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!state.days.is_empty() && !conflict, Button::new(t!("gui.import.confirm"))).clicked() {
+                    result = Some(ImportAction::Confirm);
+                }
+                if ui.button(t!("gui.common.cancel")).clicked() {
+                    result = Some(ImportAction::Cancel);
+                }
+            });
+        });
+    result
+}
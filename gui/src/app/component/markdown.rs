@@ -0,0 +1,248 @@
+//! 日记内容的Markdown预览
+use std::ops::Range;
+use eframe::egui::{Checkbox, Color32, CornerRadius, Frame, RichText, Stroke, TextStyle, Ui};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+/// 每级列表或引用的缩进
+const INDENT: f32 = 16.;
+
+/// 一段连续的、样式相同的行内文本
+#[derive(Default, Clone)]
+struct Inline {
+    text: String,
+    strong: bool,
+    italic: bool,
+    strike: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+/// 列表项前的标记
+enum Marker {
+    Bullet,
+    Number(u64),
+    /// 任务列表的勾选框，保存勾选状态及`[ ]`在原文中的位置
+    Task(bool, Range<usize>),
+}
+
+enum Block {
+    Heading(HeadingLevel, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    Item(Marker, Vec<Inline>),
+    Code(String),
+    Rule,
+}
+
+/// 带缩进的块
+struct Line {
+    /// 列表嵌套层数
+    depth: usize,
+    /// 引用嵌套层数
+    quote: usize,
+    block: Block,
+}
+
+/// 把已收集的行内文本输出为一个块
+fn flush(lines: &mut Vec<Line>, inlines: &mut Vec<Inline>, marker: &mut Option<Marker>, heading: Option<HeadingLevel>, depth: usize, quote: usize) {
+    if inlines.is_empty() && marker.is_none() { return }
+    let inlines = std::mem::take(inlines);
+    let block = match (heading, marker.take()) {
+        (Some(level), _) => Block::Heading(level, inlines),
+        (None, Some(m)) => Block::Item(m, inlines),
+        (None, None) => Block::Paragraph(inlines),
+    };
+    lines.push(Line { depth, quote, block });
+}
+
+/// 把Markdown文本拆分为逐行展示的块
+fn parse(text: &str) -> Vec<Line> {
+    let options = Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+    let mut lines = Vec::new();
+    let mut inlines: Vec<Inline> = Vec::new();
+    let mut style = Inline::default();
+    // 各层列表的下一个序号，`None`为无序列表
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut marker: Option<Marker> = None;
+    let mut quote = 0;
+    let mut heading = None;
+    let mut code: Option<String> = None;
+
+    for (event, range) in Parser::new_ext(text, options).into_offset_iter() {
+        let depth = lists.len().saturating_sub(1);
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => heading = Some(level),
+            Event::End(TagEnd::Heading(_)) => {
+                flush(&mut lines, &mut inlines, &mut marker, heading, 0, quote);
+                heading = None;
+            }
+            Event::Start(Tag::BlockQuote(_)) => quote += 1,
+            Event::End(TagEnd::BlockQuote(_)) => quote -= 1,
+            Event::Start(Tag::List(start)) => {
+                // 嵌套列表开始前，先输出外层列表项的内容
+                flush(&mut lines, &mut inlines, &mut marker, None, depth, quote);
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => { lists.pop(); }
+            Event::Start(Tag::Item) => {
+                marker = Some(match lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        Marker::Number(*n - 1)
+                    }
+                    _ => Marker::Bullet,
+                });
+            }
+            Event::TaskListMarker(checked) => marker = Some(Marker::Task(checked, range)),
+            Event::End(TagEnd::Item) | Event::End(TagEnd::Paragraph) => {
+                flush(&mut lines, &mut inlines, &mut marker, None, depth, quote);
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                flush(&mut lines, &mut inlines, &mut marker, None, depth, quote);
+                code = Some(String::new());
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                let text = code.take().unwrap_or_default();
+                lines.push(Line { depth, quote, block: Block::Code(text.trim_end().to_string()) });
+            }
+            Event::Start(Tag::Strong) => style.strong = true,
+            Event::End(TagEnd::Strong) => style.strong = false,
+            Event::Start(Tag::Emphasis) => style.italic = true,
+            Event::End(TagEnd::Emphasis) => style.italic = false,
+            Event::Start(Tag::Strikethrough) => style.strike = true,
+            Event::End(TagEnd::Strikethrough) => style.strike = false,
+            Event::Start(Tag::Link { dest_url, .. }) => style.link = Some(dest_url.to_string()),
+            Event::End(TagEnd::Link) => style.link = None,
+            Event::Text(t) => match code.as_mut() {
+                Some(code) => code.push_str(&t),
+                None => inlines.push(Inline { text: t.to_string(), ..style.clone() }),
+            },
+            Event::Code(t) => inlines.push(Inline { text: t.to_string(), code: true, ..style.clone() }),
+            Event::SoftBreak => inlines.push(Inline { text: " ".to_string(), ..style.clone() }),
+            Event::HardBreak => inlines.push(Inline { text: "\n".to_string(), ..style.clone() }),
+            Event::Rule => lines.push(Line { depth: 0, quote, block: Block::Rule }),
+            Event::Html(t) | Event::InlineHtml(t) => inlines.push(Inline { text: t.to_string(), ..style.clone() }),
+            _ => {}
+        }
+    }
+    flush(&mut lines, &mut inlines, &mut marker, heading, 0, quote);
+    lines
+}
+
+fn inline_ui(ui: &mut Ui, inlines: &[Inline], size: Option<f32>) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.;
+        for inline in inlines {
+            let mut text = RichText::new(&inline.text);
+            if let Some(size) = size { text = text.size(size).strong() }
+            if inline.strong { text = text.strong() }
+            if inline.italic { text = text.italics() }
+            if inline.strike { text = text.strikethrough() }
+            if inline.code { text = text.code() }
+            match &inline.link {
+                Some(url) => { ui.hyperlink_to(text, url); }
+                None => { ui.label(text); }
+            }
+        }
+    });
+}
+
+/// 渲染Markdown，勾选任务列表时直接修改原文，原文被修改时返回`true`
+pub fn markdown_ui(ui: &mut Ui, text: &mut String) -> bool {
+    let body = ui.text_style_height(&TextStyle::Body);
+    let mut toggled = None;
+    for line in parse(text) {
+        ui.horizontal(|ui| {
+            if line.quote > 0 {
+                // 引用以左侧竖线标出
+                let (rect, _) = ui.allocate_exact_size([INDENT * line.quote as f32, body].into(), eframe::egui::Sense::hover());
+                ui.painter().vline(rect.left() + 2., rect.y_range(), Stroke::new(3., Color32::GRAY));
+            }
+            ui.add_space(INDENT * line.depth as f32);
+            ui.vertical(|ui| match line.block {
+                Block::Heading(level, inlines) => {
+                    let scale = match level {
+                        HeadingLevel::H1 => 1.8,
+                        HeadingLevel::H2 => 1.5,
+                        HeadingLevel::H3 => 1.25,
+                        _ => 1.1,
+                    };
+                    inline_ui(ui, &inlines, Some(body * scale));
+                }
+                Block::Paragraph(inlines) => inline_ui(ui, &inlines, None),
+                Block::Item(marker, inlines) => {
+                    ui.horizontal(|ui| {
+                        match marker {
+                            Marker::Bullet => { ui.label("•"); }
+                            Marker::Number(n) => { ui.label(format!("{}.", n)); }
+                            Marker::Task(checked, range) => {
+                                let mut value = checked;
+                                if ui.add(Checkbox::without_text(&mut value)).changed() {
+                                    toggled = Some((value, range));
+                                }
+                            }
+                        }
+                        inline_ui(ui, &inlines, None);
+                    });
+                }
+                Block::Code(code) => {
+                    Frame::new()
+                        .fill(ui.visuals().code_bg_color)
+                        .corner_radius(CornerRadius::same(4))
+                        .inner_margin(6.)
+                        .show(ui, |ui| ui.label(RichText::new(code).monospace()));
+                }
+                Block::Rule => { ui.separator(); }
+            });
+        });
+    }
+    match toggled {
+        Some((checked, range)) => {
+            text.replace_range(range, if checked { "[x]" } else { "[ ]" });
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(inlines: &[Inline]) -> String {
+        inlines.iter().map(|i| i.text.as_str()).collect()
+    }
+
+    #[test]
+    fn blocks_keep_nesting_and_numbering() {
+        let lines = parse("# 标题\n\n3. 一\n4. 二\n   - 子项\n\n> 引用\n\n```\ncode\n```\n");
+        assert!(matches!(&lines[0].block, Block::Heading(HeadingLevel::H1, i) if text(i) == "标题"));
+        assert!(matches!(&lines[1].block, Block::Item(Marker::Number(3), i) if text(i) == "一"));
+        assert!(matches!(&lines[2].block, Block::Item(Marker::Number(4), i) if text(i) == "二"));
+        assert!(matches!(&lines[3].block, Block::Item(Marker::Bullet, _)));
+        assert_eq!(lines[3].depth, 1);
+        assert!(matches!(&lines[4].block, Block::Paragraph(i) if text(i) == "引用"));
+        assert_eq!(lines[4].quote, 1);
+        assert!(matches!(&lines[5].block, Block::Code(c) if c == "code"));
+    }
+
+    #[test]
+    fn inline_styles() {
+        let lines = parse("**粗** *斜* ~~删~~ `码` [链接](https://example.com)");
+        let Block::Paragraph(inlines) = &lines[0].block else { panic!() };
+        assert!(inlines[0].strong && inlines[0].text == "粗");
+        assert!(inlines[2].italic);
+        assert!(inlines[4].strike);
+        assert!(inlines[6].code);
+        assert_eq!(inlines[8].link.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn task_marker_points_at_the_checkbox() {
+        let source = "- [ ] 待办\n- [x] 完成";
+        let lines = parse(source);
+        let Block::Item(Marker::Task(false, range), _) = &lines[0].block else { panic!() };
+        assert_eq!(&source[range.clone()], "[ ]");
+        let Block::Item(Marker::Task(true, range), _) = &lines[1].block else { panic!() };
+        assert_eq!(&source[range.clone()], "[x]");
+    }
+}
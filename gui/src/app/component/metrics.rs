@@ -0,0 +1,131 @@
+//! 自定义指标的输入与定义
+use std::collections::BTreeMap;
+use eframe::egui::{Button, ComboBox, DragValue, RichText, TextEdit, Ui};
+use diary_core::model::{MetricDef, MetricKind, MetricValue};
+use diary_core::t;
+
+/// 新建指标时输入的内容
+#[derive(Default)]
+pub struct MetricsState {
+    adding: bool,
+    name: String,
+    kind: MetricKind,
+    unit: String,
+}
+
+/// 需要访问数据库的操作，由调用方执行
+pub enum MetricsEvent {
+    /// 当天的取值有修改
+    Changed,
+    Define(MetricDef),
+    /// 删除指标及其全部取值
+    Remove(String),
+}
+
+/// 绘制已定义指标的输入及新建指标的表单
+pub fn metrics_ui(ui: &mut Ui, state: &mut MetricsState, defs: &[MetricDef], values: &mut BTreeMap<String, MetricValue>) -> Option<MetricsEvent> {
+    let mut event = None;
+    for def in defs {
+        ui.horizontal(|ui| {
+            let label = ui.label(&def.name);
+            label.context_menu(|ui| {
+                if ui.button(t!("gui.metric.remove")).clicked() {
+                    event = Some(MetricsEvent::Remove(def.name.clone()));
+                }
+            });
+            if value_ui(ui, def, values) {
+                event = Some(MetricsEvent::Changed);
+            }
+        });
+    }
+    if !state.adding {
+        if ui.small_button(format!("+ {}", t!("gui.metric.new"))).clicked() {
+            state.adding = true;
+        }
+        return event;
+    }
+    ui.add(TextEdit::singleline(&mut state.name)
+        .hint_text(t!("gui.metric.name"))
+        .desired_width(100.));
+    ui.horizontal(|ui| {
+        ComboBox::from_id_salt("new_metric_kind")
+            .width(60.)
+            .selected_text(state.kind.label())
+            .show_ui(ui, |ui| {
+                for kind in MetricKind::ALL {
+                    ui.selectable_value(&mut state.kind, kind, kind.label());
+                }
+            });
+        if state.kind == MetricKind::Number {
+            ui.add(TextEdit::singleline(&mut state.unit)
+                .hint_text(t!("gui.metric.unit"))
+                .desired_width(30.));
+        }
+    });
+    ui.horizontal(|ui| {
+        let name = state.name.trim();
+        let valid = MetricDef::new(name, state.kind, &state.unit).ok()
+            .filter(|_| defs.iter().all(|d| d.name != name));
+        if ui.add_enabled(valid.is_some(), Button::new(t!("gui.metric.add")))
+            .on_disabled_hover_text(t!("gui.metric.name_rule"))
+            .clicked() {
+            event = valid.map(MetricsEvent::Define);
+            *state = MetricsState::default();
+        }
+        if ui.button(t!("gui.common.cancel")).clicked() {
+            *state = MetricsState::default();
+        }
+    });
+    event
+}
+
+/// 按类型绘制某个指标的取值，有修改时返回`true`
+fn value_ui(ui: &mut Ui, def: &MetricDef, values: &mut BTreeMap<String, MetricValue>) -> bool {
+    let mut changed = false;
+    match def.kind {
+        MetricKind::Number => match values.get_mut(&def.name) {
+            Some(MetricValue::Number(v)) => {
+                changed |= ui.add(DragValue::new(v).speed(0.1).max_decimals(2).suffix(unit_suffix(def))).changed();
+            }
+            _ => if ui.small_button(t!("gui.metric.set")).clicked() {
+                values.insert(def.name.clone(), MetricValue::Number(0.));
+                changed = true;
+            },
+        },
+        MetricKind::Bool => {
+            let mut checked = matches!(values.get(&def.name), Some(MetricValue::Bool(true)));
+            if ui.checkbox(&mut checked, "").changed() {
+                values.insert(def.name.clone(), MetricValue::Bool(checked));
+                changed = true;
+            }
+            if let Some(v) = values.get(&def.name) {
+                ui.label(RichText::new(v.describe()).weak());
+            }
+        }
+        MetricKind::Text => {
+            let mut text = match values.get(&def.name) {
+                Some(v) => v.to_string(),
+                None => String::new(),
+            };
+            if ui.add(TextEdit::singleline(&mut text).desired_width(80.)).changed() {
+                if text.trim().is_empty() {
+                    values.remove(&def.name);
+                } else {
+                    values.insert(def.name.clone(), MetricValue::Text(text));
+                }
+                changed = true;
+            }
+        }
+    }
+    // 文字清空即为清除，不需要按钮
+    if def.kind != MetricKind::Text && values.contains_key(&def.name)
+        && ui.small_button("✖").on_hover_text(t!("gui.metric.clear")).clicked() {
+        values.remove(&def.name);
+        changed = true;
+    }
+    changed
+}
+
+fn unit_suffix(def: &MetricDef) -> String {
+    if def.unit.is_empty() { String::new() } else { format!(" {}", def.unit) }
+}
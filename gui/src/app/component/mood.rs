@@ -0,0 +1,62 @@
+//! 按配置的刻度输入心情，可以明确地不记录心情
+use eframe::egui::{DragValue, RichText, Ui};
+use diary_core::model::{Mood, MoodScale};
+use diary_core::t;
+use crate::app::component::face::{mood_to_face, UNKNOWN_FACE};
+
+/// 绘制心情输入，有修改时返回`true`
+pub fn mood_ui(ui: &mut Ui, mood: &mut Option<Mood>, scale: MoodScale) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        match scale {
+            MoodScale::Emoji => {
+                ui.spacing_mut().item_spacing.x = 2.;
+                for (i, emoji) in MoodScale::EMOJI.iter().enumerate() {
+                    let value = scale.from_scale(i as f64 + 1.).ok();
+                    let selected = mood.is_some_and(|m| scale.format(m) == *emoji);
+                    let resp = ui.selectable_label(selected, RichText::new(*emoji).size(16.))
+                        .on_hover_text(value.map(|m| m.label()).unwrap_or_default());
+                    // 再次点击已选中的表情时取消
+                    if resp.clicked() {
+                        *mood = if selected { None } else { value };
+                        changed = true;
+                    }
+                }
+            }
+            _ => match mood {
+                Some(m) => {
+                    let (min, max) = scale.range();
+                    let mut value = scale.to_scale(*m);
+                    let input = DragValue::new(&mut value)
+                        .range(min..=max)
+                        .speed(scale.step() / 20.)
+                        .max_decimals(1)
+                        .prefix(format!("{}: ", t!("gui.side.mood")));
+                    if ui.add(input).changed() && let Ok(new) = scale.from_scale(value) {
+                        *m = new;
+                        changed = true;
+                    }
+                    if ui.small_button("✖").on_hover_text(t!("gui.mood.clear")).clicked() {
+                        *mood = None;
+                        changed = true;
+                    }
+                }
+                None => {
+                    if ui.button(t!("gui.mood.set")).clicked() {
+                        // 从中间值开始
+                        *mood = Mood::new((Mood::MIN + Mood::MAX) / 2.).ok();
+                        changed = true;
+                    }
+                }
+            },
+        }
+    });
+    let (face, color) = mood.map_or(UNKNOWN_FACE, |m| mood_to_face(m.value()));
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(face).color(color));
+        if let Some(m) = mood {
+            ui.label(RichText::new(m.label()).weak());
+        }
+    });
+    changed
+}
@@ -0,0 +1,100 @@
+//! “那年今日”侧栏
+use chrono::NaiveDate;
+use eframe::egui::{ComboBox, Label, RichText, ScrollArea, Ui};
+use diary_core::base::error::Error;
+use diary_core::base::i18n::weekday_name;
+use diary_core::model::Day;
+use diary_core::t;
+use diary_core::utils::onthisday::Span;
+use crate::app::component::face::mood_to_face;
+use crate::model::date::Date;
+use crate::service::executor::{Pending, Query};
+
+/// 侧栏中每条记录最多展示的字符数
+const PREVIEW_CHARS: usize = 200;
+
+/// 侧栏的状态及已加载的数据
+#[derive(Default)]
+pub struct OnThisDayState {
+    pub span: Span,
+    /// 记录及其相隔的年数
+    pub days: Vec<(i32, Day)>,
+    /// 按(日期, 范围)读取的数据
+    load: Query<(NaiveDate, Span), Vec<(i32, Day)>>,
+}
+impl OnThisDayState {
+    pub fn needs_reload(&self, date: NaiveDate) -> bool {
+        self.load.needs_load(&(date, self.span))
+    }
+    pub fn start_load(&mut self, date: NaiveDate, pending: Pending<Vec<(i32, Day)>>) {
+        self.load.start((date, self.span), pending);
+    }
+    /// 取回查询结果，出错时清空记录并返回错误
+    pub fn poll(&mut self) -> Option<Error> {
+        match self.load.poll()? {
+            Ok(days) => self.days = days,
+            Err(e) => {
+                self.days.clear();
+                return Some(e);
+            }
+        }
+        None
+    }
+    /// 数据库有变动时调用，下次展示时重新加载
+    pub fn invalidate(&mut self) {
+        self.load.invalidate();
+    }
+}
+
+fn span_label(span: Span) -> &'static str {
+    match span {
+        Span::Day => t!("gui.on_this_day.day"),
+        Span::Week => t!("gui.on_this_day.week"),
+        Span::Month => t!("gui.on_this_day.month"),
+    }
+}
+
+/// 绘制侧栏，返回用户点击要跳转到的日期
+pub fn on_this_day_ui(ui: &mut Ui, state: &mut OnThisDayState) -> Option<NaiveDate> {
+    let mut jump = None;
+    ui.heading(t!("gui.menu.on_this_day"));
+    ComboBox::from_id_salt("on_this_day_span")
+        .selected_text(span_label(state.span))
+        .show_ui(ui, |ui| {
+            for span in Span::ALL {
+                ui.selectable_value(&mut state.span, span, span_label(span));
+            }
+        });
+    ui.separator();
+    if state.days.is_empty() {
+        ui.label(RichText::new(t!("gui.on_this_day.nothing")).weak());
+        return None;
+    }
+    ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+        for (n, day) in &state.days {
+            let n = *n;
+            ui.horizontal(|ui| {
+                let title = if n == 1 { t!("gui.on_this_day.year_one").to_string() } else { t!("gui.on_this_day.year_other", n = n) };
+                if ui.link(RichText::new(title).strong()).clicked() {
+                    jump = Some(Date::from(day.date).into());
+                }
+                ui.label(RichText::new(format!("{} {}", *day.date, weekday_name(day.date.weekday()))).weak());
+            });
+            ui.horizontal(|ui| {
+                if let Some(mood) = day.mood {
+                    let (face, color) = mood_to_face(mood.value());
+                    ui.label(RichText::new(face).color(color));
+                }
+                if let Some(weather) = &day.weather {
+                    ui.label(weather.describe());
+                }
+            });
+            let text = &day.event.instruct;
+            let mut preview = text.chars().take(PREVIEW_CHARS).collect::<String>();
+            if preview.len() < text.len() { preview.push('…') }
+            ui.add(Label::new(preview).wrap());
+            ui.separator();
+        }
+    });
+    jump
+}
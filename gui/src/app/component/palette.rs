@@ -0,0 +1,106 @@
+//! 命令面板：按名称查找并执行任意操作
+use eframe::egui::{Align, Align2, Context, Id, Key, Layout, Modifiers, RichText, ScrollArea, TextEdit, Window};
+use diary_core::t;
+use crate::app::action::{Action, Shortcuts};
+
+#[derive(Default)]
+pub struct PaletteState {
+    pub open: bool,
+    query: String,
+    selected: usize,
+}
+impl PaletteState {
+    pub fn show(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+}
+
+/// 名称包含查询中全部词语的操作
+fn matches(query: &str) -> Vec<Action> {
+    let query = query.to_lowercase();
+    Action::ALL.into_iter()
+        .filter(|a| *a != Action::CommandPalette)
+        .filter(|a| {
+            let label = a.label().to_lowercase();
+            query.split_whitespace().all(|w| label.contains(w) || a.name().contains(w))
+        })
+        .collect()
+}
+
+/// 绘制命令面板，返回用户选中的操作
+pub fn palette_ui(ctx: &Context, state: &mut PaletteState, shortcuts: &Shortcuts) -> Option<Action> {
+    if !state.open { return None }
+    let actions = matches(&state.query);
+    let (up, down, enter, escape) = ctx.input_mut(|i| (
+        i.consume_key(Modifiers::NONE, Key::ArrowUp),
+        i.consume_key(Modifiers::NONE, Key::ArrowDown),
+        i.consume_key(Modifiers::NONE, Key::Enter),
+        i.consume_key(Modifiers::NONE, Key::Escape),
+    ));
+    if escape {
+        state.open = false;
+        return None;
+    }
+    if down && state.selected + 1 < actions.len() { state.selected += 1 }
+    if up { state.selected = state.selected.saturating_sub(1) }
+    state.selected = state.selected.min(actions.len().saturating_sub(1));
+    let mut chosen = enter.then(|| actions.get(state.selected).copied()).flatten();
+
+    let mut open = true;
+    Window::new("Command Palette")
+        .open(&mut open)
+        .title_bar(false)
+        .resizable(false)
+        .collapsible(false)
+        .anchor(Align2::CENTER_TOP, [0., 60.])
+        .fixed_size([360., 0.])
+        .show(ctx, |ui| {
+            let id = Id::new("command_palette_query");
+            let resp = ui.add(TextEdit::singleline(&mut state.query)
+                .id(id)
+                .hint_text(t!("gui.palette.hint"))
+                .desired_width(f32::INFINITY));
+            resp.request_focus();
+            if resp.changed() { state.selected = 0 }
+            ui.separator();
+            if actions.is_empty() {
+                ui.label(RichText::new(t!("gui.palette.nothing")).weak());
+            }
+            ScrollArea::vertical().max_height(320.).show(ui, |ui| {
+                for (i, action) in actions.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let item = ui.selectable_label(i == state.selected, action.label());
+                        if i == state.selected && (up || down) {
+                            item.scroll_to_me(None);
+                        }
+                        if item.clicked() {
+                            chosen = Some(*action);
+                        }
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            ui.label(RichText::new(shortcuts.text(ctx, *action)).weak());
+                        });
+                    });
+                }
+            });
+        });
+    if chosen.is_some() || !open {
+        state.open = false;
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_word_must_match() {
+        assert_eq!(matches("move-day"), [Action::MoveDay]);
+        assert!(matches("day").contains(&Action::DeleteDay));
+        assert!(matches("day nothing").is_empty());
+        // 命令面板本身不在列表中
+        assert_eq!(matches("").len(), Action::ALL.len() - 1);
+    }
+}
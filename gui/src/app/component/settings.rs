@@ -0,0 +1,166 @@
+//! 设置窗口，以及把外观设置应用到界面
+use std::sync::Arc;
+use eframe::egui::{ComboBox, Context, DragValue, FontData, FontDefinitions, FontFamily as EguiFamily, FontId, Grid, Slider, TextStyle, ThemePreference, Ui};
+use time::Weekday;
+use diary_core::base::config::{Config, FontConfig, FontFamily, Theme, WeekStart, MAX_AUTOSAVE};
+use diary_core::base::i18n::{weekday_name, Locale};
+use diary_core::model::MoodScale;
+use diary_core::t;
+
+const WENKAI: &str = "LXGW WenKai";
+const SMILEY_SANS: &str = "Smiley Sans";
+
+/// 各种文字相对正文字号的比例
+const TEXT_SCALES: [(TextStyle, f32); 5] = [
+    (TextStyle::Small, 0.72),
+    (TextStyle::Body, 1.),
+    (TextStyle::Button, 1.),
+    (TextStyle::Monospace, 1.),
+    (TextStyle::Heading, 1.45),
+];
+
+fn font_label(family: FontFamily) -> &'static str {
+    match family {
+        FontFamily::WenKai => WENKAI,
+        FontFamily::SmileySans => SMILEY_SANS,
+    }
+}
+
+/// 设置窗口中被修改的部分
+#[derive(Default)]
+pub struct SettingsChanged {
+    /// 主题或字体，需要重新应用外观
+    pub appearance: bool,
+    /// 界面语言，需要重新设置当前语言
+    pub language: bool,
+}
+
+/// 应用主题与字体设置
+pub fn apply_appearance(ctx: &Context, config: &Config) {
+    ctx.set_theme(match config.theme {
+        Theme::Light => ThemePreference::Light,
+        Theme::Dark => ThemePreference::Dark,
+        Theme::System => ThemePreference::System,
+    });
+
+    let mut fd = FontDefinitions::default();
+    fd.font_data.insert(
+        WENKAI.to_string(),
+        Arc::new(FontData::from_static(include_bytes!("../../../assets/LXGWWenKaiMonoLite-Regular.ttf"))),
+    );
+    fd.font_data.insert(
+        SMILEY_SANS.to_string(),
+        Arc::new(FontData::from_static(include_bytes!("../../../assets/SmileySans-Oblique.ttf"))),
+    );
+    // 选中的字体在前，另一个作为缺字时的后备
+    let order = match config.font.family {
+        FontFamily::WenKai => [WENKAI, SMILEY_SANS],
+        FontFamily::SmileySans => [SMILEY_SANS, WENKAI],
+    };
+    for family in [EguiFamily::Proportional, EguiFamily::Monospace] {
+        let fonts = fd.families.entry(family).or_default();
+        for (i, name) in order.iter().enumerate() {
+            fonts.insert(i, name.to_string());
+        }
+    }
+    ctx.set_fonts(fd);
+
+    ctx.all_styles_mut(|style| {
+        for (text_style, scale) in TEXT_SCALES {
+            let family = match text_style {
+                TextStyle::Monospace => EguiFamily::Monospace,
+                _ => EguiFamily::Proportional,
+            };
+            style.text_styles.insert(text_style, FontId::new(config.font.size * scale, family));
+        }
+    });
+}
+
+/// 设置窗口的主体内容，直接修改`config`
+pub fn settings_ui(ui: &mut Ui, config: &mut Config) -> SettingsChanged {
+    let mut changed = SettingsChanged::default();
+    Grid::new("settings_grid").num_columns(2).spacing([16., 8.]).show(ui, |ui| {
+        ui.label(t!("gui.settings.theme"));
+        ui.horizontal(|ui| {
+            for (theme, label) in [(Theme::Light, "gui.settings.light"), (Theme::Dark, "gui.settings.dark"), (Theme::System, "gui.settings.system")] {
+                changed.appearance |= ui.selectable_value(&mut config.theme, theme, t!(label)).changed();
+            }
+        });
+        ui.end_row();
+
+        ui.label(t!("gui.settings.language"));
+        ComboBox::from_id_salt("settings_language")
+            .selected_text(config.language.map_or(t!("gui.settings.system"), |l| l.native_name()))
+            .show_ui(ui, |ui| {
+                changed.language |= ui.selectable_value(&mut config.language, None, t!("gui.settings.system")).changed();
+                for locale in Locale::ALL {
+                    changed.language |= ui.selectable_value(&mut config.language, Some(locale), locale.native_name()).changed();
+                }
+            });
+        ui.end_row();
+
+        ui.label(t!("gui.settings.font"));
+        ComboBox::from_id_salt("settings_font")
+            .selected_text(font_label(config.font.family))
+            .show_ui(ui, |ui| {
+                for family in [FontFamily::WenKai, FontFamily::SmileySans] {
+                    changed.appearance |= ui.selectable_value(&mut config.font.family, family, font_label(family)).changed();
+                }
+            });
+        ui.end_row();
+
+        ui.label(t!("gui.settings.font_size"));
+        let size = Slider::new(&mut config.font.size, FontConfig::MIN_SIZE..=FontConfig::MAX_SIZE).step_by(0.5);
+        changed.appearance |= ui.add(size).changed();
+        ui.end_row();
+
+        ui.label(t!("gui.settings.database"));
+        ui.vertical(|ui| {
+            match &config.database {
+                Some(path) => ui.label(path.display().to_string()),
+                None => ui.label(t!("gui.settings.default_notebook")),
+            };
+            ui.horizontal(|ui| {
+                if ui.button(t!("gui.settings.browse")).clicked()
+                    && let Some(file) = rfd::FileDialog::new()
+                        .add_filter(t!("gui.file.database"), &["db"])
+                        .save_file() {
+                    config.database = Some(file);
+                }
+                if config.database.is_some() && ui.button(t!("gui.settings.use_default")).clicked() {
+                    config.database = None;
+                }
+            });
+        });
+        ui.end_row();
+
+        ui.label(t!("gui.settings.autosave"));
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut config.autosave).range(0..=MAX_AUTOSAVE).suffix(" s"));
+            if config.autosave == 0 {
+                ui.weak(t!("gui.settings.off"));
+            }
+        });
+        ui.end_row();
+
+        ui.label(t!("gui.settings.mood_scale"));
+        ui.horizontal(|ui| {
+            for scale in MoodScale::ALL {
+                let label = match scale {
+                    MoodScale::Emoji => format!("{} {}", MoodScale::EMOJI[3], t!("gui.settings.emoji")),
+                    _ => scale.code().to_string(),
+                };
+                ui.selectable_value(&mut config.mood, scale, label);
+            }
+        });
+        ui.end_row();
+
+        ui.label(t!("gui.settings.week_start"));
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut config.week_start, WeekStart::Monday, weekday_name(Weekday::Monday));
+            ui.selectable_value(&mut config.week_start, WeekStart::Sunday, weekday_name(Weekday::Sunday));
+        });
+        ui.end_row();
+    });
+    changed
+}
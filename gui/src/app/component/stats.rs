@@ -0,0 +1,106 @@
+use eframe::egui::{Grid, RichText, ScrollArea, Ui};
+use diary_core::base::i18n::{tr, weekday_name};
+use diary_core::t;
+use diary_core::utils::stats::{MetricSummary, MoodSummary, PeriodSummary, Statistics};
+use crate::app::component::face::mood_to_face;
+
+/// 统计窗口的主体内容
+pub fn statistics_ui(ui: &mut Ui, stats: &Statistics) {
+    Grid::new("stats_overview").num_columns(2).striped(true).show(ui, |ui| {
+        ui.label(t!("gui.charts.entries"));
+        ui.label(stats.entries.to_string());
+        ui.end_row();
+        ui.label(format!("{} / {}", t!("gui.stats.words"), t!("gui.stats.chars")));
+        ui.label(format!("{} / {}", stats.words, stats.chars));
+        ui.end_row();
+        ui.label(t!("gui.stats.current_streak"));
+        ui.label(format!("{} {}", stats.current_streak, t!("gui.common.days")));
+        ui.end_row();
+        ui.label(t!("gui.stats.longest_streak"));
+        ui.label(format!("{} {}", stats.longest_streak, t!("gui.common.days")));
+        ui.end_row();
+        ui.label(t!("gui.side.mood"));
+        mood_label(ui, stats.mood.as_ref());
+        ui.end_row();
+    });
+    if stats.entries == 0 { return }
+
+    ScrollArea::vertical().max_height(360.).show(ui, |ui| {
+        ui.collapsing(t!("gui.stats.monthly"), |ui| {
+            period_grid(ui, "stats_monthly", &stats.monthly,
+                        |p| format!("{}-{:02}", p.start.year(), p.start.month() as u8));
+        });
+        ui.collapsing(t!("gui.stats.weekly"), |ui| {
+            period_grid(ui, "stats_weekly", &stats.weekly, |p| format!("{}", *p.start));
+        });
+        ui.collapsing(t!("gui.stats.mood_by_weekday"), |ui| {
+            Grid::new("stats_weekday").num_columns(2).striped(true).show(ui, |ui| {
+                for (wd, mood) in &stats.mood_by_weekday {
+                    ui.label(weekday_name(*wd));
+                    mood_label(ui, mood.as_ref());
+                    ui.end_row();
+                }
+            });
+        });
+        ui.collapsing(t!("gui.side.weather"), |ui| {
+            Grid::new("stats_weather").num_columns(2).striped(true).show(ui, |ui| {
+                for (c, n) in &stats.weather {
+                    ui.label(format!("{} {}", c.icon(), c.label()));
+                    ui.label(n.to_string());
+                    ui.end_row();
+                }
+            });
+        });
+        if !stats.metrics.is_empty() {
+            ui.collapsing(t!("gui.side.metrics"), |ui| {
+                Grid::new("stats_metrics").num_columns(2).striped(true).show(ui, |ui| {
+                    for (name, summary) in &stats.metrics {
+                        ui.label(name);
+                        metric_label(ui, summary);
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+    });
+}
+
+fn period_grid(ui: &mut Ui, id: &str, periods: &[PeriodSummary], label: impl Fn(&PeriodSummary) -> String) {
+    Grid::new(id).num_columns(5).striped(true).show(ui, |ui| {
+        for head in ["gui.stats.period", "gui.charts.entries", "gui.stats.words", "gui.stats.chars", "gui.side.mood"].map(tr) {
+            ui.label(RichText::new(head).strong());
+        }
+        ui.end_row();
+        // 最近的周期排在最前
+        for p in periods.iter().rev() {
+            ui.label(label(p));
+            ui.label(p.entries.to_string());
+            ui.label(p.words.to_string());
+            ui.label(p.chars.to_string());
+            mood_label(ui, p.mood.as_ref());
+            ui.end_row();
+        }
+    });
+}
+
+fn metric_label(ui: &mut Ui, summary: &MetricSummary) {
+    let text = match summary {
+        MetricSummary::Number(m) => format!("{:.1} ({:.1}~{:.1}), {}", m.average, m.min, m.max,
+                                            t!("gui.stats.recorded", n = m.count)),
+        MetricSummary::Bool { yes, count } => t!("gui.stats.yes_rate", yes = yes, n = count,
+                                                 rate = format!("{:.0}", *yes as f64 / *count as f64 * 100.)),
+        MetricSummary::Text { count } => t!("gui.stats.recorded", n = count),
+    };
+    ui.label(text);
+}
+
+fn mood_label(ui: &mut Ui, mood: Option<&MoodSummary>) {
+    match mood {
+        Some(m) => {
+            let (face, color) = mood_to_face(m.average);
+            ui.label(RichText::new(format!("{:.1} ({:.1}~{:.1}) {}", m.average, m.min, m.max, face))
+                .color(color));
+        }
+        None => { ui.label("-"); }
+    }
+}
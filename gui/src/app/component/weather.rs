@@ -0,0 +1,39 @@
+//! 天气的预设状况、气温与备注输入
+use eframe::egui::{DragValue, RichText, TextEdit, Ui};
+use diary_core::model::{Condition, Weather};
+use diary_core::t;
+
+/// 绘制天气输入，有修改时返回`true`
+pub fn weather_ui(ui: &mut Ui, weather: &mut Weather) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 2.;
+        for condition in Condition::ALL {
+            let selected = weather.condition == Some(condition);
+            let resp = ui.selectable_label(selected, RichText::new(condition.icon()).size(16.))
+                .on_hover_text(condition.label());
+            // 再次点击已选中的状况时取消
+            if resp.clicked() {
+                weather.condition = (!selected).then_some(condition);
+                changed = true;
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        if let Some(condition) = weather.condition {
+            ui.label(RichText::new(condition.label()).weak());
+        }
+        let mut has_temperature = weather.temperature.is_some();
+        if ui.checkbox(&mut has_temperature, "").on_hover_text(t!("gui.weather.temperature")).changed() {
+            weather.temperature = has_temperature.then_some(20.);
+            changed = true;
+        }
+        if let Some(temperature) = weather.temperature.as_mut() {
+            changed |= ui.add(DragValue::new(temperature).range(-100.0..=100.0).speed(0.5).suffix("°C")).changed();
+        }
+    });
+    changed |= ui.add(TextEdit::singleline(&mut weather.note)
+        .hint_text(t!("gui.weather.note"))
+        .desired_width(100.)).changed();
+    changed
+}
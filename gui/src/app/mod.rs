@@ -1,2 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod app;
-mod component;
\ No newline at end of file
+mod action;
+mod component;
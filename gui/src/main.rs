@@ -2,19 +2,36 @@
 mod app;
 mod service;
 mod model;
-use std::sync::Arc;
 use eframe::{run_native, NativeOptions};
-use eframe::egui::{FontData, FontDefinitions, IconData, ViewportBuilder, Visuals};
-use eframe::epaint::FontFamily;
-use diary_core::base::executor::Executor;
+use eframe::egui::{IconData, ViewportBuilder};
+use diary_core::base::config::Config;
+use diary_core::base::blocking::BlockingDiary;
+use diary_core::base::i18n::set_locale;
+use diary_core::base::notebook::{Notebooks, DEFAULT_NOTEBOOK};
 use crate::app::app::App;
 use crate::service::executor::GuiService;
 
 fn main() -> eframe::Result {// 16x16 图标
-    let dbmgr = diary_core::base::env::open_with_default_database()
-        .expect("Could not open database");
-    let exec = Executor::from(dbmgr);
-    let exec = GuiService::new(exec);
+    let config = Config::load(None).unwrap_or_else(|e| {
+        eprintln!("Could not load config: {}", e);
+        std::process::exit(1);
+    });
+    set_locale(config.locale());
+    let notebooks = Notebooks::default();
+    // 配置了数据库路径时，不属于任何笔记本
+    let notebook = config.database.is_none().then(|| notebooks.default_notebook());
+    let open = async {
+        match &notebook {
+            Some(name) => notebooks.open(name).await,
+            None => diary_core::base::env::open_with_config(&config).await,
+        }
+    };
+    let diary = BlockingDiary::open_with(open).expect("Could not open database");
+    let title = match (&notebook, &config.database) {
+        (Some(name), _) if name != DEFAULT_NOTEBOOK => format!("Diary - {}", name),
+        (None, Some(path)) => format!("Diary - {}", path.display()),
+        _ => "Diary".to_string(),
+    };
     const WIDTH: u32 = 16;
     const HEIGHT: u32 = 16;
 
@@ -37,22 +54,14 @@ fn main() -> eframe::Result {// 16x16 图标
         height: HEIGHT,
     };
     let no = NativeOptions {
-        viewport: ViewportBuilder::default().with_icon(icon),
+        viewport: ViewportBuilder::default().with_icon(icon).with_title(title),
         ..Default::default()
     };
     run_native("Diary", no, Box::new(|x| {
-        x.egui_ctx.set_visuals(Visuals::default());
-        let mut fd = FontDefinitions::default();
-        fd.font_data.insert(
-            "CN-F".to_string(),
-            Arc::new(FontData::from_static(include_bytes!("../assets/LXGWWenKaiMonoLite-Regular.ttf"))),
-        );
         egui_extras::install_image_loaders(&x.egui_ctx);
-        fd.families.get_mut(&FontFamily::Monospace).unwrap()
-            .insert(0, "CN-F".to_string());
-        fd.families.get_mut(&FontFamily::Proportional).unwrap()
-            .insert(0, "CN-F".to_string());
-        x.egui_ctx.set_fonts(fd);
-        Ok(Box::new(App::new(exec)))
+        let exec = GuiService::new(diary, x.egui_ctx.clone());
+        let app = App::new(exec, config, notebook);
+        app.apply_appearance(&x.egui_ctx);
+        Ok(Box::new(app))
     }))
 }
\ No newline at end of file
@@ -1,33 +1,159 @@
 //! Gui 当天实体模型
+use std::collections::{BTreeMap, BTreeSet};
 use diary_core::base::date::Date;
-use diary_core::model::{Day, Event};
+use diary_core::model::{Day, Event, MetricValue, Mood, Weather};
 
-pub const DEFAULT_MOOD: f64 = 0.0;
+/// 最近一次保存（或读取）时的内容，用于判断是否有未保存的修改
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    event: String,
+    weather: Weather,
+    mood: Option<Mood>,
+    metrics: BTreeMap<String, MetricValue>,
+    habits: BTreeSet<String>,
+}
 
 pub struct GuiDayState {
     pub(crate) date: Date,
     pub(crate) event: Event,
-    pub(crate) weather: String,
-    pub(crate) mood: f64,
+    pub(crate) weather: Weather,
+    pub(crate) mood: Option<Mood>,
+    pub(crate) metrics: BTreeMap<String, MetricValue>,
+    pub(crate) habits: BTreeSet<String>,
+    saved: Snapshot,
+    /// 数据库中是否已有这一天的记录
+    stored: bool,
+}
+impl GuiDayState {
+    /// 还没有记录的一天，`event`为初始内容（如模板），不算作修改
+    pub fn new(date: Date, event: String) -> Self {
+        let mut day = Self {
+            date,
+            event: event.into(),
+            weather: Weather::default(),
+            mood: None,
+            metrics: BTreeMap::new(),
+            habits: BTreeSet::new(),
+            saved: Snapshot {
+                event: String::new(),
+                weather: Weather::default(),
+                mood: None,
+                metrics: BTreeMap::new(),
+                habits: BTreeSet::new(),
+            },
+            stored: false,
+        };
+        day.discard();
+        day
+    }
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            event: self.event.instruct.clone(),
+            weather: self.weather.clone(),
+            mood: self.mood,
+            metrics: self.metrics.clone(),
+            habits: self.habits.clone(),
+        }
+    }
+    /// 是否有未保存的修改
+    pub fn is_dirty(&self) -> bool {
+        self.snapshot() != self.saved
+    }
+    /// 保存成功后调用，当前内容即为已保存的内容
+    pub fn mark_saved(&mut self) {
+        self.saved = self.snapshot();
+        self.stored = true;
+    }
+    /// 放弃修改，不再视为未保存
+    pub fn discard(&mut self) {
+        self.saved = self.snapshot();
+    }
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+    /// 指标被删除后调用，当前与已保存的内容中都不再有它的取值
+    pub fn forget_metric(&mut self, name: &str) {
+        self.metrics.remove(name);
+        self.saved.metrics.remove(name);
+    }
+    /// 习惯被删除后调用，当前与已保存的内容中都不再有它的打卡
+    pub fn forget_habit(&mut self, name: &str) {
+        self.habits.remove(name);
+        self.saved.habits.remove(name);
+    }
 }
 impl From<Day> for GuiDayState {
     fn from(day: Day) -> Self {
-        Self {
-            date: day.date,
-            event: day.event.clone(),
-            weather: day.weather.unwrap_or_default().to_string(),
-            mood: day.mood.unwrap_or(DEFAULT_MOOD),
-        }
+        let mut state = Self::new(day.date, day.event.instruct);
+        state.weather = day.weather.unwrap_or_default();
+        state.mood = day.mood;
+        state.metrics = day.metrics;
+        state.habits = day.habits;
+        state.mark_saved();
+        state
     }
 }
 impl From<&GuiDayState> for Day {
     fn from(gs: &GuiDayState) -> Self {
-        let day = Day {
+        Day {
             date: gs.date,
             event: gs.event.clone(),
-            weather: Some(gs.weather.clone()),
-            mood: (gs.mood != DEFAULT_MOOD).then(|| gs.mood)
-        };
-        day
+            weather: (!gs.weather.is_empty()).then(|| gs.weather.clone()),
+            mood: gs.mood,
+            metrics: gs.metrics.clone(),
+            habits: gs.habits.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diary_core::model::Condition;
+    use super::*;
+
+    fn date() -> Date {
+        Date::new(2025, 1, 2).unwrap()
+    }
+
+    #[test]
+    fn template_is_not_a_change() {
+        let mut day = GuiDayState::new(date(), "> 模板".to_string());
+        assert!(!day.is_dirty() && !day.is_stored());
+        day.event.instruct.push_str("今天");
+        assert!(day.is_dirty());
+        day.mark_saved();
+        assert!(!day.is_dirty() && day.is_stored());
+    }
+
+    #[test]
+    fn every_field_counts_as_a_change() {
+        let changes: [fn(&mut GuiDayState); 4] = [
+            |d| d.mood = Some(Mood::new(7.).unwrap()),
+            |d| d.weather = Condition::Sunny.into(),
+            |d| { d.metrics.insert("步数".to_string(), MetricValue::Number(1.)); },
+            |d| { d.habits.insert("读书".to_string()); },
+        ];
+        for change in changes {
+            let mut day = GuiDayState::new(date(), "今天".to_string());
+            day.mark_saved();
+            change(&mut day);
+            assert!(day.is_dirty());
+            day.discard();
+            assert!(!day.is_dirty());
+            // 保存后再读出的内容与保存前相同
+            assert!(GuiDayState::from(Day::from(&day)).snapshot() == day.snapshot());
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn forgetting_a_metric_or_habit_is_not_a_change() {
+        let mut day = GuiDayState::new(date(), String::new());
+        day.metrics.insert("步数".to_string(), MetricValue::Number(1.));
+        day.habits.insert("读书".to_string());
+        day.mark_saved();
+        day.forget_metric("步数");
+        day.forget_habit("读书");
+        assert!(!day.is_dirty());
+        assert!(day.metrics.is_empty() && day.habits.is_empty());
+    }
+}
@@ -1,39 +1,324 @@
-use diary_core::base::date::Date;
-use diary_core::base::executor::Executor;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::path::PathBuf;
+use eframe::egui::Context;
+use diary_core::base::blocking::BlockingDiary;
+use diary_core::base::config::WeekStart;
+use diary_core::base::date::{Date, DateRange};
+use diary_core::base::env::open_with_db_file;
 use diary_core::base::error::Error;
+use diary_core::base::notebook::Notebooks;
+use diary_core::model::{Day, Habit, MetricDef};
+use diary_core::storage::MoveMode;
+use diary_core::utils::habits::{HabitProgress, DEFAULT_WINDOW};
+use diary_core::utils::onthisday::Span;
+use diary_core::utils::search::SearchCondition;
+use diary_core::utils::stats::Statistics;
+use diary_core::utils::template::Templates;
 use crate::model::day::GuiDayState;
+use crate::service::worker::{self, Job, JobKind, JobOutput, Report, Request};
 
+/// 正在后台执行的任务及其进度
+pub struct JobStatus {
+    pub kind: JobKind,
+    pub done: usize,
+    /// 为0时总数尚未确定
+    pub total: usize,
+    pub cancelling: bool,
+}
+
+/// 已发给后台线程、还没有取回结果的查询
+pub struct Pending<T>(Receiver<Result<T, Error>>);
+impl<T> Pending<T> {
+    /// 结果已经返回时取出，取出后不再有结果
+    pub fn poll(&self) -> Option<Result<T, Error>> {
+        match self.0.try_recv() {
+            Ok(res) => Some(res),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(stopped())),
+        }
+    }
+}
+
+/// 按参数在后台读取的数据，记下已取回的结果对应的参数
+pub struct Query<K, T> {
+    loaded: Option<K>,
+    pending: Option<(K, Pending<T>)>,
+}
+impl<K, T> Default for Query<K, T> {
+    fn default() -> Self {
+        Self { loaded: None, pending: None }
+    }
+}
+impl<K: PartialEq, T> Query<K, T> {
+    /// 参数为`key`的结果既没有取回，也没有正在读取
+    pub fn needs_load(&self, key: &K) -> bool {
+        self.loaded.as_ref() != Some(key) && self.pending.as_ref().is_none_or(|(k, _)| k != key)
+    }
+    /// 开始读取，之前还没有返回的结果不再需要
+    pub fn start(&mut self, key: K, pending: Pending<T>) {
+        self.pending = Some((key, pending));
+    }
+    /// 取回已经返回的结果，出错时也记为已读取，不反复重试
+    pub fn poll(&mut self) -> Option<Result<T, Error>> {
+        let res = self.pending.as_ref()?.1.poll()?;
+        self.loaded = self.pending.take().map(|(key, _)| key);
+        Some(res)
+    }
+    /// 数据库有变动时调用，正在读取的结果也一并作废
+    pub fn invalidate(&mut self) {
+        self.loaded = None;
+        self.pending = None;
+    }
+}
+
+/// 界面访问数据库的入口，实际的读写都在后台线程中进行
 pub struct GuiService {
-    pub(crate) executor: Executor,
+    requests: Sender<Request>,
+    reports: Receiver<Report>,
+    cancel: Arc<AtomicBool>,
+    job: Option<JobStatus>,
+    /// 查询返回后请求重新绘制，以便及时取回结果
+    ctx: Context,
 }
 
 impl GuiService {
-    pub fn new(executor: Executor) -> Self {
-        Self { executor }
+    pub fn new(diary: BlockingDiary, ctx: Context) -> Self {
+        let (requests, request_rx) = mpsc::channel();
+        let (report_tx, reports) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        worker::spawn(diary, request_rx, report_tx, cancel.clone());
+        Self { requests, reports, cancel, job: None, ctx }
     }
 
-    /// 查询某一天的内容
-    pub fn read_day(&mut self, date: Date) -> Result<Option<GuiDayState>, Error> {
-        async_std::task::block_on(async {
-            Ok(self.executor.conn_mut().read_day(date).await?
-                .map(GuiDayState::from))
+    /// 在后台线程中执行`f`，不等待结果，结果通过返回的[`Pending`]取回
+    ///
+    /// 请求按发送的顺序执行，有后台任务时排在任务之后
+    fn query<T: Send + 'static>(&self, f: impl FnOnce(&mut BlockingDiary) -> Result<T, Error> + Send + 'static) -> Pending<T> {
+        let (tx, rx) = mpsc::channel();
+        let ctx = self.ctx.clone();
+        let call = Box::new(move |diary: &mut BlockingDiary| {
+            let _ = tx.send(f(diary));
+            ctx.request_repaint();
+        });
+        // 后台线程已经退出时发送端随请求一起丢弃，取回结果时得到错误
+        let _ = self.requests.send(Request::Call(call));
+        Pending(rx)
+    }
+
+    /// 在后台线程中执行`f`并等待结果，用于之后的操作取决于结果的写入
+    ///
+    /// 后台任务执行期间界面被进度窗口挡住，不会发出这类请求
+    fn call<T: Send + 'static>(&self, f: impl FnOnce(&mut BlockingDiary) -> Result<T, Error> + Send + 'static) -> Result<T, Error> {
+        self.query(f).0.recv().map_err(|_| stopped())?
+    }
+
+    /// 在后台开始一个读取、导入或导出任务，同一时间只能有一个任务
+    pub fn start(&mut self, job: Job) -> Result<(), Error> {
+        if self.job.is_some() {
+            return Err(Error::InvalidData("Another import or export is still running".to_string()));
+        }
+        let kind = job.kind();
+        self.cancel.store(false, Ordering::Relaxed);
+        self.requests.send(Request::Job(job)).map_err(|_| stopped())?;
+        self.job = Some(JobStatus { kind, done: 0, total: 0, cancelling: false });
+        Ok(())
+    }
+
+    /// 请求取消正在执行的任务，任务会在处理完当前记录后停止
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        if let Some(job) = self.job.as_mut() {
+            job.cancelling = true;
+        }
+    }
+
+    pub fn job(&self) -> Option<&JobStatus> {
+        self.job.as_ref()
+    }
+
+    /// 处理后台线程的报告，任务结束时返回任务类型与结果
+    pub fn poll(&mut self) -> Option<(JobKind, Result<JobOutput, Error>)> {
+        while let Ok(report) = self.reports.try_recv() {
+            match report {
+                Report::Progress(done, total) => if let Some(job) = self.job.as_mut() {
+                    job.done = done;
+                    job.total = total;
+                },
+                Report::Done(res) => return self.job.take().map(|job| (job.kind, res)),
+            }
+        }
+        None
+    }
+
+    /// 关闭当前的数据库并打开另一个笔记本
+    pub fn open_notebook(&mut self, name: String) -> Result<(), Error> {
+        self.call(move |diary| diary.reopen_with(async move { Notebooks::default().open(&name).await }))
+    }
+
+    /// 打开任意位置的数据库文件，不存在时创建
+    pub fn open_db_file(&mut self, file: PathBuf) -> Result<(), Error> {
+        self.call(move |diary| diary.reopen_with(open_with_db_file(file)))
+    }
+
+    /// 新建笔记本，不切换当前的数据库
+    pub fn create_notebook(&mut self, name: String) -> Result<(), Error> {
+        self.call(move |diary| diary.block_on(Notebooks::default().create(&name)).map(drop))
+    }
+
+    /// 读取某一天的内容，还没有写过的日期按模板生成初始内容
+    pub fn load_day(&mut self, notebook: Option<String>, date: Date) -> Pending<GuiDayState> {
+        self.query(move |diary| match diary.read_day(date)? {
+            Some(day) => Ok(day.into()),
+            None => {
+                let template = diary.render_template(&Templates::default(), notebook.as_deref(), date)
+                    .ok().flatten().unwrap_or_default();
+                Ok(GuiDayState::new(date, template))
+            }
         })
     }
 
+    /// 某一天是否已有记录
+    pub fn has_day(&mut self, date: Date) -> Pending<bool> {
+        self.query(move |diary| diary.read_day(date).map(|day| day.is_some()))
+    }
+
     /// 更新某一天的日记内容，如果当天有就覆盖，没有就新增
     pub fn update_day(&mut self, day: &GuiDayState) -> Result<(), Error> {
-        async_std::task::block_on(async {
-            self.executor.conn_mut().add_day(&day.into()).await?;
-            Ok(())
-        })
+        let day: Day = day.into();
+        self.call(move |diary| diary.add_day(&day).map(drop))
+    }
+
+    /// 已定义的指标
+    pub fn metric_defs(&mut self) -> Pending<Vec<MetricDef>> {
+        self.query(|diary| diary.metric_defs())
+    }
+
+    /// 添加或修改指标定义
+    pub fn define_metric(&mut self, def: MetricDef) -> Result<(), Error> {
+        self.call(move |diary| diary.define_metric(&def))
+    }
+
+    /// 删除指标定义及其全部取值
+    pub fn remove_metric(&mut self, name: String) -> Result<(), Error> {
+        self.call(move |diary| diary.remove_metric(&name).map(drop))
+    }
+
+    /// 全部习惯截止到今天的完成情况
+    pub fn habit_progress(&mut self, week_start: WeekStart) -> Pending<Vec<HabitProgress>> {
+        self.query(move |diary| diary.habit_progress(Date::native_time(), DEFAULT_WINDOW, week_start))
+    }
+
+    /// 添加习惯
+    pub fn define_habit(&mut self, habit: Habit) -> Result<(), Error> {
+        self.call(move |diary| diary.define_habit(&habit))
+    }
+
+    /// 删除习惯及其全部打卡
+    pub fn remove_habit(&mut self, name: String) -> Result<(), Error> {
+        self.call(move |diary| diary.remove_habit(&name).map(drop))
+    }
+
+    /// 查询某段时间内（含首尾）的全部内容
+    pub fn read_from_to(&mut self, from: Date, to: Date) -> Pending<Vec<Day>> {
+        self.query(move |diary| diary.read_from_to(from, to))
+    }
+
+    /// 按条件搜索日记，按日期降序
+    pub fn search(&mut self, condition: SearchCondition) -> Pending<Vec<Day>> {
+        self.query(move |diary| diary.search_in_condition(condition))
+    }
+
+    /// 统计范围内的写作情况
+    pub fn statistics(&mut self, range: DateRange, week_start: WeekStart) -> Pending<Statistics> {
+        self.query(move |diary| diary.statistics(range, week_start))
+    }
+
+    /// 往年同一天（或同一周、同一月）的记录
+    pub fn on_this_day(&mut self, date: Date, span: Span) -> Pending<Vec<(i32, Day)>> {
+        self.query(move |diary| diary.on_this_day(date, span))
     }
 
     /// 删除某一天的日记
     pub fn delete_day(&mut self, date: Date) -> Result<(), Error> {
-        async_std::task::block_on(async {
-            self.executor.conn_mut().remove_day(date).await?;
-            Ok(())
-        })
+        self.call(move |diary| diary.remove_day(date).map(drop))
+    }
+
+    /// 把某一天的日记移动到另一天
+    pub fn move_day(&mut self, from: Date, to: Date, mode: MoveMode) -> Result<(), Error> {
+        self.call(move |diary| diary.move_day(from, to, mode).map(drop))
+    }
+
+    /// 把某一天的正文复制到另一天
+    pub fn copy_day(&mut self, from: Date, to: Date, mode: MoveMode) -> Result<(), Error> {
+        self.call(move |diary| diary.copy_day(from, to, mode).map(drop))
+    }
+}
+
+fn stopped() -> Error {
+    Error::InvalidData("Database worker stopped".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use diary_core::utils::io::format::Format;
+    use diary_core::utils::io::import::DuplicateStrategy;
+    use super::*;
+
+    fn day(d: u8, text: &str) -> Day {
+        Day::from(&GuiDayState::new(Date::new(2025, 1, d).unwrap(), text.to_string()))
     }
 
+    /// 等待查询返回
+    fn wait<T>(pending: Pending<T>) -> Result<T, Error> {
+        pending.0.recv().unwrap()
+    }
+
+    #[test]
+    fn query_keeps_only_the_latest_request() {
+        let mut query = Query::<u8, u8>::default();
+        let (old_tx, old_rx) = mpsc::channel();
+        let (new_tx, new_rx) = mpsc::channel();
+        assert!(query.needs_load(&1));
+        query.start(1, Pending(old_rx));
+        assert!(!query.needs_load(&1) && query.needs_load(&2));
+        query.start(2, Pending(new_rx));
+        // 被替换的请求返回的结果不再被取回
+        let _ = old_tx.send(Ok(1));
+        assert!(query.poll().is_none());
+        new_tx.send(Err(stopped())).unwrap();
+        assert!(query.poll().unwrap().is_err());
+        // 出错也记为已读取
+        assert!(!query.needs_load(&2));
+        query.invalidate();
+        assert!(query.needs_load(&2));
+    }
+
+    #[test]
+    fn requests_run_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let diary = BlockingDiary::open(dir.path().join("diary.db")).unwrap();
+        let mut service = GuiService::new(diary, Context::default());
+
+        // 后台任务执行期间发出的查询排在任务之后
+        service.start(Job::Import { days: vec![day(1, "一"), day(2, "二")], strategy: DuplicateStrategy::Fail }).unwrap();
+        assert!(service.start(Job::Export { file: dir.path().join("out.json"), format: Format::Json }).is_err());
+        let pending = service.read_from_to(Date::new(2025, 1, 1).unwrap(), Date::new(2025, 1, 31).unwrap());
+        assert_eq!(wait(pending).unwrap().len(), 2);
+        let (kind, output) = loop {
+            if let Some(done) = service.poll() { break done }
+        };
+        assert_eq!(kind, JobKind::Import);
+        assert!(matches!(output, Ok(JobOutput::Imported(summary, None)) if summary.added == 2));
+        assert!(service.job().is_none());
+
+        // 查询能看到之前的写入
+        service.update_day(&GuiDayState::from(day(3, "三"))).unwrap();
+        let loaded = wait(service.load_day(None, Date::new(2025, 1, 3).unwrap())).unwrap();
+        assert_eq!(loaded.event.instruct, "三");
+        assert!(loaded.is_stored() && !loaded.is_dirty());
+        service.delete_day(Date::new(2025, 1, 3).unwrap()).unwrap();
+        assert!(!wait(service.has_day(Date::new(2025, 1, 3).unwrap())).unwrap());
+    }
 }
@@ -1 +1,2 @@
-pub mod executor;
\ No newline at end of file
+pub mod executor;
+pub mod worker;
@@ -0,0 +1,143 @@
+//! 后台线程：持有数据库连接，通过通道接收请求，并把结果与进度报告给界面
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+use diary_core::base::blocking::BlockingDiary;
+use diary_core::base::date::Date;
+use diary_core::base::error::Error;
+use diary_core::base::executor::Executor;
+use diary_core::t;
+use diary_core::utils::io::export::Exporter;
+use diary_core::utils::io::format::Format;
+use diary_core::model::Day;
+use diary_core::utils::io::import::{DuplicateStrategy, ImportSummary, Importer};
+
+/// 在后台线程中执行、可以直接访问数据库的调用
+pub(crate) type Call = Box<dyn FnOnce(&mut BlockingDiary) + Send>;
+
+/// 耗时较长、可以显示进度与取消的任务
+pub enum Job {
+    /// 读取要导入的文件，格式为`None`时按扩展名或内容推断
+    Read {
+        file: PathBuf,
+        format: Option<Format>,
+    },
+    /// 导入已经从文件中读取的记录
+    Import {
+        days: Vec<Day>,
+        strategy: DuplicateStrategy,
+    },
+    Export {
+        file: PathBuf,
+        format: Format,
+    },
+}
+impl Job {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            Job::Read { .. } => JobKind::Read,
+            Job::Import { .. } => JobKind::Import,
+            Job::Export { .. } => JobKind::Export,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum JobKind {
+    Read,
+    Import,
+    Export,
+}
+impl JobKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobKind::Read => t!("gui.job.reading"),
+            JobKind::Import => t!("gui.job.importing"),
+            JobKind::Export => t!("gui.job.exporting"),
+        }
+    }
+}
+
+/// 导入向导预览所需的内容
+pub struct ImportPreview {
+    pub file: PathBuf,
+    pub format: Format,
+    pub days: Vec<Day>,
+    /// 无法读取的记录或文件的错误
+    pub errors: Vec<String>,
+    /// 数据库中已有记录的日期
+    pub existing: HashSet<Date>,
+}
+
+/// 任务完成后的结果
+pub enum JobOutput {
+    Read(ImportPreview),
+    /// 导入后各类记录的数量，中途失败或取消时附带原因，数量为此前已写入的
+    Imported(ImportSummary, Option<Error>),
+    Exported,
+}
+
+pub(crate) enum Request {
+    Call(Call),
+    Job(Job),
+}
+
+pub(crate) enum Report {
+    /// (已完成, 总数)，总数为0时表示尚未确定
+    Progress(usize, usize),
+    /// 任务结束
+    Done(Result<JobOutput, Error>),
+}
+
+/// 启动后台线程，`requests`的发送端全部关闭后线程退出
+pub(crate) fn spawn(mut diary: BlockingDiary, requests: Receiver<Request>, reports: Sender<Report>, cancel: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        for request in requests {
+            match request {
+                Request::Call(call) => call(&mut diary),
+                Request::Job(job) => {
+                    let res = diary.run(async |executor| run(executor, job, &reports, &cancel).await);
+                    let _ = reports.send(Report::Done(res));
+                }
+            }
+        }
+    });
+}
+
+async fn run(executor: &mut Executor, job: Job, reports: &Sender<Report>, cancel: &AtomicBool) -> Result<JobOutput, Error> {
+    let progress = |done, total| {
+        let _ = reports.send(Report::Progress(done, total));
+        !cancel.load(Ordering::Relaxed)
+    };
+    match job {
+        Job::Read { file, format } => {
+            let _ = reports.send(Report::Progress(0, 0));
+            let format = format.unwrap_or_else(|| Format::detect(&file).unwrap_or(Format::Json));
+            let (days, errors) = Importer::read_from_file(&file, format)
+                .unwrap_or_else(|e| (Vec::new(), vec![e.to_string()]));
+            let existing = executor.conn_mut().read_dates().await?.into_iter().collect();
+            if cancel.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled);
+            }
+            Ok(JobOutput::Read(ImportPreview { file, format, days, errors, existing }))
+        }
+        Job::Import { days, strategy } => {
+            let _ = reports.send(Report::Progress(0, 0));
+            let res = Importer::new(executor.conn_mut())
+                .import_with_progress(days, strategy, progress).await;
+            Ok(match res {
+                Ok(summary) => JobOutput::Imported(summary, None),
+                Err((summary, e)) => JobOutput::Imported(summary, Some(e)),
+            })
+        }
+        Job::Export { file, format } => {
+            let _ = reports.send(Report::Progress(0, 0));
+            Exporter::new(executor.conn_mut(), file, format)
+                .all_export_with_progress(progress).await?;
+            Ok(JobOutput::Exported)
+        }
+    }
+}